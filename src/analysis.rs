@@ -0,0 +1,906 @@
+use crate::config::{
+    Config, DEFAULT_TSC_TIMEOUT_SECS, EXTENSIONS, IGNORE_FOLDERS, LENIENT_MANIFEST_PATHS,
+    NODE_TYPES_PACKAGE, PACKAGE_JSON_PATH, load_config_with_warnings, tsconfig_path_aliases,
+};
+use crate::dependency::{
+    declared_packages, find_orphaned_types, find_stale_overrides, get_required_dependencies,
+    is_lockfile_readable, is_types_of_used_package, malformed_dependency_fields,
+    manifest_config_packages, package_sizes, read_cnpignore_sections, read_jsonc,
+    read_package_json, script_preloaded_packages, script_referenced_packages,
+    tooling_config_packages, unlocked_dependencies,
+};
+use crate::file_scanner::{IgnoreReason, ScanResult, ScanStats, scan_files};
+use crate::package_manager::package_manager_ls;
+use crate::progress::Progress;
+use crate::resolve::{case_mismatched_package, is_node_builtin, run_resolver_script};
+use crate::warnings::Warnings;
+use crate::workspace::{hoisted_packages, workspace_root};
+use colored::*;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// The result of analyzing a project's dependency usage.
+#[derive(Debug, Clone, Default)]
+pub struct Analysis {
+    /// Dependencies declared in the `dependencies` field of `package.json`.
+    pub dependencies: HashSet<String>,
+    /// Declared dependencies found in use by the scanned files.
+    pub used_packages: HashSet<String>,
+    /// Declared dependencies that are not used, required, ignored, side-effect-only or the types of a
+    /// used package, sorted by name.
+    pub unused_dependencies: Vec<String>,
+    /// Declared `devDependencies` that are neither imported nor run from a script, sorted by name.
+    /// These findings are informational: dev tooling is often loaded from configuration files.
+    pub unused_dev_dependencies: Vec<String>,
+    /// Imported packages that are not declared anywhere in `package.json`, sorted by name.
+    pub missing_dependencies: Vec<String>,
+    /// Imported packages that differ only by case from a declared package, mapped to the declared
+    /// name. These are not reported as missing.
+    pub case_mismatches: BTreeMap<String, String>,
+    /// Declared `@types/*` packages whose base package is no longer declared, sorted by name.
+    pub orphaned_types: Vec<String>,
+    /// Unused dependencies declaring install lifecycle scripts, only filled by `--audit-scripts`.
+    pub lifecycle_scripts: BTreeMap<String, Vec<String>>,
+    /// Normalized paths of the files that were scanned, sorted.
+    pub explored_files: Vec<String>,
+    /// Normalized paths of the files or directories that were skipped by ignore rules, sorted.
+    pub ignored_files: Vec<String>,
+    /// The rule that skipped each ignored path, keyed by normalized path.
+    pub ignore_reasons: BTreeMap<String, IgnoreReason>,
+    /// Normalized paths of the files referencing each used dependency.
+    pub dependency_files: BTreeMap<String, Vec<String>>,
+    /// Days since the last commit touching a file that uses each dependency, only filled by
+    /// `--git-age`.
+    pub git_ages: BTreeMap<String, u64>,
+    /// Size in bytes of the `node_modules` directory of each installed unused dependency.
+    pub package_sizes: BTreeMap<String, u64>,
+    /// Packages resolved to several versions in the lockfile, only filled by `--dedupe-check`.
+    pub duplicate_versions: BTreeMap<String, Vec<String>>,
+    /// Script commands that no installed dependency provides, mapped to the scripts running them,
+    /// only filled by `--bin`.
+    pub missing_script_commands: BTreeMap<String, Vec<String>>,
+    /// `package.json` entry points (`main`, `types`, `exports`, ...) mapped to their missing target
+    /// file, only filled by `--check-entrypoints`.
+    pub broken_entry_points: BTreeMap<String, String>,
+    /// Groups of packages serving the same purpose that are imported together (e.g., `moment` and
+    /// `dayjs`), only filled by `--check-duplicates-in-code`.
+    pub duplicate_purposes: Vec<Vec<String>>,
+    /// Warnings raised during the analysis, in the order they were raised.
+    pub warnings: Warnings,
+    /// Time spent in each phase of the analysis, only recorded by `--profile`.
+    pub timings: Option<Timings>,
+    /// Counters describing what the scan covered, only recorded by `--stats`.
+    pub stats: Option<ScanStats>,
+}
+
+impl Analysis {
+    /// Checks whether the project declares dependencies but no source file was scanned.
+    ///
+    /// This usually means a wrong project path or ignore rules skipping every file, so no
+    /// dependency is reported as unused (see [`analyze`]).
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if `explored_files` is empty while `dependencies` is not, `false` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let analysis = Analysis {
+    ///     dependencies: ["react".to_string()].into_iter().collect(),
+    ///     ..Analysis::default()
+    /// };
+    /// assert!(analysis.is_empty_scan());
+    /// ```
+    pub fn is_empty_scan(&self) -> bool {
+        self.explored_files.is_empty() && !self.dependencies.is_empty()
+    }
+}
+
+/// Time spent in each phase of a project's analysis, serialized in milliseconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Timings {
+    /// Reading `cnp.toml` and the manifest.
+    #[serde(serialize_with = "serialize_millis")]
+    pub manifest: Duration,
+    /// Parsing the lockfiles for required dependencies and stale overrides.
+    #[serde(serialize_with = "serialize_millis")]
+    pub lockfile: Duration,
+    /// Scanning the source files, excluding `tsc`.
+    #[serde(serialize_with = "serialize_millis")]
+    pub scan: Duration,
+    /// Running `tsc` to exclude unused TypeScript imports.
+    #[serde(serialize_with = "serialize_millis")]
+    pub tsc: Duration,
+    /// Preparing and writing the report (the JSON report is timed up to its serialization).
+    #[serde(serialize_with = "serialize_millis")]
+    pub report: Duration,
+}
+
+impl Timings {
+    /// Returns every phase with its duration, in the order the phases run.
+    pub fn phases(&self) -> [(&'static str, Duration); 5] {
+        [
+            ("manifest", self.manifest),
+            ("lockfile", self.lockfile),
+            ("scan", self.scan),
+            ("tsc", self.tsc),
+            ("report", self.report),
+        ]
+    }
+}
+
+/// Serializes a duration as a number of milliseconds, with microsecond precision.
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_micros() as f64 / 1000.0)
+}
+
+/// What to analyze and how, built from [`AnalyzeOptions::new`] and its setters.
+///
+/// The defaults match the command-line tool, except that `tsc` is not run unless enabled with
+/// [`AnalyzeOptions::use_tsc`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalyzeOptions {
+    /// The path to the project directory.
+    pub root: String,
+    /// Extensions of the files to scan, without the leading dot.
+    pub extensions: Vec<String>,
+    /// Folder names whose contents are never scanned (e.g., `node_modules`).
+    pub ignore_folders: Vec<String>,
+    /// Vendored folder names or paths whose imports never count as usage, in addition to the
+    /// `vendored` setting of `cnp.toml`.
+    pub vendored_folders: Vec<String>,
+    /// Path globs, relative to the project directory, whose files are never scanned, in addition
+    /// to the `[paths]` section of `.cnpignore`.
+    pub ignore_paths: Vec<String>,
+    /// The `package.json` fields (buckets) whose packages are checked for usage.
+    pub dependency_fields: Vec<String>,
+    /// If `true`, runs `tsc` to exclude unused TypeScript imports.
+    pub use_tsc: bool,
+    /// The maximum total time `tsc` may run before TypeScript imports are matched like JavaScript ones.
+    pub tsc_timeout: Duration,
+    /// If `true`, reuses the `tsc` diagnostics cached while the TypeScript files are unchanged.
+    pub cache: bool,
+    /// The maximum number of directory levels to scan, or `None` for no limit.
+    pub max_depth: Option<usize>,
+    /// The names of the packages to analyze, or empty to analyze every package.
+    pub packages: Vec<String>,
+    /// If `true`, records the time spent in each phase in `Analysis::timings`.
+    pub profile: bool,
+    /// If `true`, records the scan counters in `Analysis::stats`.
+    pub stats: bool,
+    /// If `true`, honors the `cnp-keep`, `cnp-ignore-next-line` and `cnp-disable` comments of the
+    /// scanned files (see [`crate::file_scanner::apply_pragmas`]).
+    pub pragmas: bool,
+    /// The number of threads reading and matching source files. Results are identical for any
+    /// count; `1` (the default) runs the whole analysis on the calling thread.
+    pub threads: usize,
+    /// If `true`, only the dependencies of production lockfile entries make a package required,
+    /// leaving out the declared dependencies and development-only entries (npm `dev` flags, pnpm
+    /// `devDependencies`).
+    pub ignore_dev: bool,
+    /// If `true`, `@types/node` is reported as unused like any other package, whatever the
+    /// `keep_types_node` setting of `cnp.toml`.
+    pub report_types_node: bool,
+    /// An external script resolving the imports whose packages are not declared, or `None`.
+    /// See [`run_resolver_script`] for its line protocol.
+    pub resolver_script: Option<String>,
+    /// If `true`, asks the package manager for the installed packages (`npm ls`, `pnpm ls`) when
+    /// the lockfile is missing or cannot be parsed.
+    pub use_pm_ls: bool,
+    /// An explicit list of dependencies to analyze in place of the manifest, or `None` to read
+    /// `package.json`. With a list, neither the manifest nor the lockfile is read.
+    pub dependency_list: Option<Vec<String>>,
+    /// The lockfile to read (e.g., `yarn.lock`), or `None` to detect the lockfile of the project
+    /// directory.
+    pub lockfile: Option<String>,
+    /// If `true`, only the manifest and the lockfile are read: no file is scanned, so no
+    /// dependency is reported as unused or missing, and declared dependencies missing from the
+    /// lockfile are reported as warnings.
+    pub manifest_only: bool,
+}
+
+impl AnalyzeOptions {
+    /// Creates the default options for analyzing the project in the given directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The path to the project directory.
+    ///
+    /// # Returns
+    ///
+    /// Returns `AnalyzeOptions` scanning the default extensions outside the default ignored
+    /// folders, checking the `dependencies` field, without running `tsc`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let options = AnalyzeOptions::new("./app")
+    ///     .extensions(["js", "vue"])
+    ///     .dependency_fields(["dependencies", "peerDependencies"])
+    ///     .max_depth(Some(5));
+    /// let analysis = analyze(&options, &NoProgress)?;
+    /// ```
+    pub fn new(root: impl Into<String>) -> Self {
+        AnalyzeOptions {
+            root: root.into(),
+            extensions: EXTENSIONS.iter().map(|s| s.to_string()).collect(),
+            ignore_folders: IGNORE_FOLDERS.iter().map(|s| s.to_string()).collect(),
+            vendored_folders: Vec::new(),
+            ignore_paths: Vec::new(),
+            dependency_fields: vec!["dependencies".to_string()],
+            use_tsc: false,
+            tsc_timeout: Duration::from_secs(DEFAULT_TSC_TIMEOUT_SECS),
+            cache: true,
+            max_depth: None,
+            packages: Vec::new(),
+            profile: false,
+            stats: false,
+            pragmas: false,
+            threads: 1,
+            ignore_dev: false,
+            report_types_node: false,
+            resolver_script: None,
+            use_pm_ls: false,
+            dependency_list: None,
+            lockfile: None,
+            manifest_only: false,
+        }
+    }
+
+    /// Sets the extensions of the files to scan, without the leading dot.
+    pub fn extensions<S: Into<String>>(mut self, extensions: impl IntoIterator<Item = S>) -> Self {
+        self.extensions = extensions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the folder names whose contents are never scanned.
+    pub fn ignore_folders<S: Into<String>>(mut self, folders: impl IntoIterator<Item = S>) -> Self {
+        self.ignore_folders = folders.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the vendored folder names or paths skipped in addition to those of `cnp.toml`.
+    pub fn vendored_folders<S: Into<String>>(
+        mut self,
+        folders: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.vendored_folders = folders.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the path globs skipped in addition to the `[paths]` section of `.cnpignore`.
+    pub fn ignore_paths<S: Into<String>>(mut self, paths: impl IntoIterator<Item = S>) -> Self {
+        self.ignore_paths = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the `package.json` fields whose packages are checked for usage.
+    pub fn dependency_fields<S: Into<String>>(
+        mut self,
+        fields: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.dependency_fields = fields.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets whether `tsc` runs to exclude unused TypeScript imports.
+    pub fn use_tsc(mut self, use_tsc: bool) -> Self {
+        self.use_tsc = use_tsc;
+        self
+    }
+
+    /// Sets the maximum total time `tsc` may run.
+    pub fn tsc_timeout(mut self, timeout: Duration) -> Self {
+        self.tsc_timeout = timeout;
+        self
+    }
+
+    /// Sets whether the `tsc` diagnostics are cached and reused while the inputs are unchanged.
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Sets the maximum number of directory levels to scan, or `None` for no limit.
+    pub fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets the number of threads reading and matching source files (at least one).
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Sets whether the time spent in each phase is recorded.
+    pub fn profile(mut self, profile: bool) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Sets whether the scan counters are recorded.
+    pub fn stats(mut self, stats: bool) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Sets whether the `cnp-*` pragma comments of the scanned files are honored.
+    pub fn pragmas(mut self, pragmas: bool) -> Self {
+        self.pragmas = pragmas;
+        self
+    }
+
+    /// Sets whether only the dependencies of production lockfile entries make a package required.
+    pub fn ignore_dev(mut self, ignore_dev: bool) -> Self {
+        self.ignore_dev = ignore_dev;
+        self
+    }
+
+    /// Sets whether `@types/node` is reported as unused like any other package.
+    pub fn report_types_node(mut self, report_types_node: bool) -> Self {
+        self.report_types_node = report_types_node;
+        self
+    }
+
+    /// Sets the external script resolving imported packages that are not declared.
+    pub fn resolver_script(mut self, script: Option<String>) -> Self {
+        self.resolver_script = script;
+        self
+    }
+
+    /// Sets whether the package manager lists the installed packages when the lockfile cannot be
+    /// read.
+    pub fn use_pm_ls(mut self, use_pm_ls: bool) -> Self {
+        self.use_pm_ls = use_pm_ls;
+        self
+    }
+
+    /// Sets an explicit list of dependencies analyzed in place of those of the manifest.
+    pub fn dependency_list(mut self, dependencies: Option<Vec<String>>) -> Self {
+        self.dependency_list = dependencies;
+        self
+    }
+
+    /// Sets the lockfile to read, or `None` to detect it.
+    pub fn lockfile(mut self, lockfile: Option<String>) -> Self {
+        self.lockfile = lockfile;
+        self
+    }
+
+    /// Sets whether only the manifest and the lockfile are read, without scanning files.
+    pub fn manifest_only(mut self, manifest_only: bool) -> Self {
+        self.manifest_only = manifest_only;
+        self
+    }
+
+    /// Restricts the analysis to the named packages; an empty list analyzes every package.
+    pub fn packages<S: Into<String>>(mut self, packages: impl IntoIterator<Item = S>) -> Self {
+        self.packages = packages.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// Analyzes the dependency usage of the project in the given directory.
+///
+/// This function reads `package.json`, scans the project files for dependency usage (including
+/// packages preloaded with Node.js flags in `package.json` scripts), and compares used and declared
+/// dependencies to find unused ones, respecting required, ignored and side-effect-only dependencies
+/// (including the `runtime_required` packages of `cnp.toml`). An `@types/*` package is never
+/// reported as unused while its base package is used, and `@types/node`, which the compiler loads
+/// without an import, never is, unless `keep_types_node` is off in `cnp.toml` or
+/// [`AnalyzeOptions::report_types_node`] is set. The `node_modules` size of each installed unused
+/// dependency is measured. Imported packages that are not declared in any dependency field of `package.json` (or
+/// resolved by a `tsconfig.json` path alias) are reported as missing, except those differing only
+/// by case from a declared package, which are warned about instead, and, in a workspace package,
+/// those the monorepo root declares or locks (see [`hoisted_packages`]). Unused `devDependencies`
+/// are reported separately, for information only; packages named in tooling directories such as
+/// `.storybook` and `.husky`, or as GraphQL Code Generator plugins and presets in `codegen.*`,
+/// count as used devDependencies. A warning is recorded for each `overrides`/`resolutions` entry
+/// whose package is no longer part of the dependency tree. Warnings are collected in
+/// `Analysis::warnings` rather than printed. Companion packages of declared frameworks (see
+/// [`Config::framework_companions`]) count as used. When no source file is scanned although
+/// dependencies are declared, nothing is reported as unused and a warning points at the project
+/// path and ignore rules instead. When `packages` is not empty, only the named packages are
+/// analyzed: every other declared or imported package is left out of the analysis and its results.
+///
+/// # Arguments
+///
+/// * `options` - A reference to the `AnalyzeOptions` naming the project directory and how to scan it.
+/// * `progress` - Receives progress updates (an `indicatif` spinner, or `NoProgress`).
+///
+/// # Returns
+///
+/// Returns `Ok(Analysis)` with the analysis results.
+/// Returns `Err(String)` with an error message if `package.json` cannot be read or parsed (it is
+/// not read when `dependency_list` is set).
+///
+/// # Examples
+///
+/// ```
+/// let pb = create_spinner("Initializing...");
+/// match analyze(&AnalyzeOptions::new(".").use_tsc(true), &pb) {
+///     Ok(analysis) => println!("Unused: {:?}", analysis.unused_dependencies),
+///     Err(e) => eprintln!("{}", e),
+/// }
+/// ```
+pub fn analyze(options: &AnalyzeOptions, progress: &dyn Progress) -> Result<Analysis, String> {
+    let dir_path = options.root.as_str();
+    let packages = &options.packages;
+    let mut timings = Timings::default();
+    let mut phase_start = Instant::now();
+    let mut warnings = Warnings::default();
+    let config = load_config_with_warnings(dir_path, &mut warnings);
+
+    // Read package.json, unless an explicit dependency list replaces it
+    let package_json = match options.dependency_list {
+        Some(_) => Value::Object(Default::default()),
+        None => read_manifest(dir_path, &config)?,
+    };
+    for message in malformed_dependency_fields(&package_json) {
+        warnings.push(message);
+    }
+    timings.manifest = phase_start.elapsed();
+
+    // Collect dependencies, restricted to the selected packages (fewer patterns to match)
+    let is_selected = |package: &String| packages.is_empty() || packages.contains(package);
+    let dependencies: HashSet<String> = match &options.dependency_list {
+        Some(list) => list
+            .iter()
+            .filter(|dep| is_selected(dep))
+            .cloned()
+            .collect(),
+        None => options
+            .dependency_fields
+            .iter()
+            .filter_map(|field| package_json.get(field).and_then(Value::as_object))
+            .flat_map(|map| map.keys())
+            .filter(|dep| is_selected(dep))
+            .cloned()
+            .collect(),
+    };
+
+    // Scan for used dependencies, skipping vendored third-party code and the `.cnpignore` paths,
+    // and including the extra extensions of `cnp.toml`
+    let mut vendored_folders = config.vendored.clone();
+    vendored_folders.extend(options.vendored_folders.iter().cloned());
+    let cnp_ignore_path = Path::new(dir_path).join(".cnpignore");
+    let cnpignore = read_cnpignore_sections(cnp_ignore_path.to_str().unwrap_or_default());
+    let mut ignore_paths = options.ignore_paths.clone();
+    for pattern in cnpignore.paths {
+        if let Err(e) = glob::Pattern::new(&pattern) {
+            warnings.push(format!(
+                "Ignoring invalid path `{}` in .cnpignore: {}",
+                pattern, e
+            ));
+        } else if !ignore_paths.contains(&pattern) {
+            ignore_paths.push(pattern);
+        }
+    }
+    let mut extensions = options.extensions.clone();
+    for extension in &config.extra_extensions {
+        let extension = extension.trim_start_matches('.').to_string();
+        if !extensions.contains(&extension) {
+            extensions.push(extension);
+        }
+    }
+    let scan_options = AnalyzeOptions {
+        extensions,
+        vendored_folders,
+        ignore_paths,
+        ..options.clone()
+    };
+    phase_start = Instant::now();
+    let mut scan = if options.manifest_only {
+        ScanResult::default()
+    } else {
+        progress.set_message("Scanning files...");
+        scan_files(&scan_options, &dependencies, progress)
+    };
+    timings.tsc = scan.tsc_duration;
+    timings.scan = phase_start.elapsed().saturating_sub(scan.tsc_duration);
+
+    progress.finish(&"Scanning complete!".green().to_string());
+    warnings.extend(std::mem::take(&mut scan.warnings));
+
+    // The package's own name covers its self-references, including every `exports` subpath
+    // (`my-pkg/internal` -> `my-pkg`)
+    let mut declared = declared_packages(&package_json);
+    declared.extend(options.dependency_list.iter().flatten().cloned());
+    if let Some(name) = package_json.get("name").and_then(Value::as_str) {
+        declared.insert(name.to_string());
+    }
+    let path_aliases = tsconfig_path_aliases(dir_path);
+
+    // Framework companions are used through the framework's conventions (`next` -> `sharp`)
+    let framework_companions = config.framework_companions(&declared);
+    scan.used_packages.extend(
+        framework_companions
+            .iter()
+            .filter(|package| dependencies.contains(*package))
+            .cloned(),
+    );
+
+    // Let the resolver script map undeclared imports (custom aliases, monorepo packages) to the
+    // packages they refer to. It gets the specifiers as written, since an alias resolver needs
+    // `@app/ui/button` whole, or the package name of imports without a literal specifier
+    if let Some(script) = &options.resolver_script {
+        let mut specifier_packages = BTreeMap::new();
+        for package in scan
+            .imported_packages
+            .difference(&declared)
+            .filter(|package| !is_node_builtin(package) && !is_path_alias(package, &path_aliases))
+        {
+            match scan.import_specifiers.get(package) {
+                Some(specifiers) => {
+                    for specifier in specifiers {
+                        specifier_packages.insert(specifier.clone(), package.clone());
+                    }
+                }
+                None => {
+                    specifier_packages.insert(package.clone(), package.clone());
+                }
+            }
+        }
+        let specifiers: Vec<String> = specifier_packages.keys().cloned().collect();
+        if !specifiers.is_empty() {
+            match run_resolver_script(script, dir_path, &specifiers) {
+                Ok(resolved) => {
+                    // A package is only replaced once all of its specifiers are resolved
+                    let unresolved: HashSet<&String> = specifier_packages
+                        .iter()
+                        .filter(|(specifier, _)| !resolved.contains_key(*specifier))
+                        .map(|(_, package)| package)
+                        .collect();
+                    for package in specifier_packages.values() {
+                        if !unresolved.contains(package) {
+                            scan.imported_packages.remove(package);
+                        }
+                    }
+                    for package in resolved.into_values().flatten() {
+                        if dependencies.contains(&package) {
+                            scan.used_packages.insert(package.clone());
+                        }
+                        scan.imported_packages.insert(package);
+                    }
+                }
+                Err(e) => warnings.push(format!("{}. Undeclared imports are left unresolved.", e)),
+            }
+        }
+    }
+
+    // Packages preloaded with `-r`/`--require`/`--import` in scripts are used at runtime
+    scan.used_packages.extend(
+        script_preloaded_packages(dir_path, &package_json)
+            .into_iter()
+            .filter(|package| dependencies.contains(package)),
+    );
+
+    // Identify unused dependencies, keeping `@types/node` (unless overridden) and the `@types/*`
+    // packages whose base package is used
+    phase_start = Instant::now();
+    let mut required_deps = match options.dependency_list {
+        Some(_) => HashSet::new(),
+        None => get_required_dependencies(
+            dir_path,
+            options.ignore_dev,
+            options.lockfile.as_deref(),
+            &mut warnings,
+        ),
+    };
+    required_deps.extend(config.runtime_required.iter().cloned());
+    required_deps.extend(
+        dependencies
+            .iter()
+            .filter(|dep| config.is_workspace_package(dep))
+            .cloned(),
+    );
+
+    // The installed tree is authoritative when the lockfile cannot be used
+    if options.use_pm_ls
+        && options.dependency_list.is_none()
+        && !is_lockfile_readable(dir_path, options.lockfile.as_deref())
+    {
+        match package_manager_ls(dir_path) {
+            Ok(installed) => required_deps.extend(installed),
+            Err(e) => warnings.push(e),
+        }
+    }
+
+    // Overrides pin transitive versions: they are never dependencies, but may outlive their target
+    for package in find_stale_overrides(dir_path, &package_json, &required_deps) {
+        warnings.push(format!(
+            "Override of '{}' targets a package that is no longer a dependency.",
+            package
+        ));
+    }
+    timings.lockfile = phase_start.elapsed();
+    let ignored_deps = cnpignore.packages;
+    let keep_types_node = config.keep_types_node && !options.report_types_node;
+    let is_kept_types_node = |dep: &str| keep_types_node && dep == NODE_TYPES_PACKAGE;
+    let mut unused_dependencies: Vec<_> = dependencies
+        .difference(&scan.used_packages)
+        .filter(|dep| {
+            !required_deps.contains(*dep)
+                && !is_kept_types_node(dep)
+                && !ignored_deps.contains(*dep)
+                && !config.side_effect_packages.contains(*dep)
+                && !is_types_of_used_package(dep, &scan.used_packages)
+        })
+        .cloned()
+        .collect();
+    unused_dependencies.sort();
+
+    // Identify unused devDependencies: imported, run from a script, preloaded, or named in a
+    // tooling directory (`.storybook`, `.husky`) or a `package.json` tool config counts as used
+    let dev_dependencies: HashSet<String> = package_json
+        .get("devDependencies")
+        .and_then(Value::as_object)
+        .map_or_else(HashSet::new, |map| {
+            map.keys().filter(|dep| is_selected(dep)).cloned().collect()
+        });
+    let mut dev_used: HashSet<String> = scan.imported_packages.clone();
+    dev_used.extend(scan.used_packages.iter().cloned());
+    dev_used.extend(script_referenced_packages(
+        dir_path,
+        &package_json,
+        &dev_dependencies,
+    ));
+    dev_used.extend(script_preloaded_packages(dir_path, &package_json));
+    dev_used.extend(tooling_config_packages(dir_path, &dev_dependencies));
+    dev_used.extend(manifest_config_packages(&package_json, &dev_dependencies));
+    dev_used.extend(framework_companions);
+    let mut unused_dev_dependencies: Vec<_> = dev_dependencies
+        .difference(&dev_used)
+        .filter(|dep| {
+            !dependencies.contains(*dep)
+                && !is_kept_types_node(dep)
+                && !ignored_deps.contains(*dep)
+                && !config.side_effect_packages.contains(*dep)
+                && !config.runtime_required.contains(*dep)
+                && !config.is_workspace_package(dep)
+                && !is_types_of_used_package(dep, &dev_used)
+        })
+        .cloned()
+        .collect();
+    unused_dev_dependencies.sort();
+
+    // Without any scanned file, every dependency would look unused: a manifest-only run checks the
+    // lockfile instead, and an empty scan is reported
+    if options.manifest_only {
+        unused_dependencies.clear();
+        unused_dev_dependencies.clear();
+        if options.dependency_list.is_none() {
+            // Workspace and local packages (`workspace:*`, `file:../ui`) are not always locked
+            let is_local = |dep: &String| {
+                config.is_workspace_package(dep)
+                    || options
+                        .dependency_fields
+                        .iter()
+                        .chain([&"devDependencies".to_string()])
+                        .filter_map(|field| package_json.get(field)?.get(dep)?.as_str())
+                        .any(|spec| {
+                            ["workspace:", "file:", "link:", "portal:"]
+                                .iter()
+                                .any(|protocol| spec.starts_with(protocol))
+                        })
+            };
+            let mut declared_deps = dependencies.clone();
+            declared_deps.extend(dev_dependencies.iter().cloned());
+            declared_deps.retain(|dep| !is_local(dep));
+            if let Some((lockfile, unlocked)) =
+                unlocked_dependencies(dir_path, options.lockfile.as_deref(), &declared_deps)
+            {
+                for dep in unlocked {
+                    warnings.push(format!(
+                        "'{}' is declared in package.json but missing from {}. Run your package manager's install to update it.",
+                        dep, lockfile
+                    ));
+                }
+            }
+        }
+    } else if scan.explored_files.is_empty() && !dependencies.is_empty() {
+        warnings.push(format!(
+            "No source files were scanned, so none of the {} dependencies is reported as unused. Check the project path and the ignore rules.",
+            dependencies.len()
+        ));
+        unused_dependencies.clear();
+        unused_dev_dependencies.clear();
+    }
+
+    // Identify imported but undeclared dependencies
+    let case_mismatches: BTreeMap<String, String> = scan
+        .imported_packages
+        .iter()
+        .filter_map(|package| {
+            let declared_name = case_mismatched_package(package, &declared)?;
+            Some((package.clone(), declared_name.to_string()))
+        })
+        .filter(|(_, declared_name)| is_selected(declared_name))
+        .collect();
+    for (package, declared_name) in &case_mismatches {
+        warnings.push(format!(
+            "'{}' is imported but the dependency is declared as '{}'. This breaks on case-sensitive filesystems.",
+            package, declared_name
+        ));
+    }
+    // Packages hoisted from the monorepo root are installed even though they are not declared here
+    let hoisted = workspace_root(dir_path)
+        .map(|root| hoisted_packages(&root))
+        .unwrap_or_default();
+    let mut missing_dependencies: Vec<_> = scan
+        .imported_packages
+        .difference(&declared)
+        .filter(|package| {
+            is_selected(package)
+                && !is_node_builtin(package)
+                && !is_path_alias(package, &path_aliases)
+                && !case_mismatches.contains_key(*package)
+                && !hoisted.contains(*package)
+        })
+        .cloned()
+        .collect();
+    missing_dependencies.sort();
+    let package_sizes = package_sizes(dir_path, &unused_dependencies);
+
+    Ok(Analysis {
+        dependencies,
+        used_packages: scan.used_packages,
+        unused_dependencies,
+        unused_dev_dependencies,
+        missing_dependencies,
+        case_mismatches,
+        orphaned_types: find_orphaned_types(&package_json)
+            .into_iter()
+            .filter(is_selected)
+            .collect(),
+        lifecycle_scripts: BTreeMap::new(),
+        explored_files: scan.explored_files,
+        ignored_files: scan.ignored_files,
+        ignore_reasons: scan.ignore_reasons,
+        dependency_files: scan.dependency_files,
+        git_ages: BTreeMap::new(),
+        package_sizes,
+        duplicate_versions: BTreeMap::new(),
+        missing_script_commands: BTreeMap::new(),
+        broken_entry_points: BTreeMap::new(),
+        duplicate_purposes: Vec::new(),
+        warnings,
+        timings: options.profile.then_some(timings),
+        stats: options.stats.then_some(scan.stats),
+    })
+}
+
+/// Reads the project's `package.json`, honoring the `strict_manifest` setting of `cnp.toml`.
+///
+/// When `package.json` is absent, a `package.json5` or `package.jsonc` manifest is read instead,
+/// always leniently (comments and trailing commas allowed).
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `config` - A reference to the project's `Config`.
+///
+/// # Returns
+///
+/// Returns `Ok(Value)` containing the parsed manifest.
+/// Returns `Err(String)` with an error message if `package.json` cannot be read or parsed.
+///
+/// # Examples
+///
+/// ```
+/// let package_json = read_manifest(".", &load_config("."))?;
+/// println!("Name: {}", package_json["name"]);
+/// ```
+pub fn read_manifest(dir_path: &str, config: &Config) -> Result<Value, String> {
+    let package_json_path = Path::new(dir_path).join(PACKAGE_JSON_PATH);
+    if !package_json_path.exists() {
+        let lenient_manifest = LENIENT_MANIFEST_PATHS
+            .iter()
+            .map(|file_name| Path::new(dir_path).join(file_name))
+            .find(|path| path.is_file());
+        if let Some(path) = lenient_manifest {
+            return read_jsonc(path.to_str().unwrap_or_default());
+        }
+    }
+
+    let package_json_path = package_json_path.to_str().unwrap_or_default();
+    if config.strict_manifest {
+        read_package_json(package_json_path)
+    } else {
+        read_jsonc(package_json_path)
+    }
+}
+
+/// Checks whether an imported package name is actually a `tsconfig.json` path alias.
+///
+/// # Arguments
+///
+/// * `package` - The package name extracted from an import specifier.
+/// * `path_aliases` - The alias prefixes returned by `tsconfig_path_aliases`.
+///
+/// # Returns
+///
+/// Returns `true` if the package name matches one of the alias prefixes, `false` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// let aliases = vec!["@/".to_string(), "~/".to_string()];
+/// assert!(is_path_alias("@/components", &aliases));
+/// assert!(is_path_alias("~", &aliases));
+/// assert!(!is_path_alias("lodash", &aliases));
+/// ```
+fn is_path_alias(package: &str, path_aliases: &[String]) -> bool {
+    path_aliases
+        .iter()
+        .any(|alias| package == alias.trim_end_matches('/') || package.starts_with(alias.as_str()))
+}
+
+/// Which unused dependencies make a run fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailPolicy {
+    /// Fail on any unused `dependencies` or `devDependencies`.
+    All,
+    /// Fail only on unused production `dependencies`; unused `devDependencies` stay informational.
+    Production,
+}
+
+impl fmt::Display for FailPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailPolicy::All => write!(f, "all"),
+            FailPolicy::Production => write!(f, "production"),
+        }
+    }
+}
+
+impl FromStr for FailPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(FailPolicy::All),
+            "production" => Ok(FailPolicy::Production),
+            _ => Err(format!("Error: Unknown fail policy `{}`.", s)),
+        }
+    }
+}
+
+impl FailPolicy {
+    /// Checks whether an analysis has findings that fail the run under this policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `analysis` - A reference to the project's `Analysis`.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the analysis has unused dependencies in a failing bucket, `false` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let analysis = Analysis {
+    ///     unused_dev_dependencies: vec!["eslint".to_string()],
+    ///     ..Analysis::default()
+    /// };
+    /// assert!(FailPolicy::All.fails(&analysis));
+    /// assert!(!FailPolicy::Production.fails(&analysis));
+    /// ```
+    pub fn fails(&self, analysis: &Analysis) -> bool {
+        match self {
+            FailPolicy::All => {
+                !analysis.unused_dependencies.is_empty()
+                    || !analysis.unused_dev_dependencies.is_empty()
+            }
+            FailPolicy::Production => !analysis.unused_dependencies.is_empty(),
+        }
+    }
+}
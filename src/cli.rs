@@ -0,0 +1,1530 @@
+use crate::analysis::{Analysis, AnalyzeOptions, FailPolicy, analyze, read_manifest};
+use crate::compare::{
+    ReportDiff, diff_reports, previous_project_report, read_previous_report, write_report_diff,
+};
+use crate::config::{
+    DEFAULT_TSC_TIMEOUT_SECS, EffectiveConfig, Flags, LENIENT_MANIFEST_PATHS, PACKAGE_JSON_PATH,
+    load_config,
+};
+use crate::dependency::{
+    LOCKFILES, audit_lifecycle_scripts, broken_entry_points, duplicate_versions,
+    find_dependency_line, find_orphaned_types, missing_script_commands, read_dependency_list,
+};
+use crate::file_scanner::is_tsc_available;
+use crate::git::dependency_ages;
+use crate::init::init_project;
+use crate::report::{
+    JsonEntry, JsonError, JsonReport, JunitCase, Metric, OutputFormat, ProjectSummary,
+    ReportSections, RunCounts, Summary, SummaryTemplate, TomlProjects, format_count, junit_cases,
+    manifest_display_path, write_dependency_report, write_github_annotations, write_github_command,
+    write_html_report, write_ignored_files, write_junit_report, write_orphaned_types, write_stats,
+    write_timings, write_toml_report, write_warnings,
+};
+use crate::tarball::extract_tarball;
+use crate::uninstall::{
+    ReinstallOptions, ReinstallPolicy, handle_unused_dependencies, write_removal_plan,
+};
+use crate::utils::{ColorChoice, create_spinner, is_ci};
+use crate::workspace::workspace_packages;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use colored::*;
+use indicatif::ProgressBar;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Options of a run, parsed from the command-line arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    /// Project directories to analyze, in the order given.
+    pub paths: Vec<String>,
+    pub dry_run: bool,
+    pub interactive: bool,
+    /// Start the interactive prompt with every unused dependency selected.
+    pub preselect_all: bool,
+    /// When `node_modules` is reinstalled after unused dependencies are removed.
+    pub reinstall: ReinstallPolicy,
+    /// Reinstall with the package manager's frozen-lockfile install (`npm ci`).
+    pub frozen_install: bool,
+    pub format: OutputFormat,
+    /// When the output is colored.
+    pub color: ColorChoice,
+    pub count: bool,
+    pub print_config: bool,
+    pub init: bool,
+    pub force: bool,
+    pub types_only: bool,
+    pub audit_scripts: bool,
+    /// Skip `tsc` and match TypeScript imports like JavaScript ones.
+    pub no_tsc: bool,
+    /// Run `tsc` even in CI, where it is skipped by default.
+    pub tsc: bool,
+    /// Seconds `tsc` may run before falling back to matching TypeScript imports like JavaScript ones.
+    pub tsc_timeout: u64,
+    /// Run `tsc` even when its cached diagnostics are up to date.
+    pub no_cache: bool,
+    /// An external script resolving the imported packages that are not declared.
+    pub resolver_script: Option<String>,
+    /// Also write the JSON report to the summary writer (stderr) alongside the console report.
+    pub summary_json_stderr: bool,
+    pub explain_ignored: bool,
+    /// Silence the warnings raised during the analysis, in every output format.
+    pub no_warnings: bool,
+    /// Additional reports written to files, as `(format, path)` pairs from `--report-file`.
+    pub report_files: Vec<(OutputFormat, String)>,
+    /// Record and print the time spent in each analysis phase.
+    pub profile: bool,
+    /// Record and print the scan counters (files per extension, bytes read, imports found).
+    pub stats: bool,
+    /// Honor the `cnp-keep`, `cnp-ignore-next-line` and `cnp-disable` comments of source files.
+    pub scan_comments_for_pragmas: bool,
+    /// The number of threads reading and matching source files.
+    pub threads: usize,
+    /// Fail the run when more warnings than this are raised across every project.
+    pub max_warnings: Option<usize>,
+    /// Re-run the analysis on file changes. Unused dependencies are never removed in this mode.
+    pub watch: bool,
+    pub git_age: bool,
+    pub dedupe_check: bool,
+    /// Report script commands that no installed dependency provides.
+    pub bin: bool,
+    /// Report `package.json` entry points whose target file does not exist.
+    pub check_entrypoints: bool,
+    /// Report groups of packages serving the same purpose that are imported together.
+    pub check_duplicates_in_code: bool,
+    /// Only count production lockfile entries as required packages.
+    pub ignore_dev: bool,
+    /// Report `@types/node` as unused like any other package, overriding `keep_types_node`.
+    pub report_types_node: bool,
+    /// Ask the package manager for the installed packages when the lockfile cannot be read.
+    pub use_pm_ls: bool,
+    /// Fail the run when a project declares dependencies but no source file was scanned.
+    pub fail_empty: bool,
+    /// Only read the manifest and the lockfile, without scanning files.
+    pub manifest_only: bool,
+    /// A file listing the dependencies to analyze in place of those of `package.json`.
+    pub dependencies_from: Option<String>,
+    /// A package tarball (e.g., from `npm pack`) analyzed in place of the project paths.
+    pub tarball: Option<String>,
+    /// The lockfile to read when the project holds several.
+    pub lockfile: Option<String>,
+    /// Print a single line instead of the console report for projects without dependencies.
+    pub allow_empty: bool,
+    /// The dependency lists shown in the console report.
+    pub report_sections: ReportSections,
+    /// The rows of the console metrics table, in order.
+    pub reporter_columns: Vec<Metric>,
+    pub fail_on: Option<FailPolicy>,
+    /// Exit with a code telling unused (3), missing (4) or both kinds of dependencies (5) apart.
+    pub diff_exit_code: bool,
+    /// A template of a single line printed at the end of the run, rendered against its counts.
+    pub summary_line_format: Option<SummaryTemplate>,
+    pub compare: Option<String>,
+    pub max_depth: Option<usize>,
+    /// Packages to restrict the analysis to; empty to analyze every declared package.
+    pub packages: Vec<String>,
+    pub workspaces: bool,
+}
+
+impl Options {
+    /// Extracts the run options from parsed command-line arguments.
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        Options {
+            paths: with_workspaces(
+                matches
+                    .get_many::<String>("paths")
+                    .map(|paths| paths.cloned().collect())
+                    .unwrap_or_else(|| vec![".".to_string()]),
+                matches.get_flag("workspaces"),
+            ),
+            dry_run: matches.get_flag("dry-run"),
+            interactive: matches.get_flag("interactive"),
+            preselect_all: matches.get_flag("preselect-all"),
+            reinstall: matches
+                .get_one::<String>("reinstall")
+                .and_then(|policy| policy.parse().ok())
+                .unwrap_or(ReinstallPolicy::Always),
+            frozen_install: matches.get_flag("frozen-install"),
+            format: matches
+                .get_one::<String>("format")
+                .and_then(|format| format.parse().ok())
+                .unwrap_or(OutputFormat::Console),
+            color: matches
+                .get_one::<String>("color")
+                .and_then(|color| color.parse().ok())
+                .unwrap_or(ColorChoice::Auto),
+            count: matches.get_flag("count"),
+            print_config: matches.get_flag("print-config"),
+            init: matches.get_flag("init"),
+            force: matches.get_flag("force"),
+            types_only: matches.get_flag("types-only"),
+            audit_scripts: matches.get_flag("audit-scripts"),
+            no_tsc: matches.get_flag("no-tsc"),
+            tsc: matches.get_flag("tsc"),
+            tsc_timeout: matches
+                .get_one::<u64>("tsc-timeout")
+                .copied()
+                .unwrap_or(DEFAULT_TSC_TIMEOUT_SECS),
+            no_cache: matches.get_flag("no-cache"),
+            resolver_script: matches.get_one::<String>("resolver-script").cloned(),
+            summary_json_stderr: matches.get_flag("summary-json-stderr"),
+            explain_ignored: matches.get_flag("explain-ignored"),
+            no_warnings: matches.get_flag("no-warnings"),
+            max_warnings: matches.get_one::<usize>("max-warnings").copied(),
+            profile: matches.get_flag("profile"),
+            stats: matches.get_flag("stats"),
+            scan_comments_for_pragmas: matches.get_flag("scan-comments-for-pragmas"),
+            threads: matches.get_one::<usize>("threads").copied().unwrap_or(1),
+            report_files: matches
+                .get_many::<(OutputFormat, String)>("report-file")
+                .map(|files| files.cloned().collect())
+                .unwrap_or_default(),
+            watch: matches.get_flag("watch"),
+            git_age: matches.get_flag("git-age"),
+            dedupe_check: matches.get_flag("dedupe-check"),
+            bin: matches.get_flag("bin"),
+            check_entrypoints: matches.get_flag("check-entrypoints"),
+            check_duplicates_in_code: matches.get_flag("check-duplicates-in-code"),
+            ignore_dev: matches.get_flag("ignore-dev"),
+            report_types_node: matches.get_flag("report-types-node"),
+            use_pm_ls: matches.get_flag("use-pm-ls"),
+            fail_empty: matches.get_flag("fail-empty"),
+            manifest_only: matches.get_flag("manifest-only"),
+            dependencies_from: matches.get_one::<String>("dependencies-from").cloned(),
+            tarball: matches.get_one::<String>("tarball").cloned(),
+            lockfile: matches.get_one::<String>("lockfile").cloned(),
+            allow_empty: matches.get_flag("allow-empty"),
+            report_sections: if matches.get_flag("report-used-only") {
+                ReportSections::UsedOnly
+            } else if matches.get_flag("report-unused-only") {
+                ReportSections::UnusedOnly
+            } else {
+                ReportSections::All
+            },
+            reporter_columns: matches.get_many::<Metric>("reporter-columns").map_or_else(
+                || Metric::DEFAULT.to_vec(),
+                |metrics| metrics.copied().collect(),
+            ),
+            workspaces: matches.get_flag("workspaces"),
+            compare: matches.get_one::<String>("compare").cloned(),
+            max_depth: matches.get_one::<usize>("max-depth").copied(),
+            packages: matches
+                .get_many::<String>("packages")
+                .map(|packages| packages.cloned().collect())
+                .unwrap_or_default(),
+            fail_on: matches
+                .get_one::<String>("fail-on")
+                .and_then(|policy| policy.parse().ok()),
+            diff_exit_code: matches.get_flag("diff-exit-code"),
+            summary_line_format: matches
+                .get_one::<SummaryTemplate>("summary-line-format")
+                .cloned(),
+        }
+    }
+}
+
+impl Options {
+    /// Lists the options of the run for `--print-config`.
+    ///
+    /// # Arguments
+    ///
+    /// * `use_tsc` - Whether `tsc` runs, once resolved against CI detection and its availability.
+    ///
+    /// # Returns
+    ///
+    /// Returns the `Flags` describing every option that changes what the run does.
+    pub fn flags(&self, use_tsc: bool) -> Flags {
+        let optional = |value: &Option<String>| value.clone();
+        Flags {
+            paths: self.paths.clone(),
+            workspaces: self.workspaces,
+            tarball: optional(&self.tarball),
+            format: self.format.to_string(),
+            color: self.color.to_string(),
+            count: self.count,
+            dry_run: self.dry_run,
+            interactive: self.interactive,
+            preselect_all: self.preselect_all,
+            reinstall: self.reinstall.to_string(),
+            frozen_install: self.frozen_install,
+            tsc: use_tsc,
+            tsc_timeout: self.tsc_timeout,
+            cache: !self.no_cache,
+            resolver_script: optional(&self.resolver_script),
+            threads: self.threads,
+            max_depth: self.max_depth,
+            packages: self.packages.clone(),
+            scan_comments_for_pragmas: self.scan_comments_for_pragmas,
+            ignore_dev: self.ignore_dev,
+            report_types_node: self.report_types_node,
+            use_pm_ls: self.use_pm_ls,
+            manifest_only: self.manifest_only,
+            lockfile: optional(&self.lockfile),
+            dependencies_from: optional(&self.dependencies_from),
+            fail_on: self.fail_on.map(|policy| policy.to_string()),
+            fail_empty: self.fail_empty,
+            max_warnings: self.max_warnings,
+            diff_exit_code: self.diff_exit_code,
+            compare: optional(&self.compare),
+            allow_empty: self.allow_empty,
+            no_warnings: self.no_warnings,
+            report_sections: self.report_sections.to_string(),
+            reporter_columns: self
+                .reporter_columns
+                .iter()
+                .map(|metric| metric.name().to_string())
+                .collect(),
+            summary_line_format: self
+                .summary_line_format
+                .as_ref()
+                .map(|template| template.to_string()),
+            summary_json_stderr: self.summary_json_stderr,
+            report_files: self
+                .report_files
+                .iter()
+                .map(|(format, path)| format!("{}={}", format, path))
+                .collect(),
+            types_only: self.types_only,
+            audit_scripts: self.audit_scripts,
+            explain_ignored: self.explain_ignored,
+            profile: self.profile,
+            stats: self.stats,
+            git_age: self.git_age,
+            dedupe_check: self.dedupe_check,
+            bin: self.bin,
+            check_entrypoints: self.check_entrypoints,
+            check_duplicates_in_code: self.check_duplicates_in_code,
+            watch: self.watch,
+        }
+    }
+}
+
+/// Builds the command-line interface definition.
+///
+/// # Returns
+///
+/// Returns the `clap::Command` describing every supported argument.
+///
+/// # Examples
+///
+/// ```
+/// let matches = build_command().get_matches_from(["cnp", "--dry-run"]);
+/// let options = Options::from_matches(&matches);
+/// assert!(options.dry_run);
+/// ```
+pub fn build_command() -> Command {
+    Command::new("Check Node Packages")
+        .about("A utility tool written in Rust to check unused node packages.")
+        .arg(
+            Arg::new("paths")
+                .help("Project directories to analyze (defaults to the current directory)")
+                .num_args(1..)
+                .default_value("."),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("Simulate actions without making changes (e.g., no uninstalls)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("interactive")
+                .short('i')
+                .long("interactive")
+                .help("Prompt the user before taking actions on unused dependencies")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("preselect-all")
+                .long("preselect-all")
+                .help("Select every unused dependency at first in the interactive prompt")
+                .requires("interactive")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("reinstall")
+                .long("reinstall")
+                .help("Reinstall node_modules after removing dependencies (auto: only if the package manager left it stale)")
+                .value_parser(["always", "never", "auto"])
+                .default_value("always"),
+        )
+        .arg(
+            Arg::new("frozen-install")
+                .long("frozen-install")
+                .help("Reinstall without updating the lockfile (npm ci, yarn install --immutable, pnpm/bun install --frozen-lockfile)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("Output format of the report")
+                .value_parser(["console", "json", "github", "junit", "html", "toml"])
+                .default_value("console"),
+        )
+        .arg(
+            Arg::new("color")
+                .long("color")
+                .value_name("WHEN")
+                .help("When to color the output (auto: only on a terminal without NO_COLOR)")
+                .value_parser(["always", "never", "auto"])
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::new("report-file")
+                .long("report-file")
+                .value_name("FORMAT=PATH")
+                .help("Also write the report in FORMAT (json, github, junit, html or toml) to PATH; repeatable")
+                .value_parser(parse_report_file)
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("count")
+                .long("count")
+                .help("Print only the total, used, unused and missing dependency counts")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("init")
+                .long("init")
+                .help("Write a starter cnp.toml and an example .cnpignore into the project")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .help("Overwrite existing files with --init")
+                .requires("init")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("print-config")
+                .long("print-config")
+                .help("Print the effective configuration and exit")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("types-only")
+                .long("types-only")
+                .help("Only report @types packages whose base package is no longer declared")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("audit-scripts")
+                .long("audit-scripts")
+                .help("Flag unused dependencies whose installed package declares install scripts")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("no-tsc")
+                .long("no-tsc")
+                .help("Skip tsc and match TypeScript imports like JavaScript ones (default when tsc is not found)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("tsc")
+                .long("tsc")
+                .help("Run tsc even when the CI environment variable is set (tsc is skipped in CI by default)")
+                .conflicts_with("no-tsc")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("tsc-timeout")
+                .long("tsc-timeout")
+                .value_name("SECS")
+                .help("Seconds tsc may run before falling back to matching TypeScript imports like JavaScript ones (default: 60)")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("no-cache")
+                .long("no-cache")
+                .help("Run tsc even when the TypeScript files are unchanged since its cached diagnostics")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("resolver-script")
+                .long("resolver-script")
+                .value_name("PATH")
+                .help("Script resolving undeclared imports: reads one specifier per line on stdin, prints one package name (or - for none) per line"),
+        )
+        .arg(
+            Arg::new("summary-json-stderr")
+                .long("summary-json-stderr")
+                .help("Also print the JSON report on stderr while the console report goes to stdout")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("explain-ignored")
+                .long("explain-ignored")
+                .help("List every ignored file with the rule that skipped it")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("no-warnings")
+                .long("no-warnings")
+                .help("Silence warnings (multiple lockfiles, tsc failures, invalid configuration, ...)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("threads")
+                .long("threads")
+                .value_name("N")
+                .help("Read and match source files on N threads (default: 1, fully sequential)")
+                .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..)),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .help("Print the time spent in each phase (manifest, lockfile, scan, tsc, report)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("stats")
+                .long("stats")
+                .help("Print scan statistics (files per extension, bytes read, import statements, average matches per file)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("scan-comments-for-pragmas")
+                .long("scan-comments-for-pragmas")
+                .help("Honor cnp-keep <package>, cnp-ignore-next-line and cnp-disable comments in source files")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("max-warnings")
+                .long("max-warnings")
+                .value_name("N")
+                .help("Exit with a non-zero code when more than N warnings are raised (default: unlimited)")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("watch")
+                .long("watch")
+                .short('w')
+                .help("Re-run the analysis when source files or package.json change (never removes packages)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("git-age")
+                .long("git-age")
+                .help("Show how long ago each used dependency was last referenced, from git history")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dedupe-check")
+                .long("dedupe-check")
+                .help("Report packages that resolve to more than one version in the lockfile")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("bin")
+                .long("bin")
+                .help("Report script commands that no installed dependency provides in its bin field")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("check-entrypoints")
+                .long("check-entrypoints")
+                .help("Report main, module, types and exports entries of package.json pointing at missing files")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("check-duplicates-in-code")
+                .long("check-duplicates-in-code")
+                .help("Report packages serving the same purpose that are imported together (e.g. moment and dayjs)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("ignore-dev")
+                .long("ignore-dev")
+                .help("Only count the dependencies of production lockfile entries (not npm dev or pnpm devDependencies) as required")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("report-types-node")
+                .long("report-types-node")
+                .help("Report @types/node as unused like any other package (overrides keep_types_node in cnp.toml)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("use-pm-ls")
+                .long("use-pm-ls")
+                .help("Read the installed packages from npm ls/pnpm ls when the lockfile is missing or cannot be parsed (slower, needs node_modules)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("fail-empty")
+                .long("fail-empty")
+                .help("Exit with an error when dependencies are declared but no source file was scanned")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("manifest-only")
+                .long("manifest-only")
+                .help("Skip the file scan: only check package.json against the lockfile (no unused or missing dependencies are reported)")
+                .conflicts_with_all(["fail-empty", "types-only"])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dependencies-from")
+                .long("dependencies-from")
+                .value_name("FILE")
+                .help("Analyze the newline-delimited package names of FILE instead of reading package.json")
+                .conflicts_with("types-only"),
+        )
+        .arg(
+            Arg::new("tarball")
+                .long("tarball")
+                .value_name("PATH")
+                .help("Extract a package tarball (e.g., from npm pack) to a temporary directory and analyze it; nothing is removed")
+                .conflicts_with_all(["paths", "workspaces", "init", "watch", "interactive"]),
+        )
+        .arg(
+            Arg::new("lockfile")
+                .long("lockfile")
+                .value_name("NAME")
+                .help("Read this lockfile of the project directory, even when others are present")
+                .value_parser(LOCKFILES),
+        )
+        .arg(
+            Arg::new("allow-empty")
+                .long("allow-empty")
+                .help("Print a single line instead of the report when no dependency is declared")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("report-used-only")
+                .long("report-used-only")
+                .help("Only list the used dependencies in the console report")
+                .conflicts_with("report-unused-only")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("report-unused-only")
+                .long("report-unused-only")
+                .help("Only list the unused dependencies in the console report")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("reporter-columns")
+                .long("reporter-columns")
+                .value_name("METRICS")
+                .help("Rows of the console metrics table, in order (comma-separated): project, extensions, ignored-folders, explored-files, ignored-files, total, used, unused, missing, bytes-scanned (with --stats), scan-duration (with --profile)")
+                .value_parser(|value: &str| value.parse::<Metric>())
+                .value_delimiter(',')
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("workspaces")
+                .long("workspaces")
+                .help("Also analyze every workspace package (pnpm-workspace.yaml or package.json workspaces)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("max-depth")
+                .long("max-depth")
+                .value_name("N")
+                .help("Scan at most N directory levels below the project directory")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("packages")
+                .long("packages")
+                .value_name("NAMES")
+                .help("Only analyze these packages (comma-separated or repeated)")
+                .value_delimiter(',')
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("compare")
+                .long("compare")
+                .value_name("FILE")
+                .help("Compare with a previously saved JSON report and exit with code 1 on newly unused dependencies"),
+        )
+        .arg(
+            Arg::new("fail-on")
+                .long("fail-on")
+                .help("Exit with code 1 when unused dependencies are found in these buckets (production ignores devDependencies)")
+                .value_parser(["all", "production"]),
+        )
+        .arg(
+            Arg::new("diff-exit-code")
+                .long("diff-exit-code")
+                .help("Exit with 3 when unused dependencies are found, 4 when missing ones are, 5 for both (1 stays fatal)")
+                .conflicts_with("fail-on")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("summary-line-format")
+                .long("summary-line-format")
+                .value_name("TEMPLATE")
+                .help("Print a single line at the end of the run from a template such as `unused={{unused}} missing={{missing}} in {{files}} files` (variables: projects, total, used, unused, unused_dev, missing, files, ignored)")
+                .value_parser(|value: &str| value.parse::<SummaryTemplate>()),
+        )
+}
+
+/// Appends the workspace packages of each project path, if requested.
+///
+/// # Arguments
+///
+/// * `paths` - The project paths given on the command line.
+/// * `workspaces` - If `true`, each path is followed by its workspace package directories.
+///
+/// # Returns
+///
+/// Returns the project paths to analyze, without duplicates and in command-line order.
+///
+/// # Examples
+///
+/// ```
+/// // pnpm-workspace.yaml: packages: ["packages/*"]
+/// let paths = with_workspaces(vec![".".to_string()], true);
+/// assert_eq!(paths, vec![".", "./packages/api", "./packages/web"]);
+/// ```
+pub fn with_workspaces(paths: Vec<String>, workspaces: bool) -> Vec<String> {
+    if !workspaces {
+        return paths;
+    }
+
+    let mut expanded: Vec<String> = Vec::new();
+    for path in paths {
+        let packages = workspace_packages(&path);
+        for path in std::iter::once(path).chain(packages) {
+            if !expanded.contains(&path) {
+                expanded.push(path);
+            }
+        }
+    }
+    expanded
+}
+
+/// Resolves a project path argument to the directory to analyze.
+///
+/// # Arguments
+///
+/// * `path` - A string slice representing the project path given on the command line.
+///
+/// # Returns
+///
+/// Returns `Ok(String)` with the canonical path of the project directory.
+/// Returns `Err(String)` with an error message if the path is not an existing directory.
+///
+/// # Examples
+///
+/// ```
+/// let dir_path = resolve_project_path("./app")?;
+/// println!("Analyzing {}", dir_path);
+/// ```
+pub fn resolve_project_path(path: &str) -> Result<String, String> {
+    let dir_path = Path::new(path);
+    if !dir_path.is_dir() {
+        return Err(format!("Error: `{}` is not a directory.", path));
+    }
+
+    dir_path
+        .canonicalize()
+        .map(|dir_path| dir_path.display().to_string())
+        .map_err(|_| format!("Error: `{}` is not a directory.", path))
+}
+
+/// Decides whether `tsc` should be run, before checking that it is installed.
+///
+/// `tsc` is skipped with `--no-tsc`, and in CI unless `--tsc` is given, since it can be slow enough
+/// on large projects to stall a CI job.
+///
+/// # Arguments
+///
+/// * `options` - A reference to the run `Options`.
+/// * `ci` - Whether the process runs in CI (see `is_ci`).
+///
+/// # Returns
+///
+/// Returns `true` if `tsc` should be run, `false` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// let options = Options::from_matches(&build_command().get_matches_from(["cnp"]));
+/// assert!(use_tsc(&options, false));
+/// assert!(!use_tsc(&options, true));
+/// ```
+pub fn use_tsc(options: &Options, ci: bool) -> bool {
+    !options.no_tsc && (options.tsc || !ci)
+}
+
+/// Prints the error of a project to stderr, prefixed with the project path when several projects
+/// are analyzed.
+fn print_project_error(path: &str, err: &str, multiple: bool) {
+    if multiple {
+        eprintln!("{} {}", format!("[{}]", path).bold(), err.red());
+    } else {
+        eprintln!("{}", err.red());
+    }
+}
+
+/// Runs the dependency analysis for every project path and writes the reports.
+///
+/// Each project is analyzed independently. When several projects are given, the console report is
+/// sectioned by project, JSON output becomes an array with one entry per project, and `--count`
+/// prints one line (or JSON entry) per project. Unused dependencies are handled (e.g., prompted for
+/// removal) per project, only for the full console report and never in watch mode. Warnings raised
+/// during the analysis are included in the JSON report and GitHub annotations, printed to stderr for
+/// the other formats, and dropped with `--no-warnings`; they still count towards `--max-warnings`.
+/// With `--format json`, a project that cannot be analyzed gets an error object with a `code` (see
+/// [`project_error_code`]) in place of its report, so the output is always valid JSON.
+/// With `--summary-json-stderr`, the JSON
+/// report is also written to `summary_out` and the progress bar is disabled so it cannot interleave
+/// with the JSON. With `--tarball`, the tarball is extracted to a temporary directory (removed
+/// afterwards) and analyzed as the only project, in dry-run mode.
+///
+/// # Arguments
+///
+/// * `options` - A reference to the run `Options`.
+/// * `out` - The writer the reports are written to (e.g. stdout). Errors go to stderr.
+/// * `summary_out` - The writer the JSON report is written to with `--summary-json-stderr` (e.g.
+///   stderr).
+///
+/// # Returns
+///
+/// Returns the process exit code: `0` if every project was analyzed, `1` if any project failed
+/// (e.g., missing or invalid `package.json`), with `--fail-on`, has unused dependencies in a
+/// failing bucket, with `--compare`, has newly unused dependencies or, with `--max-warnings`, if
+/// more warnings were raised than allowed.
+///
+/// # Examples
+///
+/// ```
+/// let options = Options::from_matches(&build_command().get_matches());
+/// let code = run(&options, &mut io::stdout(), &mut io::stderr());
+/// std::process::exit(code);
+/// ```
+pub fn run(options: &Options, out: &mut dyn Write, summary_out: &mut dyn Write) -> i32 {
+    // Analyze an extracted tarball in place of the project paths, without removing anything
+    if let Some(tarball) = options.tarball.as_deref() {
+        return match extract_tarball(tarball) {
+            Ok(extracted) => {
+                let options = Options {
+                    paths: vec![extracted.root().to_string_lossy().to_string()],
+                    dry_run: true,
+                    tarball: None,
+                    ..options.clone()
+                };
+                run(&options, out, summary_out)
+            }
+            Err(err) => {
+                eprintln!("{}", err.red());
+                1
+            }
+        };
+    }
+
+    let multiple = options.paths.len() > 1;
+
+    // Write the starter configuration into the first project
+    if options.init {
+        return match init_project(&options.paths[0], options.force) {
+            Ok(files) => {
+                for file in files {
+                    let _ = writeln!(out, "{}", format!("Created {}", file).green());
+                }
+                0
+            }
+            Err(err) => {
+                eprintln!("{}", err.red());
+                1
+            }
+        };
+    }
+
+    // Print the effective configuration of the first project
+    if options.print_config {
+        let flags = options.flags(use_tsc(options, is_ci()) && is_tsc_available());
+        let effective = EffectiveConfig::resolve(&options.paths[0], flags);
+        let _ = writeln!(out, "{}", effective.render(options.format));
+        return 0;
+    }
+
+    if options.types_only {
+        return run_types_only(options, out);
+    }
+
+    let mut exit_code = 0;
+    let mut warning_count = 0;
+    let mut found_unused = false;
+    let mut found_missing = false;
+    let mut json_reports = Vec::new();
+    let mut file_reports = Vec::new();
+    let mut summaries = Vec::new();
+    let mut run_counts = RunCounts::default();
+    let use_tsc = use_tsc(options, is_ci()) && is_tsc_available();
+    let previous_report = match options.compare.as_deref().map(read_previous_report) {
+        Some(Err(err)) => {
+            eprintln!("{}", err.red());
+            return 1;
+        }
+        previous_report => previous_report.and_then(Result::ok),
+    };
+    let dependency_list = match options
+        .dependencies_from
+        .as_deref()
+        .map(read_dependency_list)
+        .transpose()
+    {
+        Ok(dependency_list) => dependency_list,
+        Err(err) => {
+            eprintln!("{}", err.red());
+            return 1;
+        }
+    };
+
+    for path in &options.paths {
+        // Initialize progress bar (hidden for machine-readable or summary-only output)
+        let pb = if options.count
+            || options.format != OutputFormat::Console
+            || options.summary_json_stderr
+        {
+            ProgressBar::hidden()
+        } else {
+            create_spinner("Initializing...")
+        };
+
+        let analysis = match resolve_project_path(path).and_then(|dir_path| {
+            let analyze_options = AnalyzeOptions::new(dir_path.as_str())
+                .use_tsc(use_tsc)
+                .tsc_timeout(Duration::from_secs(options.tsc_timeout))
+                .cache(!options.no_cache)
+                .max_depth(options.max_depth)
+                .packages(options.packages.iter().cloned())
+                .profile(options.profile)
+                .stats(options.stats)
+                .pragmas(options.scan_comments_for_pragmas)
+                .threads(options.threads)
+                .ignore_dev(options.ignore_dev)
+                .report_types_node(options.report_types_node)
+                .resolver_script(options.resolver_script.clone())
+                .use_pm_ls(options.use_pm_ls)
+                .dependency_list(dependency_list.clone())
+                .lockfile(options.lockfile.clone())
+                .manifest_only(options.manifest_only);
+            analyze(&analyze_options, &pb).map(|analysis| (dir_path, analysis))
+        }) {
+            Ok(analysis) => analysis,
+            Err(err) => {
+                pb.finish_and_clear();
+                print_project_error(path, &err, multiple);
+                exit_code = 1;
+                // JSON consumers get an error object in place of the report
+                if options.format == OutputFormat::Json && !options.count {
+                    json_reports.push((path, Err(err)));
+                }
+                continue;
+            }
+        };
+        let (dir_path, mut analysis) = analysis;
+        let report_start = Instant::now();
+
+        // Silenced warnings still count towards `--max-warnings`
+        let had_too_many_warnings = options.max_warnings.is_some_and(|max| warning_count > max);
+        warning_count += analysis.warnings.len();
+        if let Some(max) = options.max_warnings.filter(|max| warning_count > *max) {
+            if !had_too_many_warnings {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "Error: {} warnings raised, more than the --max-warnings limit of {}.",
+                        warning_count, max
+                    )
+                    .red()
+                );
+            }
+            exit_code = 1;
+        }
+
+        // Warnings are part of the JSON and TOML reports and GitHub annotations; other formats print
+        // them to stderr
+        if options.no_warnings {
+            analysis.warnings.clear();
+        } else if !matches!(
+            options.format,
+            OutputFormat::Json | OutputFormat::Toml | OutputFormat::Github
+        ) {
+            let _ = write_warnings(&mut io::stderr(), &analysis.warnings);
+        }
+
+        if options
+            .fail_on
+            .is_some_and(|policy| policy.fails(&analysis))
+        {
+            exit_code = 1;
+        }
+        found_unused |= !analysis.unused_dependencies.is_empty();
+        found_missing |= !analysis.missing_dependencies.is_empty();
+        run_counts.add(&analysis);
+
+        if options.fail_empty && analysis.is_empty_scan() {
+            print_project_error(
+                path,
+                "Error: No source files were scanned. Check the project path and the ignore rules.",
+                multiple,
+            );
+            exit_code = 1;
+        }
+
+        // Compare with the previous report: only newly unused dependencies fail the run
+        let diff = previous_report
+            .as_ref()
+            .and_then(|previous| previous_project_report(previous, path))
+            .map(|previous| {
+                let current = serde_json::to_value(JsonReport::new(path, &analysis))
+                    .expect("Failed to serialize report");
+                diff_reports(previous, &current)
+            });
+        if diff.as_ref().is_some_and(ReportDiff::has_regressions) {
+            exit_code = 1;
+        }
+        let is_console_report = !options.count && options.format == OutputFormat::Console;
+        if let Some(diff) = diff.as_ref().filter(|_| !is_console_report) {
+            let _ = write_report_diff(summary_out, diff);
+        }
+
+        if options.git_age {
+            analysis.git_ages = dependency_ages(&dir_path, &analysis.dependency_files);
+        }
+
+        if options.dedupe_check {
+            analysis.duplicate_versions =
+                duplicate_versions(&dir_path, options.lockfile.as_deref());
+        }
+
+        if options.bin {
+            let config = load_config(&dir_path);
+            analysis.missing_script_commands = read_manifest(&dir_path, &config)
+                .map(|package_json| missing_script_commands(&dir_path, &package_json))
+                .unwrap_or_default();
+        }
+
+        if options.check_entrypoints {
+            let config = load_config(&dir_path);
+            analysis.broken_entry_points = read_manifest(&dir_path, &config)
+                .map(|package_json| broken_entry_points(&dir_path, &package_json))
+                .unwrap_or_default();
+        }
+
+        if options.check_duplicates_in_code {
+            let imported: HashSet<String> = analysis
+                .used_packages
+                .iter()
+                .chain(&analysis.missing_dependencies)
+                .cloned()
+                .collect();
+            analysis.duplicate_purposes = load_config(&dir_path).duplicate_purposes(&imported);
+        }
+
+        if options.audit_scripts {
+            analysis.lifecycle_scripts =
+                audit_lifecycle_scripts(&dir_path, &analysis.unused_dependencies);
+        }
+
+        if !options.report_files.is_empty() {
+            file_reports.push((path, dir_path.clone(), analysis.clone()));
+        }
+
+        if options.count {
+            summaries.push((path, Summary::from_analysis(&analysis)));
+            continue;
+        }
+
+        match options.format {
+            OutputFormat::Console => {
+                // A project without dependencies gets a single line instead of the full report
+                if options.allow_empty && analysis.dependencies.is_empty() {
+                    let _ = writeln!(
+                        out,
+                        "{}",
+                        format!(
+                            "No dependencies declared in {}.",
+                            manifest_display_path(path)
+                        )
+                        .green()
+                    );
+                    continue;
+                }
+
+                if multiple {
+                    let _ = writeln!(out, "\n{}", format!("Project: {}", path).bold().underline());
+                }
+                let _ = write_dependency_report(
+                    out,
+                    path,
+                    &analysis,
+                    // Nothing is reported unused without a scan
+                    if options.manifest_only {
+                        ReportSections::UsedOnly
+                    } else {
+                        options.report_sections
+                    },
+                    &options.reporter_columns,
+                );
+                if let Some(diff) = diff.as_ref() {
+                    let _ = write_report_diff(out, diff);
+                }
+                if options.explain_ignored {
+                    let _ = write_ignored_files(out, &analysis.ignore_reasons);
+                }
+
+                // Process unused dependencies (never in watch mode)
+                if !analysis.unused_dependencies.is_empty() && !options.watch {
+                    let removal = handle_unused_dependencies(
+                        &dir_path,
+                        &analysis.unused_dependencies,
+                        &analysis.package_sizes,
+                        options.dry_run,
+                        options.interactive,
+                        options.preselect_all,
+                        ReinstallOptions {
+                            policy: options.reinstall,
+                            frozen: options.frozen_install,
+                        },
+                    );
+                    if removal.dry_run {
+                        let _ = write_removal_plan(out, &removal, &analysis.package_sizes);
+                    }
+                }
+
+                if let Some(stats) = &analysis.stats {
+                    let _ = write_stats(out, stats);
+                }
+                if let Some(timings) = analysis.timings.as_mut() {
+                    timings.report = report_start.elapsed();
+                    let _ = write_timings(out, timings);
+                }
+
+                if options.summary_json_stderr {
+                    json_reports.push((path, Ok(analysis)));
+                }
+            }
+            OutputFormat::Json | OutputFormat::Junit | OutputFormat::Html | OutputFormat::Toml => {
+                if let Some(timings) = analysis.timings.as_mut() {
+                    timings.report = report_start.elapsed();
+                }
+                json_reports.push((path, Ok(analysis)));
+            }
+            OutputFormat::Github => {
+                let manifest = fs::read_to_string(Path::new(&dir_path).join(PACKAGE_JSON_PATH))
+                    .unwrap_or_default();
+                let _ = write_github_annotations(out, path, &manifest, &analysis);
+                if let Some(stats) = &analysis.stats {
+                    let _ = write_stats(&mut io::stderr(), stats);
+                }
+                if let Some(timings) = analysis.timings.as_mut() {
+                    timings.report = report_start.elapsed();
+                    let _ = write_timings(&mut io::stderr(), timings);
+                }
+            }
+        }
+    }
+
+    // Print counts
+    if options.count {
+        match (multiple, options.format) {
+            (false, _) => {
+                for (_, summary) in &summaries {
+                    let _ = writeln!(out, "{}", format_count(summary, options.format));
+                }
+            }
+            (
+                true,
+                OutputFormat::Console
+                | OutputFormat::Github
+                | OutputFormat::Junit
+                | OutputFormat::Html,
+            ) => {
+                for (path, summary) in &summaries {
+                    let _ = writeln!(out, "{}: {}", path, format_count(summary, options.format));
+                }
+            }
+            (true, OutputFormat::Json) => {
+                let summaries: Vec<_> = summaries
+                    .iter()
+                    .map(|(path, summary)| ProjectSummary {
+                        project: path,
+                        summary: *summary,
+                    })
+                    .collect();
+                let _ = writeln!(
+                    out,
+                    "{}",
+                    serde_json::to_string(&summaries).expect("Failed to serialize summary")
+                );
+            }
+            (true, OutputFormat::Toml) => {
+                let projects: Vec<_> = summaries
+                    .iter()
+                    .map(|(path, summary)| ProjectSummary {
+                        project: path,
+                        summary: *summary,
+                    })
+                    .collect();
+                let _ = write!(
+                    out,
+                    "{}",
+                    toml::to_string(&TomlProjects { projects })
+                        .expect("Failed to serialize summary")
+                );
+            }
+        }
+    }
+
+    // Write the additional report files
+    for (format, file) in &options.report_files {
+        let written = fs::File::create(file).and_then(|mut writer| {
+            write_report_file(&mut writer, *format, &file_reports, multiple)
+        });
+        if let Err(err) = written {
+            eprintln!(
+                "{}",
+                format!("Error: Could not write the report to `{}` ({}).", file, err).red()
+            );
+            exit_code = 1;
+        }
+    }
+
+    // Failures keep exit code 1; otherwise the code tells the kind of findings apart
+    if options.diff_exit_code && exit_code == 0 {
+        exit_code = diff_exit_code(found_unused, found_missing);
+    }
+
+    // Print the summary line after the console report, or beside a machine-readable one
+    let summary_line = options
+        .summary_line_format
+        .as_ref()
+        .map(|template| template.render(&run_counts));
+    if let Some(line) = summary_line.as_ref()
+        && !matches!(options.format, OutputFormat::Console | OutputFormat::Github)
+    {
+        let _ = writeln!(summary_out, "{}", line);
+    }
+
+    // Print the JUnit report of every project at once
+    if options.format == OutputFormat::Junit {
+        let suites: Vec<_> = json_reports
+            .iter()
+            .filter_map(|(path, analysis)| {
+                Some((path.as_str(), junit_cases(analysis.as_ref().ok()?)))
+            })
+            .collect();
+        let _ = write_junit_report(out, &suites);
+        return exit_code;
+    }
+
+    // Print the HTML report of every project at once
+    if options.format == OutputFormat::Html {
+        let projects: Vec<_> = json_reports
+            .iter()
+            .filter_map(|(path, analysis)| Some((path.as_str(), analysis.as_ref().ok()?)))
+            .collect();
+        let _ = write_html_report(out, &projects);
+        return exit_code;
+    }
+
+    // Print the TOML report of every project at once
+    if options.format == OutputFormat::Toml && !options.count {
+        let projects: Vec<_> = json_reports
+            .iter()
+            .filter_map(|(path, analysis)| Some((path.as_str(), analysis.as_ref().ok()?)))
+            .collect();
+        let _ = write_toml_report(out, &projects, multiple);
+        return exit_code;
+    }
+
+    // Print JSON reports (on the summary writer when they accompany the console report)
+    if !json_reports.is_empty() {
+        let reports: Vec<_> = json_reports
+            .iter()
+            .map(|(path, analysis)| match analysis {
+                Ok(analysis) => JsonEntry::Report(Box::new(JsonReport::new(path, analysis))),
+                Err(err) => JsonEntry::Error(JsonError::new(path, err, project_error_code(path))),
+            })
+            .collect();
+        let json = render_json_reports(&reports, multiple);
+        if options.format == OutputFormat::Json {
+            let _ = writeln!(out, "{}", json);
+        } else {
+            let _ = writeln!(summary_out, "{}", json);
+        }
+    }
+
+    if let Some(line) = summary_line
+        && matches!(options.format, OutputFormat::Console | OutputFormat::Github)
+    {
+        let _ = writeln!(out, "{}", line);
+    }
+
+    exit_code
+}
+
+/// Computes the `--diff-exit-code` exit code of a run that did not fail.
+///
+/// # Arguments
+///
+/// * `unused` - Whether unused dependencies were found in any project (devDependencies excluded).
+/// * `missing` - Whether missing dependencies were found in any project.
+///
+/// # Returns
+///
+/// Returns `0` without findings, `3` for unused dependencies only, `4` for missing dependencies
+/// only and `5` for both. Fatal errors keep exit code `1`, which this function never returns.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(diff_exit_code(false, false), 0);
+/// assert_eq!(diff_exit_code(true, true), 5);
+/// ```
+pub fn diff_exit_code(unused: bool, missing: bool) -> i32 {
+    match (unused, missing) {
+        (false, false) => 0,
+        (true, false) => 3,
+        (false, true) => 4,
+        (true, true) => 5,
+    }
+}
+
+/// Parses a `--report-file` value of the form `FORMAT=PATH`.
+///
+/// # Arguments
+///
+/// * `value` - The argument value, e.g. `json=cnp.json`.
+///
+/// # Returns
+///
+/// Returns `Ok((OutputFormat, String))` with the report format and file path.
+/// Returns `Err(String)` if the value has no `=`, names an unknown format or the console format,
+/// or has an empty path.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(parse_report_file("json=cnp.json"), Ok((OutputFormat::Json, "cnp.json".to_string())));
+/// assert!(parse_report_file("cnp.json").is_err());
+/// ```
+pub fn parse_report_file(value: &str) -> Result<(OutputFormat, String), String> {
+    let (format, path) = value
+        .split_once('=')
+        .ok_or_else(|| format!("Error: Expected FORMAT=PATH, got `{}`.", value))?;
+    let format: OutputFormat = format.trim().parse()?;
+    if format == OutputFormat::Console {
+        return Err("Error: The console report cannot be written to a file.".to_string());
+    }
+    if path.trim().is_empty() {
+        return Err(format!("Error: Missing the file path in `{}`.", value));
+    }
+    Ok((format, path.trim().to_string()))
+}
+
+/// Classifies why a project could not be analyzed, for the `code` of its JSON error object.
+///
+/// # Arguments
+///
+/// * `path` - A string slice representing the project path given on the command line.
+///
+/// # Returns
+///
+/// Returns `not_a_directory` if the path is not a directory, `manifest_not_found` if it has no
+/// manifest, or `manifest_invalid` if the manifest cannot be read or parsed.
+///
+/// # Examples
+///
+/// ```
+/// // ./empty is a directory without package.json
+/// assert_eq!(project_error_code("./empty"), "manifest_not_found");
+/// assert_eq!(project_error_code("./missing"), "not_a_directory");
+/// ```
+pub fn project_error_code(path: &str) -> &'static str {
+    let dir_path = Path::new(path);
+    let has_manifest = std::iter::once(PACKAGE_JSON_PATH)
+        .chain(LENIENT_MANIFEST_PATHS)
+        .any(|manifest| dir_path.join(manifest).exists());
+    if !dir_path.is_dir() {
+        "not_a_directory"
+    } else if !has_manifest {
+        "manifest_not_found"
+    } else {
+        "manifest_invalid"
+    }
+}
+
+/// Serializes JSON reports: a single object for one project, an array for several.
+fn render_json_reports<T: Serialize>(reports: &[T], multiple: bool) -> String {
+    let json = if multiple {
+        serde_json::to_string_pretty(reports)
+    } else {
+        serde_json::to_string_pretty(&reports[0])
+    };
+    json.expect("Failed to serialize report")
+}
+
+/// Writes the report of every analyzed project in one format, for `--report-file`.
+fn write_report_file(
+    out: &mut dyn Write,
+    format: OutputFormat,
+    reports: &[(&String, String, Analysis)],
+    multiple: bool,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Json if !reports.is_empty() => {
+            let reports: Vec<_> = reports
+                .iter()
+                .map(|(path, _, analysis)| JsonReport::new(path, analysis))
+                .collect();
+            writeln!(out, "{}", render_json_reports(&reports, multiple))
+        }
+        OutputFormat::Junit => {
+            let suites: Vec<_> = reports
+                .iter()
+                .map(|(path, _, analysis)| (path.as_str(), junit_cases(analysis)))
+                .collect();
+            write_junit_report(out, &suites)
+        }
+        OutputFormat::Html => {
+            let projects: Vec<_> = reports
+                .iter()
+                .map(|(path, _, analysis)| (path.as_str(), analysis))
+                .collect();
+            write_html_report(out, &projects)
+        }
+        OutputFormat::Toml => {
+            let projects: Vec<_> = reports
+                .iter()
+                .map(|(path, _, analysis)| (path.as_str(), analysis))
+                .collect();
+            write_toml_report(out, &projects, multiple)
+        }
+        OutputFormat::Github => {
+            for (path, dir_path, analysis) in reports {
+                let manifest = fs::read_to_string(Path::new(dir_path).join(PACKAGE_JSON_PATH))
+                    .unwrap_or_default();
+                write_github_annotations(out, path, &manifest, analysis)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Json | OutputFormat::Console => Ok(()),
+    }
+}
+
+/// Reports orphaned `@types/*` packages for every project path, without scanning any file.
+///
+/// # Arguments
+///
+/// * `options` - A reference to the run `Options`.
+/// * `out` - The writer the reports are written to (e.g. stdout). Errors go to stderr.
+///
+/// # Returns
+///
+/// Returns the process exit code: `0` if every manifest was read, `1` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// let code = run_types_only(&options, &mut io::stdout());
+/// ```
+fn run_types_only(options: &Options, out: &mut dyn Write) -> i32 {
+    let multiple = options.paths.len() > 1;
+    let mut exit_code = 0;
+    let mut json_reports = Vec::new();
+    let mut toml_reports = Vec::new();
+    let mut junit_suites = Vec::new();
+
+    for path in &options.paths {
+        let orphaned_types = match resolve_project_path(path).and_then(|dir_path| {
+            read_manifest(&dir_path, &load_config(&dir_path))
+                .map(|package_json| find_orphaned_types(&package_json))
+        }) {
+            Ok(orphaned_types) => orphaned_types,
+            Err(err) => {
+                print_project_error(path, &err, multiple);
+                exit_code = 1;
+                if options.format == OutputFormat::Json {
+                    let error = JsonError::new(path, &err, project_error_code(path));
+                    json_reports
+                        .push(serde_json::to_value(error).expect("Failed to serialize error"));
+                }
+                continue;
+            }
+        };
+
+        match options.format {
+            // The HTML report covers full analyses only
+            OutputFormat::Console | OutputFormat::Html => {
+                if multiple {
+                    let _ = writeln!(out, "\n{}", format!("Project: {}", path).bold().underline());
+                }
+                let _ = write_orphaned_types(out, &orphaned_types);
+            }
+            OutputFormat::Json => json_reports.push(serde_json::json!({
+                "project": path,
+                "orphaned_types": orphaned_types,
+            })),
+            OutputFormat::Toml => toml_reports.push(serde_json::json!({
+                "project": path,
+                "orphaned_types": orphaned_types,
+            })),
+            OutputFormat::Junit => {
+                let cases = orphaned_types
+                    .iter()
+                    .map(|types_package| JunitCase {
+                        classname: "orphaned_types",
+                        name: types_package.clone(),
+                        failure: Some(format!("Orphaned types package '{}'", types_package)),
+                    })
+                    .collect();
+                junit_suites.push((path.as_str(), cases));
+            }
+            OutputFormat::Github => {
+                let manifest = resolve_project_path(path)
+                    .ok()
+                    .and_then(|dir_path| {
+                        fs::read_to_string(Path::new(&dir_path).join(PACKAGE_JSON_PATH)).ok()
+                    })
+                    .unwrap_or_default();
+                for types_package in &orphaned_types {
+                    let _ = write_github_command(
+                        out,
+                        "warning",
+                        &manifest_display_path(path),
+                        find_dependency_line(&manifest, types_package),
+                        &format!("Orphaned types package '{}'", types_package),
+                    );
+                }
+            }
+        }
+    }
+
+    if options.format == OutputFormat::Junit {
+        let _ = write_junit_report(out, &junit_suites);
+    }
+
+    if !toml_reports.is_empty() {
+        let toml = if multiple {
+            toml::to_string(&TomlProjects {
+                projects: toml_reports,
+            })
+        } else {
+            toml::to_string(&toml_reports[0])
+        };
+        let _ = write!(out, "{}", toml.expect("Failed to serialize report"));
+    }
+
+    if !json_reports.is_empty() {
+        let json = if multiple {
+            serde_json::to_string_pretty(&json_reports)
+        } else {
+            serde_json::to_string_pretty(&json_reports[0])
+        };
+        let _ = writeln!(out, "{}", json.expect("Failed to serialize report"));
+    }
+
+    exit_code
+}
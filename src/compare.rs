@@ -0,0 +1,157 @@
+use colored::*;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, Write};
+
+/// The changes between a previously saved JSON report and the current analysis.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ReportDiff {
+    /// Dependencies unused now but not in the previous report (regressions), sorted by name.
+    pub newly_unused: Vec<String>,
+    /// Dependencies used now but not in the previous report, sorted by name.
+    pub newly_used: Vec<String>,
+    /// Dependencies unused in the previous report but no longer unused, sorted by name.
+    pub resolved: Vec<String>,
+}
+
+impl ReportDiff {
+    /// Checks whether the current analysis has regressed, i.e. has newly unused dependencies.
+    pub fn has_regressions(&self) -> bool {
+        !self.newly_unused.is_empty()
+    }
+}
+
+/// Reads a JSON report previously saved with `--format json`.
+///
+/// # Arguments
+///
+/// * `path` - A string slice representing the path to the saved report.
+///
+/// # Returns
+///
+/// Returns `Ok(Value)` with the report: an object for a single project, or an array of objects when
+/// several projects were analyzed.
+/// Returns `Err(String)` with an error message if the file is not found or is not valid JSON.
+///
+/// # Examples
+///
+/// ```
+/// let previous = read_previous_report("cnp-report.json")?;
+/// ```
+pub fn read_previous_report(path: &str) -> Result<Value, String> {
+    let content = fs::read_to_string(path).map_err(|_| format!("Error: `{}` not found.", path))?;
+    serde_json::from_str(&content).map_err(|_| format!("Error: Invalid JSON in {}.", path))
+}
+
+/// Finds the report of a project in a previously saved JSON report.
+///
+/// # Arguments
+///
+/// * `previous` - A reference to the saved report (object or array of objects).
+/// * `project` - The project path, as given on the command line.
+///
+/// # Returns
+///
+/// Returns `Some(&Value)` with the project's report: the report itself for a single-project report,
+/// or the array entry whose `project` matches. Returns `None` if the project is not found.
+///
+/// # Examples
+///
+/// ```
+/// let previous = read_previous_report("cnp-report.json")?;
+/// let app = previous_project_report(&previous, "./app");
+/// ```
+pub fn previous_project_report<'a>(previous: &'a Value, project: &str) -> Option<&'a Value> {
+    match previous.as_array() {
+        Some(reports) => reports
+            .iter()
+            .find(|report| report.get("project").and_then(Value::as_str) == Some(project)),
+        None => Some(previous),
+    }
+}
+
+/// Compares the `used` and `unused` lists of two JSON reports.
+///
+/// # Arguments
+///
+/// * `previous` - A reference to the previous project report.
+/// * `current` - A reference to the current project report.
+///
+/// # Returns
+///
+/// Returns a `ReportDiff` with the newly unused, newly used and resolved dependencies.
+///
+/// # Examples
+///
+/// ```
+/// let previous = json!({ "used": ["react"], "unused": ["lodash"] });
+/// let current = json!({ "used": ["react", "lodash"], "unused": ["moment"] });
+/// let diff = diff_reports(&previous, &current);
+/// assert_eq!(diff.newly_unused, vec!["moment".to_string()]);
+/// assert_eq!(diff.resolved, vec!["lodash".to_string()]);
+/// ```
+pub fn diff_reports(previous: &Value, current: &Value) -> ReportDiff {
+    let list = |report: &Value, key: &str| -> BTreeSet<String> {
+        report
+            .get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect()
+    };
+    let (previous_used, previous_unused) = (list(previous, "used"), list(previous, "unused"));
+    let (current_used, current_unused) = (list(current, "used"), list(current, "unused"));
+
+    ReportDiff {
+        newly_unused: current_unused
+            .difference(&previous_unused)
+            .cloned()
+            .collect(),
+        newly_used: current_used.difference(&previous_used).cloned().collect(),
+        resolved: previous_unused
+            .difference(&current_unused)
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Writes the changes since a previous report for the console.
+///
+/// # Arguments
+///
+/// * `out` - The writer the changes are written to.
+/// * `diff` - A reference to the `ReportDiff` to write.
+///
+/// # Returns
+///
+/// Returns an `io::Result<()>` indicating whether writing succeeded.
+///
+/// # Examples
+///
+/// ```
+/// write_report_diff(&mut io::stdout(), &diff)?;
+/// // Changes Since Previous Report:
+/// // + moment (newly unused, in red)
+/// // - lodash (resolved, in green)
+/// ```
+pub fn write_report_diff(out: &mut dyn Write, diff: &ReportDiff) -> io::Result<()> {
+    writeln!(out, "\n{}", "Changes Since Previous Report:".blue().bold())?;
+    if *diff == ReportDiff::default() {
+        return writeln!(out, "{}", "No changes.".green());
+    }
+
+    for dep in &diff.newly_unused {
+        writeln!(out, "+ {} {}", dep.red(), "(newly unused)".dimmed())?;
+    }
+    for dep in &diff.resolved {
+        writeln!(out, "- {} {}", dep.green(), "(resolved)".dimmed())?;
+    }
+    for dep in &diff.newly_used {
+        writeln!(out, "* {} {}", dep.cyan(), "(newly used)".dimmed())?;
+    }
+
+    Ok(())
+}
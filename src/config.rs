@@ -1,7 +1,22 @@
+use crate::dependency::{read_cnpignore, read_jsonc};
+use crate::report::OutputFormat;
+use crate::warnings::Warnings;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
 use std::path::Path;
 
 pub const PACKAGE_JSON_PATH: &str = "package.json";
-pub const EXTENSIONS: [&str; 7] = ["js", "ts", "jsx", "tsx", "mdx", "cjs", "mjs"];
+/// Lenient manifests read (with comments and trailing commas) when `package.json` is absent.
+pub const LENIENT_MANIFEST_PATHS: [&str; 2] = ["package.json5", "package.jsonc"];
+pub const EXTENSIONS: [&str; 8] = ["js", "ts", "jsx", "tsx", "mdx", "cjs", "mjs", "css"];
+/// Stylesheet extensions, whose files are searched for `@import`, `@plugin` and `@config`
+/// directives instead of JavaScript imports.
+pub const STYLESHEET_EXTENSIONS: [&str; 3] = ["css", "pcss", "postcss"];
+/// Single-file component extensions, whose `<style>` blocks are also searched like stylesheets.
+pub const SFC_EXTENSIONS: [&str; 2] = ["vue", "svelte"];
 pub const IGNORE_FOLDERS: [&str; 10] = [
     "node_modules",
     "dist",
@@ -14,7 +29,316 @@ pub const IGNORE_FOLDERS: [&str; 10] = [
     "test",
     "output",
 ];
+/// Folders holding vendored third-party code, whose imports are not the project's own usage.
+pub const VENDORED_FOLDERS: [&str; 2] = ["vendor", "third_party"];
 pub const TYPESCRIPT_EXTENSIONS: [&str; 4] = ["ts", "tsx", "d.ts", "cts"];
+/// The type declarations of the Node.js runtime, used by the compiler without any import, so never
+/// reported as unused or orphaned.
+pub const NODE_TYPES_PACKAGE: &str = "@types/node";
+pub const NODE_BUILTINS: [&str; 41] = [
+    "assert",
+    "async_hooks",
+    "buffer",
+    "child_process",
+    "cluster",
+    "console",
+    "constants",
+    "crypto",
+    "dgram",
+    "diagnostics_channel",
+    "dns",
+    "domain",
+    "events",
+    "fs",
+    "http",
+    "http2",
+    "https",
+    "inspector",
+    "module",
+    "net",
+    "os",
+    "path",
+    "perf_hooks",
+    "process",
+    "punycode",
+    "querystring",
+    "readline",
+    "repl",
+    "stream",
+    "string_decoder",
+    "sys",
+    "timers",
+    "tls",
+    "trace_events",
+    "tty",
+    "url",
+    "util",
+    "v8",
+    "vm",
+    "worker_threads",
+    "zlib",
+];
+pub const CONFIG_FILE_NAME: &str = "cnp.toml";
+/// Seconds `tsc` may run before the TypeScript analysis falls back to plain import matching.
+pub const DEFAULT_TSC_TIMEOUT_SECS: u64 = 60;
+pub const SIDE_EFFECT_PACKAGES: [&str; 5] = [
+    "core-js",
+    "regenerator-runtime",
+    "whatwg-fetch",
+    "reflect-metadata",
+    "@babel/polyfill",
+];
+/// Companion packages that frameworks use through their conventions (peer runtimes, image
+/// optimization, lint presets) rather than through imports, keyed by framework package. Projects
+/// add their own with the `framework_companions` table of `cnp.toml`.
+pub const FRAMEWORK_COMPANIONS: [(&str, &[&str]); 5] = [
+    (
+        "next",
+        &[
+            "react",
+            "react-dom",
+            "sharp",
+            "eslint-config-next",
+            "@next/eslint-plugin-next",
+        ],
+    ),
+    (
+        "nuxt",
+        &["vue", "vue-router", "@nuxt/devtools", "@nuxt/kit"],
+    ),
+    (
+        "@remix-run/react",
+        &[
+            "react",
+            "react-dom",
+            "@remix-run/node",
+            "@remix-run/serve",
+            "@remix-run/dev",
+            "isbot",
+        ],
+    ),
+    ("astro", &["sharp", "@astrojs/check"]),
+    (
+        "@sveltejs/kit",
+        &[
+            "svelte",
+            "@sveltejs/adapter-auto",
+            "@sveltejs/vite-plugin-svelte",
+        ],
+    ),
+];
+/// Packages serving the same purpose, reported by `--check-duplicates-in-code` when several
+/// members of a group are imported. Projects add their own with the `duplicate_purpose_groups`
+/// setting of `cnp.toml`.
+pub const DUPLICATE_PURPOSE_GROUPS: [&[&str]; 5] = [
+    &["lodash", "lodash-es", "underscore"],
+    &["moment", "dayjs", "date-fns", "luxon"],
+    &["axios", "node-fetch", "got", "ky", "superagent"],
+    &["uuid", "nanoid"],
+    &["bcrypt", "bcryptjs"],
+];
+pub const LIFECYCLE_SCRIPTS: [&str; 3] = ["preinstall", "install", "postinstall"];
+/// Tooling directories whose files reference devDependencies by name (addons, hook commands)
+/// rather than only through imports.
+pub const TOOLING_CONFIG_DIRS: [&str; 2] = [".storybook", ".husky"];
+/// Tooling configuration files at the project root that reference devDependencies by name, such
+/// as GraphQL Code Generator plugins and presets.
+pub const TOOLING_CONFIG_FILES: [&str; 7] = [
+    "codegen.yml",
+    "codegen.yaml",
+    "codegen.json",
+    "codegen.ts",
+    "codegen.js",
+    "codegen.cjs",
+    "codegen.mjs",
+];
+/// Tool configurations that may live in `package.json` itself, with the prefixes their tool adds
+/// to short plugin names (`"presets": ["env"]` loads `babel-preset-env`).
+pub const MANIFEST_CONFIG_FIELDS: [(&str, &[&str]); 7] = [
+    ("babel", &["babel-preset-", "babel-plugin-"]),
+    ("browserslist", &[]),
+    ("eslintConfig", &["eslint-config-", "eslint-plugin-"]),
+    ("postcss", &[]),
+    ("prettier", &["prettier-plugin-"]),
+    ("stylelint", &["stylelint-config-", "stylelint-"]),
+    ("jest", &[]),
+];
+/// Script commands provided by the shell, the system or the package manager rather than by a
+/// dependency's `bin`.
+pub const SYSTEM_COMMANDS: [&str; 40] = [
+    "node", "npm", "npx", "yarn", "pnpm", "pnpx", "bun", "bunx", "corepack", "deno", "cd", "echo",
+    "exit", "export", "exec", "set", "test", "true", "false", "env", "sh", "bash", "rm", "cp",
+    "mv", "mkdir", "touch", "cat", "ls", "chmod", "ln", "sleep", "printf", "sed", "grep", "find",
+    "git", "docker", "make", "curl",
+];
+
+/// Project-level configuration read from `cnp.toml`.
+///
+/// Every field is optional in the file; missing fields fall back to the built-in defaults.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// Packages loaded only for their side effects (polyfills, runtime shims). These are never
+    /// reported as unused, even when no import referencing them is found.
+    pub side_effect_packages: Vec<String>,
+    /// Whether `package.json` must be strict JSON. When `false`, comments and trailing commas
+    /// are tolerated.
+    pub strict_manifest: bool,
+    /// Packages required at runtime without any static import (e.g., `dotenv` preloaded with
+    /// `node -r dotenv/config`). These are merged into the required set and never reported as
+    /// unused.
+    pub runtime_required: Vec<String>,
+    /// Whether `@types/node` is always kept. The compiler loads it without any import, so by
+    /// default it is never reported as unused; when `false`, it is reported like any package.
+    pub keep_types_node: bool,
+    /// Directories holding vendored third-party code, skipped so that their imports never count as
+    /// usage. A name (`vendor`) matches a folder anywhere in the project, a path (`libs/legacy`)
+    /// matches from the project directory.
+    pub vendored: Vec<String>,
+    /// Additional file extensions to scan (e.g., `ejs`, `hbs`), without the leading dot. Their
+    /// files count a package as used when it appears in an `import` or `require` string.
+    pub extra_extensions: Vec<String>,
+    /// Companion packages counted as used while their framework is declared, keyed by framework
+    /// package, in addition to the built-in `FRAMEWORK_COMPANIONS`.
+    pub framework_companions: BTreeMap<String, Vec<String>>,
+    /// Name prefixes of workspace-internal packages (`@myorg/` or `@myorg/*`), linked from the
+    /// monorepo rather than published. They are always required and never reported as unused.
+    pub workspace_packages: Vec<String>,
+    /// Groups of packages serving the same purpose, in addition to the built-in
+    /// `DUPLICATE_PURPOSE_GROUPS`.
+    pub duplicate_purpose_groups: Vec<Vec<String>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            side_effect_packages: SIDE_EFFECT_PACKAGES.iter().map(|s| s.to_string()).collect(),
+            strict_manifest: true,
+            runtime_required: Vec::new(),
+            keep_types_node: true,
+            vendored: VENDORED_FOLDERS.iter().map(|s| s.to_string()).collect(),
+            extra_extensions: Vec::new(),
+            framework_companions: BTreeMap::new(),
+            workspace_packages: Vec::new(),
+            duplicate_purpose_groups: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Checks whether a package is workspace-internal, i.e. its name starts with one of the
+    /// `workspace_packages` prefixes of `cnp.toml`. A trailing `*` is ignored, so `@myorg/*` and
+    /// `@myorg/` match the same packages.
+    ///
+    /// # Arguments
+    ///
+    /// * `package` - The name of the package to check.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the package matches a `workspace_packages` prefix, `false` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let config = Config { workspace_packages: vec!["@myorg/*".to_string()], ..Config::default() };
+    /// assert!(config.is_workspace_package("@myorg/ui"));
+    /// assert!(!config.is_workspace_package("@other/ui"));
+    /// ```
+    pub fn is_workspace_package(&self, package: &str) -> bool {
+        self.workspace_packages.iter().any(|prefix| {
+            let prefix = prefix.trim_end_matches('*');
+            !prefix.is_empty() && package.starts_with(prefix)
+        })
+    }
+
+    /// Collects the companion packages of the declared frameworks.
+    ///
+    /// Both the built-in `FRAMEWORK_COMPANIONS` and the `framework_companions` of `cnp.toml` are
+    /// used. A framework counts as present when it is declared, since frameworks are often only
+    /// run from scripts (`next build`) and never imported.
+    ///
+    /// # Arguments
+    ///
+    /// * `declared` - A reference to the set of packages declared in `package.json`.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `HashSet<String>` with the companions of every declared framework.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let declared = HashSet::from(["next".to_string(), "sharp".to_string()]);
+    /// assert!(Config::default().framework_companions(&declared).contains("sharp"));
+    /// ```
+    pub fn framework_companions(&self, declared: &HashSet<String>) -> HashSet<String> {
+        let built_in = FRAMEWORK_COMPANIONS
+            .iter()
+            .map(|(framework, companions)| (*framework, companions.to_vec()));
+        let configured = self
+            .framework_companions
+            .iter()
+            .map(|(framework, companions)| {
+                (
+                    framework.as_str(),
+                    companions.iter().map(String::as_str).collect(),
+                )
+            });
+
+        built_in
+            .chain(configured)
+            .filter(|(framework, _): &(&str, Vec<&str>)| declared.contains(*framework))
+            .flat_map(|(_, companions)| companions)
+            .map(String::from)
+            .collect()
+    }
+
+    /// Finds the duplicate-purpose groups with more than one member in use.
+    ///
+    /// Both the built-in `DUPLICATE_PURPOSE_GROUPS` and the `duplicate_purpose_groups` of
+    /// `cnp.toml` are checked, so importing both `moment` and `dayjs` reports the date group.
+    ///
+    /// # Arguments
+    ///
+    /// * `used` - A reference to the set of packages imported by the project.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Vec<Vec<String>>` with the used members of each such group, sorted, without
+    /// repeated groups.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let used = HashSet::from(["moment".to_string(), "dayjs".to_string()]);
+    /// assert_eq!(
+    ///     Config::default().duplicate_purposes(&used),
+    ///     vec![vec!["dayjs".to_string(), "moment".to_string()]]
+    /// );
+    /// ```
+    pub fn duplicate_purposes(&self, used: &HashSet<String>) -> Vec<Vec<String>> {
+        let built_in = DUPLICATE_PURPOSE_GROUPS
+            .iter()
+            .map(|group| group.iter().map(|package| package.to_string()).collect());
+        let mut duplicates: Vec<Vec<String>> = built_in
+            .chain(self.duplicate_purpose_groups.iter().cloned())
+            .map(|group: Vec<String>| {
+                let mut members: Vec<String> = group
+                    .into_iter()
+                    .filter(|package| used.contains(package))
+                    .collect();
+                members.sort();
+                members.dedup();
+                members
+            })
+            .filter(|members| members.len() > 1)
+            .collect();
+        duplicates.sort();
+        duplicates.dedup();
+        duplicates
+    }
+}
 
 /// Checks if the current directory is a TypeScript project by looking for a `tsconfig.json` file.
 ///
@@ -38,3 +362,273 @@ pub const TYPESCRIPT_EXTENSIONS: [&str; 4] = ["ts", "tsx", "d.ts", "cts"];
 pub fn is_typescript_project(dir_path: &str) -> bool {
     Path::new(&dir_path).join("tsconfig.json").exists()
 }
+
+/// The resolved command-line options of a run, as shown by `--print-config`.
+///
+/// Fields are named after their flags. Values that are not given are resolved to their defaults,
+/// and are left out of TOML output when there is none.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct Flags {
+    pub paths: Vec<String>,
+    pub workspaces: bool,
+    pub tarball: Option<String>,
+    pub format: String,
+    pub color: String,
+    pub count: bool,
+    pub dry_run: bool,
+    pub interactive: bool,
+    pub preselect_all: bool,
+    pub reinstall: String,
+    pub frozen_install: bool,
+    /// Whether `tsc` runs, resolved from `--tsc`, `--no-tsc`, CI detection and its availability.
+    pub tsc: bool,
+    pub tsc_timeout: u64,
+    /// Whether cached `tsc` diagnostics are reused, `false` with `--no-cache`.
+    pub cache: bool,
+    pub resolver_script: Option<String>,
+    pub threads: usize,
+    pub max_depth: Option<usize>,
+    pub packages: Vec<String>,
+    pub scan_comments_for_pragmas: bool,
+    pub ignore_dev: bool,
+    pub report_types_node: bool,
+    pub use_pm_ls: bool,
+    pub manifest_only: bool,
+    pub lockfile: Option<String>,
+    pub dependencies_from: Option<String>,
+    pub fail_on: Option<String>,
+    pub fail_empty: bool,
+    pub max_warnings: Option<usize>,
+    pub diff_exit_code: bool,
+    pub compare: Option<String>,
+    pub allow_empty: bool,
+    pub no_warnings: bool,
+    pub report_sections: String,
+    pub reporter_columns: Vec<String>,
+    pub summary_line_format: Option<String>,
+    pub summary_json_stderr: bool,
+    /// The `--report-file` reports, as `FORMAT=PATH`.
+    pub report_files: Vec<String>,
+    pub types_only: bool,
+    pub audit_scripts: bool,
+    pub explain_ignored: bool,
+    pub profile: bool,
+    pub stats: bool,
+    pub git_age: bool,
+    pub dedupe_check: bool,
+    pub bin: bool,
+    pub check_entrypoints: bool,
+    pub check_duplicates_in_code: bool,
+    pub watch: bool,
+}
+
+/// The fully-resolved configuration of a run: built-in defaults, `cnp.toml`, `.cnpignore` and
+/// command-line flags combined. Flags overriding a `cnp.toml` setting (`--report-types-node` for
+/// `keep_types_node`) take precedence in `config`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EffectiveConfig {
+    pub extensions: Vec<String>,
+    pub ignore_folders: Vec<String>,
+    pub typescript_extensions: Vec<String>,
+    /// Packages listed in `.cnpignore`, sorted by name.
+    pub ignored_packages: Vec<String>,
+    #[serde(flatten)]
+    pub config: Config,
+    pub flags: Flags,
+}
+
+impl EffectiveConfig {
+    /// Resolves the effective configuration of the project in the given directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir_path` - A string slice representing the path to the project directory.
+    /// * `flags` - The command-line `Flags` of the run.
+    ///
+    /// # Returns
+    ///
+    /// Returns the `EffectiveConfig` combining defaults, `cnp.toml`, `.cnpignore` and `flags`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let effective = EffectiveConfig::resolve(".", flags);
+    /// println!("{}", effective.render(OutputFormat::Console));
+    /// ```
+    pub fn resolve(dir_path: &str, flags: Flags) -> Self {
+        let cnp_ignore_path = Path::new(dir_path).join(".cnpignore");
+        let mut ignored_packages: Vec<String> =
+            read_cnpignore(cnp_ignore_path.to_str().unwrap_or_default())
+                .into_iter()
+                .collect();
+        ignored_packages.sort();
+        let config = load_config(dir_path);
+
+        EffectiveConfig {
+            extensions: EXTENSIONS.iter().map(|s| s.to_string()).collect(),
+            ignore_folders: IGNORE_FOLDERS.iter().map(|s| s.to_string()).collect(),
+            typescript_extensions: TYPESCRIPT_EXTENSIONS
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            ignored_packages,
+            config: Config {
+                keep_types_node: config.keep_types_node && !flags.report_types_node,
+                ..config
+            },
+            flags,
+        }
+    }
+
+    /// Renders the effective configuration as TOML (console format) or pretty-printed JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The `OutputFormat` to render the configuration in.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `String` containing the rendered configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let effective = EffectiveConfig::resolve(".", flags);
+    /// println!("{}", effective.render(OutputFormat::Json));
+    /// ```
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Console
+            | OutputFormat::Github
+            | OutputFormat::Junit
+            | OutputFormat::Html
+            | OutputFormat::Toml => {
+                toml::to_string_pretty(self).expect("Failed to serialize configuration")
+            }
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(self).expect("Failed to serialize configuration")
+            }
+        }
+    }
+}
+
+/// Loads the `cnp.toml` configuration from the given project directory.
+///
+/// If the file does not exist, the default configuration is returned. If the file exists but
+/// cannot be parsed, a warning is printed and the default configuration is returned.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+///
+/// # Returns
+///
+/// Returns the `Config` read from `cnp.toml`, or `Config::default()` if it is absent or invalid.
+///
+/// # Examples
+///
+/// ```
+/// let config = load_config(".");
+/// println!("Side-effect packages: {:?}", config.side_effect_packages);
+/// ```
+pub fn load_config(dir_path: &str) -> Config {
+    let mut warnings = Warnings::default();
+    let config = load_config_with_warnings(dir_path, &mut warnings);
+    for warning in warnings.iter() {
+        eprintln!("{}: {}", "Warning".yellow().bold(), warning);
+    }
+    config
+}
+
+/// Loads the `cnp.toml` configuration from the given project directory, recording a warning
+/// instead of printing it when the file cannot be parsed.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `warnings` - The `Warnings` the invalid configuration warning is added to.
+///
+/// # Returns
+///
+/// Returns the `Config` read from `cnp.toml`, or `Config::default()` if it is absent or invalid.
+///
+/// # Examples
+///
+/// ```
+/// let mut warnings = Warnings::default();
+/// let config = load_config_with_warnings(".", &mut warnings);
+/// assert!(warnings.is_empty());
+/// ```
+pub fn load_config_with_warnings(dir_path: &str, warnings: &mut Warnings) -> Config {
+    let config_path = Path::new(dir_path).join(CONFIG_FILE_NAME);
+    let Ok(content) = fs::read_to_string(&config_path) else {
+        return Config::default();
+    };
+
+    toml::from_str(&content).unwrap_or_else(|err| {
+        warnings.push(format!(
+            "Invalid {} ({}). Using default configuration.",
+            CONFIG_FILE_NAME,
+            err.message()
+        ));
+        Config::default()
+    })
+}
+
+/// Reads the project's `tsconfig.json`, tolerating comments and trailing commas.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+///
+/// # Returns
+///
+/// Returns `Some(Value)` with the parsed `tsconfig.json`, or `None` if the file does not exist or
+/// cannot be parsed.
+///
+/// # Examples
+///
+/// ```
+/// if let Some(tsconfig) = read_tsconfig(".") {
+///     println!("Compiler options: {:?}", tsconfig["compilerOptions"]);
+/// }
+/// ```
+pub fn read_tsconfig(dir_path: &str) -> Option<Value> {
+    let tsconfig_path = Path::new(dir_path).join("tsconfig.json");
+    read_jsonc(tsconfig_path.to_str()?).ok()
+}
+
+/// Returns the import prefixes declared as path aliases in `tsconfig.json`.
+///
+/// Each key of `compilerOptions.paths` is returned without its trailing `*` wildcard, so
+/// `"@/*"` yields `"@/"` and an exact alias such as `"utils"` is returned unchanged.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+///
+/// # Returns
+///
+/// Returns a `Vec<String>` of alias prefixes. Returns an empty vector if there is no
+/// `tsconfig.json` or it declares no `paths`.
+///
+/// # Examples
+///
+/// ```
+/// // tsconfig.json: { "compilerOptions": { "paths": { "@/*": ["./src/*"] } } }
+/// assert_eq!(tsconfig_path_aliases("."), vec!["@/".to_string()]);
+/// ```
+pub fn tsconfig_path_aliases(dir_path: &str) -> Vec<String> {
+    read_tsconfig(dir_path)
+        .as_ref()
+        .and_then(|tsconfig| tsconfig.get("compilerOptions"))
+        .and_then(|options| options.get("paths"))
+        .and_then(Value::as_object)
+        .map(|paths| {
+            paths
+                .keys()
+                .map(|alias| alias.trim_end_matches('*').to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
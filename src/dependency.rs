@@ -1,10 +1,17 @@
-use colored::*;
 use serde_json::Value;
-use std::collections::HashSet;
+use serde_json::error::Category;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fs;
+use std::io;
 use std::path::Path;
 
-use crate::utils::get_file_name_and_extension;
+use crate::config::{
+    LIFECYCLE_SCRIPTS, MANIFEST_CONFIG_FIELDS, NODE_TYPES_PACKAGE, PACKAGE_JSON_PATH,
+    SYSTEM_COMMANDS, TOOLING_CONFIG_DIRS, TOOLING_CONFIG_FILES,
+};
+use crate::resolve::specifier_to_package;
+use crate::utils::{directory_size, get_file_name_and_extension, strip_jsonc};
+use crate::warnings::Warnings;
 
 /// Reads and parses a `package.json` file into a JSON value.
 ///
@@ -15,7 +22,8 @@ use crate::utils::get_file_name_and_extension;
 /// # Returns
 ///
 /// Returns `Ok(Value)` containing the parsed JSON if successful.
-/// Returns `Err(String)` with an error message if the file is not found or contains invalid JSON.
+/// Returns `Err(String)` with an error message if the file cannot be read (not found, permission
+/// denied, ...) or contains invalid JSON, in which case the message gives the line and column.
 ///
 /// # Examples
 ///
@@ -28,19 +36,1457 @@ use crate::utils::get_file_name_and_extension;
 pub fn read_package_json(path: &str) -> Result<Value, String> {
     let file_name_and_extension = get_file_name_and_extension(path).unwrap_or_default();
     let content = fs::read_to_string(path)
-        .map_err(|_| format!("Error: `{}` not found.", file_name_and_extension.0))?;
-    serde_json::from_str(&content).map_err(|_| "Error: Invalid JSON in package.json.".to_string())
+        .map_err(|err| describe_read_error(&file_name_and_extension.0, &err))?;
+    serde_json::from_str(&content).map_err(|err| describe_json_error(PACKAGE_JSON_PATH, &err))
+}
+
+/// Describes why a file could not be read, telling a missing file apart from a permission
+/// problem.
+///
+/// # Arguments
+///
+/// * `file_name` - The name of the file shown in the message.
+/// * `err` - A reference to the `io::Error` returned when reading the file.
+///
+/// # Returns
+///
+/// Returns the error message as a `String`.
+///
+/// # Examples
+///
+/// ```
+/// let err = io::Error::from(io::ErrorKind::PermissionDenied);
+/// assert_eq!(
+///     describe_read_error("package.json", &err),
+///     "Error: Permission denied reading `package.json`."
+/// );
+/// ```
+pub fn describe_read_error(file_name: &str, err: &io::Error) -> String {
+    match err.kind() {
+        io::ErrorKind::NotFound => format!("Error: `{}` not found.", file_name),
+        io::ErrorKind::PermissionDenied => {
+            format!("Error: Permission denied reading `{}`.", file_name)
+        }
+        _ => format!("Error: Could not read `{}` ({}).", file_name, err),
+    }
+}
+
+/// Describes why a JSON file could not be parsed, including the line and column of the error.
+///
+/// # Arguments
+///
+/// * `file_name` - The name of the file shown in the message.
+/// * `err` - A reference to the `serde_json::Error` returned when parsing the file.
+///
+/// # Returns
+///
+/// Returns the error message as a `String`.
+///
+/// # Examples
+///
+/// ```
+/// let err = serde_json::from_str::<Value>("{ \"name\": }").unwrap_err();
+/// assert_eq!(
+///     describe_json_error("package.json", &err),
+///     "Error: Invalid JSON in package.json. Syntax error at line 1, column 11."
+/// );
+/// ```
+pub fn describe_json_error(file_name: &str, err: &serde_json::Error) -> String {
+    let kind = match err.classify() {
+        Category::Syntax => "Syntax error",
+        Category::Eof => "Unexpected end of file",
+        Category::Data => "Invalid data",
+        Category::Io => "Read error",
+    };
+    format!(
+        "Error: Invalid JSON in {}. {} at line {}, column {}.",
+        file_name,
+        kind,
+        err.line(),
+        err.column()
+    )
+}
+
+/// Reads and parses a JSONC file (JSON with comments and trailing commas) into a JSON value.
+///
+/// This is the lenient counterpart of [`read_package_json`], used for files such as
+/// `tsconfig.json` that commonly contain comments, or for `package.json` when strict parsing is
+/// disabled in `cnp.toml`.
+///
+/// # Arguments
+///
+/// * `path` - A string slice representing the path to the JSONC file.
+///
+/// # Returns
+///
+/// Returns `Ok(Value)` containing the parsed JSON if successful.
+/// Returns `Err(String)` with an error message if the file is not found or contains invalid JSONC.
+///
+/// # Examples
+///
+/// ```
+/// match read_jsonc("tsconfig.json") {
+///     Ok(json) => println!("Compiler options: {:?}", json["compilerOptions"]),
+///     Err(e) => eprintln!("Failed to read tsconfig.json: {}", e),
+/// }
+/// ```
+pub fn read_jsonc(path: &str) -> Result<Value, String> {
+    let (file_name, _) = get_file_name_and_extension(path).unwrap_or_default();
+    let content = fs::read_to_string(path).map_err(|err| describe_read_error(&file_name, &err))?;
+    serde_json::from_str(&strip_jsonc(&content))
+        .map_err(|err| describe_json_error(&file_name, &err))
+}
+
+/// Manifest fields whose entries count as declared packages.
+pub const DECLARED_DEPENDENCY_FIELDS: [&str; 4] = [
+    "dependencies",
+    "devDependencies",
+    "peerDependencies",
+    "optionalDependencies",
+];
+
+/// Collects every package declared in any dependency field of a `package.json`.
+///
+/// # Arguments
+///
+/// * `package_json` - A reference to the parsed `package.json`.
+///
+/// # Returns
+///
+/// Returns a `HashSet<String>` with the package names found in `dependencies`,
+/// `devDependencies`, `peerDependencies` and `optionalDependencies`.
+///
+/// # Examples
+///
+/// ```
+/// let package_json = read_package_json("package.json")?;
+/// let declared = declared_packages(&package_json);
+/// println!("Declared packages: {:?}", declared);
+/// ```
+pub fn declared_packages(package_json: &Value) -> HashSet<String> {
+    DECLARED_DEPENDENCY_FIELDS
+        .iter()
+        .filter_map(|field| package_json.get(field).and_then(Value::as_object))
+        .flat_map(|map| map.keys().cloned())
+        .collect()
+}
+
+/// Finds the dependency fields of a `package.json` that exist but are not objects.
+///
+/// Such fields (e.g., `"dependencies": []` from a bad generator) declare no package, which would
+/// otherwise look like a genuinely empty field.
+///
+/// # Arguments
+///
+/// * `package_json` - A reference to the parsed `package.json`.
+///
+/// # Returns
+///
+/// Returns a `Vec<String>` with one message per malformed field, naming the field and its type.
+///
+/// # Examples
+///
+/// ```
+/// let package_json = json!({ "dependencies": [], "devDependencies": {} });
+/// assert_eq!(
+///     malformed_dependency_fields(&package_json),
+///     vec!["`dependencies` in package.json is an array instead of an object; it is treated as empty.".to_string()]
+/// );
+/// ```
+pub fn malformed_dependency_fields(package_json: &Value) -> Vec<String> {
+    DECLARED_DEPENDENCY_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let kind = match package_json.get(field)? {
+                Value::Object(_) => return None,
+                Value::Array(_) => "an array",
+                Value::String(_) => "a string",
+                Value::Number(_) => "a number",
+                Value::Bool(_) => "a boolean",
+                Value::Null => "null",
+            };
+            Some(format!(
+                "`{}` in package.json is {} instead of an object; it is treated as empty.",
+                field, kind
+            ))
+        })
+        .collect()
+}
+
+/// Manifest fields listing packages bundled into the published tarball (both npm spellings).
+pub const BUNDLED_DEPENDENCY_FIELDS: [&str; 2] = ["bundledDependencies", "bundleDependencies"];
+
+/// Collects every package listed in the bundled dependency arrays of a `package.json`.
+///
+/// Bundled packages ship inside the published tarball, so they must never be reported as unused.
+/// The boolean form (`"bundledDependencies": true`) bundles every dependency and lists no names.
+///
+/// # Arguments
+///
+/// * `package_json` - A reference to the parsed `package.json`.
+///
+/// # Returns
+///
+/// Returns a `HashSet<String>` with the package names found in `bundledDependencies` and
+/// `bundleDependencies`.
+///
+/// # Examples
+///
+/// ```
+/// let package_json = json!({ "bundledDependencies": ["a", "b"] });
+/// assert!(bundled_packages(&package_json).contains("a"));
+/// ```
+pub fn bundled_packages(package_json: &Value) -> HashSet<String> {
+    BUNDLED_DEPENDENCY_FIELDS
+        .iter()
+        .filter_map(|field| package_json.get(field).and_then(Value::as_array))
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Returns the base package described by an `@types/*` package name.
+///
+/// Scoped base packages follow the DefinitelyTyped `__` convention, so `@types/babel__core`
+/// describes `@babel/core` and `@types/apollo__client` describes `@apollo/client`.
+///
+/// # Arguments
+///
+/// * `types_package` - The name of the `@types/*` package.
+///
+/// # Returns
+///
+/// Returns `Some(String)` with the base package name, or `None` if the name is not a well-formed
+/// `@types/*` package.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(types_base_package("@types/lodash"), Some("lodash".to_string()));
+/// assert_eq!(types_base_package("@types/babel__core"), Some("@babel/core".to_string()));
+/// assert_eq!(types_base_package("@types/babel__"), None);
+/// assert_eq!(types_base_package("lodash"), None);
+/// ```
+pub fn types_base_package(types_package: &str) -> Option<String> {
+    let name = types_package.strip_prefix("@types/")?;
+    if name.is_empty() {
+        return None;
+    }
+
+    match name.split_once("__") {
+        Some((scope, package)) if !scope.is_empty() && !package.is_empty() => {
+            Some(format!("@{}/{}", scope, package))
+        }
+        Some(_) => None,
+        None => Some(name.to_string()),
+    }
+}
+
+/// Returns the package targeted by an `overrides`, `resolutions` or `pnpm.overrides` key.
+///
+/// Keys may carry a version range (`lodash@^4`), a dependency path (`**/lodash`,
+/// `webpack/terser`, `parent>child`), or both; the last package of the path is the target.
+///
+/// # Arguments
+///
+/// * `key` - The override key.
+///
+/// # Returns
+///
+/// Returns `Some(String)` with the targeted package name, or `None` if the key names no package
+/// (e.g., npm's `.` self-override).
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(override_package_name("lodash@^4"), Some("lodash".to_string()));
+/// assert_eq!(override_package_name("**/@babel/core"), Some("@babel/core".to_string()));
+/// assert_eq!(override_package_name("webpack>terser"), Some("terser".to_string()));
+/// ```
+pub fn override_package_name(key: &str) -> Option<String> {
+    let path = key.rsplit('>').next().unwrap_or(key).trim();
+    let segments: Vec<&str> = path.split('/').collect();
+    let target = match segments.as_slice() {
+        [.., scope, name] if scope.starts_with('@') => format!("{}/{}", scope, name),
+        [.., name] => name.to_string(),
+        [] => return None,
+    };
+    let name = match target.rfind('@') {
+        Some(index) if index > 0 => &target[..index],
+        _ => &target,
+    };
+
+    specifier_to_package(name).filter(|name| name != "**")
+}
+
+/// Collects the packages pinned by the `overrides` (npm), `resolutions` (yarn) and
+/// `pnpm.overrides` fields of a `package.json`.
+///
+/// Nested npm overrides are followed, so `{ "react": { "loose-envify": "1.4.0" } }` targets both
+/// `react` and `loose-envify`. These packages are usually transitive: they are never treated as
+/// declared or required dependencies.
+///
+/// # Arguments
+///
+/// * `package_json` - A reference to the parsed `package.json`.
+///
+/// # Returns
+///
+/// Returns a sorted `Vec<String>` of the overridden package names, without duplicates.
+///
+/// # Examples
+///
+/// ```
+/// let package_json = json!({ "overrides": { "lodash": "4.17.21" }, "resolutions": { "**/minimist": "1.2.8" } });
+/// assert_eq!(override_targets(&package_json), vec!["lodash".to_string(), "minimist".to_string()]);
+/// ```
+pub fn override_targets(package_json: &Value) -> Vec<String> {
+    fn collect(overrides: &Value, targets: &mut BTreeSet<String>) {
+        if let Some(map) = overrides.as_object() {
+            for (key, value) in map {
+                targets.extend(override_package_name(key));
+                collect(value, targets);
+            }
+        }
+    }
+
+    let mut targets = BTreeSet::new();
+    for overrides in [
+        package_json.get("overrides"),
+        package_json.get("resolutions"),
+        package_json.pointer("/pnpm/overrides"),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        collect(overrides, &mut targets);
+    }
+    targets.into_iter().collect()
+}
+
+/// Finds override targets that no longer belong to the project's dependency tree.
+///
+/// An override is stale when its package is neither declared in `package.json`, nor listed in the
+/// required set (which includes lockfile packages), nor installed in `node_modules`. Without a
+/// lockfile or `node_modules`, only declared packages are known.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `package_json` - A reference to the parsed `package.json`.
+/// * `required` - A reference to the required dependencies of the project.
+///
+/// # Returns
+///
+/// Returns a sorted `Vec<String>` of the stale override targets.
+///
+/// # Examples
+///
+/// ```
+/// let required = get_required_dependencies(".", false, None, &mut Warnings::default());
+/// for package in find_stale_overrides(".", &package_json, &required) {
+///     println!("Override of '{}' no longer applies", package);
+/// }
+/// ```
+pub fn find_stale_overrides(
+    dir_path: &str,
+    package_json: &Value,
+    required: &HashSet<String>,
+) -> Vec<String> {
+    let declared = declared_packages(package_json);
+    let node_modules = Path::new(dir_path).join("node_modules");
+
+    override_targets(package_json)
+        .into_iter()
+        .filter(|package| {
+            !declared.contains(package)
+                && !required.contains(package)
+                && !node_modules.join(package).is_dir()
+        })
+        .collect()
+}
+
+/// Finds the line of `package.json` on which a package is declared.
+///
+/// # Arguments
+///
+/// * `manifest` - A string slice containing the raw `package.json` content.
+/// * `package` - The name of the declared package.
+///
+/// # Returns
+///
+/// Returns `Some(usize)` with the 1-based line number of the first `"<package>":` key, or `None`
+/// if the package is not found.
+///
+/// # Examples
+///
+/// ```
+/// let manifest = "{\n  \"dependencies\": {\n    \"lodash\": \"^4.17.21\"\n  }\n}";
+/// assert_eq!(find_dependency_line(manifest, "lodash"), Some(3));
+/// assert_eq!(find_dependency_line(manifest, "react"), None);
+/// ```
+pub fn find_dependency_line(manifest: &str, package: &str) -> Option<usize> {
+    let key = format!("\"{}\"", package);
+    manifest
+        .lines()
+        .position(|line| {
+            line.trim_start()
+                .strip_prefix(&key)
+                .is_some_and(|rest| rest.trim_start().starts_with(':'))
+        })
+        .map(|index| index + 1)
+}
+
+/// Checks whether a package is the `@types/*` package of a used dependency.
+///
+/// Types packages are never imported directly, so they must be kept while their base package (see
+/// [`types_base_package`]) is in use.
+///
+/// # Arguments
+///
+/// * `package` - The name of the declared package.
+/// * `used_packages` - A reference to the `HashSet<String>` of used dependencies.
+///
+/// # Returns
+///
+/// Returns `true` if `package` is an `@types/*` package whose base package is used, `false`
+/// otherwise.
+///
+/// # Examples
+///
+/// ```
+/// let used = HashSet::from(["@babel/core".to_string()]);
+/// assert!(is_types_of_used_package("@types/babel__core", &used));
+/// assert!(!is_types_of_used_package("@types/lodash", &used));
+/// ```
+pub fn is_types_of_used_package(package: &str, used_packages: &HashSet<String>) -> bool {
+    types_base_package(package).is_some_and(|base| used_packages.contains(&base))
+}
+
+/// Finds declared `@types/*` packages whose base package is no longer declared.
+///
+/// A types package is orphaned when its base package (see [`types_base_package`]) is absent from
+/// every dependency field of `package.json`. `@types/node` describes the Node.js runtime rather
+/// than a package and is never reported.
+///
+/// # Arguments
+///
+/// * `package_json` - A reference to the parsed `package.json`.
+///
+/// # Returns
+///
+/// Returns a sorted `Vec<String>` of orphaned `@types/*` package names.
+///
+/// # Examples
+///
+/// ```
+/// // package.json declares "@types/lodash" but no longer declares "lodash"
+/// let package_json = read_package_json("package.json")?;
+/// assert_eq!(find_orphaned_types(&package_json), vec!["@types/lodash".to_string()]);
+/// ```
+pub fn find_orphaned_types(package_json: &Value) -> Vec<String> {
+    let declared = declared_packages(package_json);
+    let mut orphaned: Vec<String> = declared
+        .iter()
+        .filter(|package| package.as_str() != NODE_TYPES_PACKAGE)
+        .filter(|package| types_base_package(package).is_some_and(|base| !declared.contains(&base)))
+        .cloned()
+        .collect();
+    orphaned.sort();
+    orphaned
+}
+
+/// Node.js flags whose argument is a module preloaded before the entry point.
+const PRELOAD_FLAGS: [&str; 5] = [
+    "-r",
+    "--require",
+    "--import",
+    "--loader",
+    "--experimental-loader",
+];
+
+/// Collects the packages preloaded with Node.js flags in a command line.
+///
+/// Both `-r pkg` and `--require=pkg` forms are recognized for every preload flag (`-r`,
+/// `--require`, `--import`, `--loader`, `--experimental-loader`), including inside a quoted
+/// `NODE_OPTIONS` assignment. Subpath arguments are reduced to their package name
+/// (`dotenv/config` -> `dotenv`), and relative paths are skipped.
+///
+/// # Arguments
+///
+/// * `command` - A string slice containing the command line (e.g., a `package.json` script).
+///
+/// # Returns
+///
+/// Returns a `HashSet<String>` containing the preloaded package names.
+///
+/// # Examples
+///
+/// ```
+/// let found = find_preloaded_packages("node -r dotenv/config --import tsx src/index.ts");
+/// assert_eq!(found, HashSet::from(["dotenv".to_string(), "tsx".to_string()]));
+/// ```
+pub fn find_preloaded_packages(command: &str) -> HashSet<String> {
+    let unquoted = command.replace(['"', '\''], " ");
+    let mut tokens = unquoted.split_whitespace().map(|token| {
+        // Strip environment assignments such as `NODE_OPTIONS=--require`
+        match token.split_once('=') {
+            Some((name, value)) if !name.starts_with('-') => value,
+            _ => token,
+        }
+    });
+
+    let mut packages = HashSet::new();
+    while let Some(token) = tokens.next() {
+        let argument = if PRELOAD_FLAGS.contains(&token) {
+            tokens.next()
+        } else {
+            token
+                .split_once('=')
+                .filter(|(flag, _)| PRELOAD_FLAGS.contains(flag))
+                .map(|(_, value)| value)
+        };
+
+        if let Some(package) = argument.and_then(specifier_to_package) {
+            packages.insert(package);
+        }
+    }
+
+    packages
+}
+
+/// Collects the packages preloaded with Node.js flags by the project's scripts.
+///
+/// This function reads every `package.json` script and, if present, the project's `.node-flags`
+/// file, and extracts the packages passed to preload flags (see [`find_preloaded_packages`]).
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `package_json` - A reference to the parsed `package.json`.
+///
+/// # Returns
+///
+/// Returns a `HashSet<String>` containing the preloaded package names.
+///
+/// # Examples
+///
+/// ```
+/// // package.json: { "scripts": { "start": "node -r dotenv/config index.js" } }
+/// let package_json = read_package_json("package.json")?;
+/// assert!(script_preloaded_packages(".", &package_json).contains("dotenv"));
+/// ```
+pub fn script_preloaded_packages(dir_path: &str, package_json: &Value) -> HashSet<String> {
+    let mut packages: HashSet<String> = package_json
+        .get("scripts")
+        .and_then(Value::as_object)
+        .map(|scripts| {
+            scripts
+                .values()
+                .filter_map(Value::as_str)
+                .flat_map(find_preloaded_packages)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if let Ok(node_flags) = fs::read_to_string(Path::new(dir_path).join(".node-flags")) {
+        packages.extend(find_preloaded_packages(&node_flags));
+    }
+
+    packages
+}
+
+/// Finds the packages invoked by name in the `scripts` of a `package.json`.
+///
+/// Command-line tools are usually run from scripts rather than imported, so a package counts as
+/// referenced when a script word equals its full name (`npx @biomejs/biome check`) or one of the
+/// commands its installed `package.json` declares in `bin` (`biome check`, `tsc` for
+/// `typescript`). A package that is not installed is only matched by its full name, so a word
+/// merely equal to its unscoped name (`build` for `@acme/build`) does not count.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `package_json` - A reference to the parsed `package.json`.
+/// * `candidates` - A reference to the package names to look for.
+///
+/// # Returns
+///
+/// Returns a `HashSet<String>` with the candidates referenced by at least one script.
+///
+/// # Examples
+///
+/// ```
+/// // node_modules/@biomejs/biome/package.json: "bin": { "biome": "bin/biome" }
+/// let package_json = json!({ "scripts": { "lint": "eslint . && biome check" } });
+/// let candidates = HashSet::from(["eslint".to_string(), "@biomejs/biome".to_string()]);
+/// assert_eq!(script_referenced_packages(".", &package_json, &candidates), candidates);
+/// ```
+pub fn script_referenced_packages(
+    dir_path: &str,
+    package_json: &Value,
+    candidates: &HashSet<String>,
+) -> HashSet<String> {
+    let words: HashSet<&str> = package_json
+        .get("scripts")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|scripts| scripts.values().filter_map(Value::as_str))
+        .flat_map(|script| script.split(|c: char| c.is_whitespace() || "&|;()'\"=`".contains(c)))
+        .collect();
+
+    candidates
+        .iter()
+        .filter(|package| {
+            words.contains(package.as_str())
+                || installed_package_bins(dir_path, package)
+                    .iter()
+                    .any(|bin| words.contains(bin.as_str()))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Finds the packages referenced by tooling configuration files.
+///
+/// Directories such as `.storybook` and `.husky` name their packages in strings (Storybook
+/// `addons: ["@storybook/addon-essentials"]`) or run them as commands (`npx lint-staged`) instead
+/// of importing them. Every file of the `TOOLING_CONFIG_DIRS` is searched, recursively, along with
+/// the `TOOLING_CONFIG_FILES` at the project root, for quoted specifiers and command words naming
+/// one of the candidates. GraphQL Code Generator configurations (`codegen.*`) may also use the
+/// short plugin and preset names it resolves itself (`typescript` for
+/// `@graphql-codegen/typescript`, `client` for `@graphql-codegen/client-preset`).
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `candidates` - A reference to the package names to look for.
+///
+/// # Returns
+///
+/// Returns a `HashSet<String>` with the candidates referenced by at least one tooling file.
+///
+/// # Examples
+///
+/// ```
+/// // .storybook/main.ts: addons: ["@storybook/addon-essentials"]
+/// let candidates = HashSet::from(["@storybook/addon-essentials".to_string()]);
+/// assert_eq!(tooling_config_packages(".", &candidates), candidates);
+/// ```
+pub fn tooling_config_packages(dir_path: &str, candidates: &HashSet<String>) -> HashSet<String> {
+    fn collect_files(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => collect_files(&path, files),
+                Ok(file_type) if file_type.is_file() => files.push(path),
+                _ => {}
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    for dir in TOOLING_CONFIG_DIRS {
+        collect_files(&Path::new(dir_path).join(dir), &mut files);
+    }
+    files.extend(
+        TOOLING_CONFIG_FILES
+            .iter()
+            .map(|file| Path::new(dir_path).join(file))
+            .filter(|path| path.is_file()),
+    );
+
+    let mut packages = HashSet::new();
+    for file in &files {
+        let Ok(content) = fs::read_to_string(file) else {
+            continue;
+        };
+        let words: Vec<&str> = content
+            .split(|c: char| c.is_whitespace() || "&|;(),[]{}'\"=`".contains(c))
+            .collect();
+        packages.extend(
+            words
+                .iter()
+                .filter_map(|word| specifier_to_package(word))
+                .filter(|package| candidates.contains(package)),
+        );
+
+        if file.file_stem().is_some_and(|stem| stem == "codegen") {
+            for word in words.iter().map(|word| word.trim_end_matches(':')) {
+                for name in [
+                    format!("@graphql-codegen/{}", word),
+                    format!("@graphql-codegen/{}-preset", word),
+                ] {
+                    if candidates.contains(&name) {
+                        packages.insert(name);
+                    }
+                }
+            }
+        }
+    }
+    packages
+}
+
+/// Finds the packages referenced by the tool configurations embedded in a `package.json`.
+///
+/// Tools such as Babel, ESLint or PostCSS read their configuration from a field of the manifest
+/// (see `MANIFEST_CONFIG_FIELDS`), which names presets and plugins instead of importing them. Every
+/// string and object key of these fields is looked up, nested arrays and objects included, both as
+/// written and with the short-name prefixes of its tool (`"presets": ["env"]` for
+/// `babel-preset-env`).
+///
+/// # Arguments
+///
+/// * `package_json` - A reference to the parsed `package.json`.
+/// * `candidates` - A reference to the package names to look for.
+///
+/// # Returns
+///
+/// Returns a `HashSet<String>` with the candidates referenced by at least one configuration field.
+///
+/// # Examples
+///
+/// ```
+/// let package_json = json!({ "babel": { "presets": [["@babel/preset-env", { "loose": true }]] } });
+/// let candidates = HashSet::from(["@babel/preset-env".to_string()]);
+/// assert_eq!(manifest_config_packages(&package_json, &candidates), candidates);
+/// ```
+pub fn manifest_config_packages(
+    package_json: &Value,
+    candidates: &HashSet<String>,
+) -> HashSet<String> {
+    fn collect_names<'a>(value: &'a Value, names: &mut Vec<&'a str>) {
+        match value {
+            Value::String(name) => names.push(name),
+            Value::Array(items) => items.iter().for_each(|item| collect_names(item, names)),
+            Value::Object(map) => {
+                for (key, item) in map {
+                    names.push(key);
+                    collect_names(item, names);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut packages = HashSet::new();
+    for (field, prefixes) in MANIFEST_CONFIG_FIELDS {
+        let Some(config) = package_json.get(field) else {
+            continue;
+        };
+        let mut names = Vec::new();
+        collect_names(config, &mut names);
+        for name in names {
+            let mut references: Vec<String> = specifier_to_package(name).into_iter().collect();
+            if !name.starts_with('@') {
+                references.extend(prefixes.iter().map(|prefix| format!("{}{}", prefix, name)));
+            }
+            packages.extend(
+                references
+                    .into_iter()
+                    .filter(|package| candidates.contains(package)),
+            );
+        }
+    }
+    packages
+}
+
+/// Measures the disk footprint of installed dependencies.
+///
+/// Each size is the total size of the `node_modules/<dependency>` directory, so nested
+/// dependencies installed inside it are included but hoisted ones are not. Dependencies that are
+/// not installed are left out, and nothing is measured if `node_modules` does not exist.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `dependencies` - A slice of `String` containing the dependency names to measure.
+///
+/// # Returns
+///
+/// Returns a `BTreeMap<String, u64>` mapping installed dependencies to their size in bytes.
+///
+/// # Examples
+///
+/// ```
+/// let sizes = package_sizes(".", &analysis.unused_dependencies);
+/// for (dep, size) in &sizes {
+///     println!("{}: {}", dep, format_size(*size));
+/// }
+/// ```
+pub fn package_sizes(dir_path: &str, dependencies: &[String]) -> BTreeMap<String, u64> {
+    let node_modules = Path::new(dir_path).join("node_modules");
+    if !node_modules.is_dir() {
+        return BTreeMap::new();
+    }
+
+    dependencies
+        .iter()
+        .filter_map(|dep| {
+            let package_dir = node_modules.join(dep);
+            package_dir
+                .is_dir()
+                .then(|| (dep.clone(), directory_size(&package_dir)))
+        })
+        .collect()
+}
+
+/// Lists the install lifecycle scripts declared by an installed dependency.
+///
+/// This function reads `node_modules/<dependency>/package.json` and returns the lifecycle hooks
+/// (`preinstall`, `install`, `postinstall`) found in its `scripts` field. These hooks run
+/// arbitrary code on every install, which makes the dependency a higher-value removal.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `dependency` - The name of the installed dependency.
+///
+/// # Returns
+///
+/// Returns a `Vec<String>` with the lifecycle script names, in install order. Returns an empty
+/// vector if the dependency is not installed or its manifest cannot be read.
+///
+/// # Examples
+///
+/// ```
+/// let scripts = find_lifecycle_scripts(".", "esbuild");
+/// assert_eq!(scripts, vec!["postinstall".to_string()]);
+/// ```
+pub fn find_lifecycle_scripts(dir_path: &str, dependency: &str) -> Vec<String> {
+    let manifest_path = Path::new(dir_path)
+        .join("node_modules")
+        .join(dependency)
+        .join(PACKAGE_JSON_PATH);
+    let Ok(content) = fs::read_to_string(manifest_path) else {
+        return Vec::new();
+    };
+    let Ok(package_json) = serde_json::from_str::<Value>(&content) else {
+        return Vec::new();
+    };
+    let Some(scripts) = package_json.get("scripts").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+
+    LIFECYCLE_SCRIPTS
+        .iter()
+        .filter(|script| scripts.contains_key(**script))
+        .map(|script| script.to_string())
+        .collect()
+}
+
+/// Audits the install lifecycle scripts of a list of dependencies.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `dependencies` - A slice of `String` containing the dependency names to audit.
+///
+/// # Returns
+///
+/// Returns a `BTreeMap<String, Vec<String>>` mapping each dependency that declares lifecycle
+/// scripts to their names (see [`find_lifecycle_scripts`]). Dependencies without lifecycle scripts
+/// or without an installed `node_modules` entry are left out.
+///
+/// # Examples
+///
+/// ```
+/// let unused = vec!["esbuild".to_string(), "lodash".to_string()];
+/// let scripts = audit_lifecycle_scripts(".", &unused);
+/// assert_eq!(scripts["esbuild"], vec!["postinstall".to_string()]);
+/// assert!(!scripts.contains_key("lodash"));
+/// ```
+pub fn audit_lifecycle_scripts(
+    dir_path: &str,
+    dependencies: &[String],
+) -> BTreeMap<String, Vec<String>> {
+    dependencies
+        .iter()
+        .map(|dep| (dep.clone(), find_lifecycle_scripts(dir_path, dep)))
+        .filter(|(_, scripts)| !scripts.is_empty())
+        .collect()
+}
+
+/// Extracts the commands run by a `package.json` script.
+///
+/// The script is split into the commands chained with `&&`, `||`, `;` and `|`. The command of each
+/// part is its first word after any leading `NAME=value` environment assignments. Paths
+/// (`./scripts/build.sh`) and variables (`$EDITOR`) are not commands.
+///
+/// # Arguments
+///
+/// * `script` - A string slice containing the script command line.
+///
+/// # Returns
+///
+/// Returns a `Vec<&str>` with the command names, in script order.
+///
+/// # Examples
+///
+/// ```
+/// let commands = find_script_commands("NODE_ENV=test jest && eslint . | tee lint.log");
+/// assert_eq!(commands, vec!["jest", "eslint", "tee"]);
+/// ```
+pub fn find_script_commands(script: &str) -> Vec<&str> {
+    script
+        .split([';', '|'])
+        .flat_map(|part| part.split("&&"))
+        .filter_map(|part| {
+            part.split_whitespace()
+                .map(|word| word.trim_matches(|c| "()'\"".contains(c)))
+                .find(|word| !word.contains('=') && !word.is_empty())
+        })
+        .filter(|command| !command.contains('/') && !command.starts_with(['$', '.', '-']))
+        .collect()
+}
+
+/// Finds the commands provided by the `bin` field of every package installed in `node_modules`.
+///
+/// A string `bin` provides a command named after the unscoped package name; an object `bin` provides
+/// one command per key.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+///
+/// # Returns
+///
+/// Returns a `HashSet<String>` with the names of the installed commands.
+///
+/// # Examples
+///
+/// ```
+/// // node_modules/typescript/package.json: "bin": { "tsc": "./bin/tsc", "tsserver": "./bin/tsserver" }
+/// assert!(installed_bins(".").contains("tsc"));
+/// ```
+pub fn installed_bins(dir_path: &str) -> HashSet<String> {
+    let node_modules = Path::new(dir_path).join("node_modules");
+    let mut package_dirs = Vec::new();
+    for entry in fs::read_dir(&node_modules).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('@') {
+            package_dirs.extend(
+                fs::read_dir(&path)
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(|e| e.path()),
+            );
+        } else {
+            package_dirs.push(path);
+        }
+    }
+
+    package_dirs
+        .iter()
+        .flat_map(|package_dir| package_dir_bins(package_dir))
+        .collect()
+}
+
+/// Finds the commands provided by the `bin` field of an installed package, if any.
+fn installed_package_bins(dir_path: &str, package: &str) -> Vec<String> {
+    package_dir_bins(&Path::new(dir_path).join("node_modules").join(package))
+}
+
+/// Reads the commands provided by the `bin` field of the package installed in a directory.
+fn package_dir_bins(package_dir: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(package_dir.join(PACKAGE_JSON_PATH)) else {
+        return Vec::new();
+    };
+    let Ok(package_json) = serde_json::from_str::<Value>(&content) else {
+        return Vec::new();
+    };
+    match package_json.get("bin") {
+        Some(Value::String(_)) => package_json
+            .get("name")
+            .and_then(Value::as_str)
+            .map(|name| name.rsplit('/').next().unwrap_or(name).to_string())
+            .into_iter()
+            .collect(),
+        Some(Value::Object(commands)) => commands.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Finds the script commands that no installed dependency provides.
+///
+/// Each command of the `scripts` of `package.json` (see [`find_script_commands`]) is looked up in the
+/// `bin` fields of the packages in `node_modules` (see [`installed_bins`]). Shell and system commands
+/// (`SYSTEM_COMMANDS`) and the commands of `node_modules/.bin` are always available. Nothing is
+/// reported when `node_modules` does not exist, since every command would look missing before the
+/// first install.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `package_json` - A reference to the parsed `package.json`.
+///
+/// # Returns
+///
+/// Returns a `BTreeMap` mapping each missing command to the sorted names of the scripts running it.
+///
+/// # Examples
+///
+/// ```
+/// // eslint is not installed
+/// let package_json = json!({ "scripts": { "lint": "eslint ." } });
+/// let missing = missing_script_commands(".", &package_json);
+/// assert_eq!(missing["eslint"], vec!["lint".to_string()]);
+/// ```
+pub fn missing_script_commands(
+    dir_path: &str,
+    package_json: &Value,
+) -> BTreeMap<String, Vec<String>> {
+    let node_modules = Path::new(dir_path).join("node_modules");
+    if !node_modules.is_dir() {
+        return BTreeMap::new();
+    }
+    let mut bins = installed_bins(dir_path);
+    bins.extend(
+        fs::read_dir(node_modules.join(".bin"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string()),
+    );
+
+    let mut missing: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let scripts = package_json.get("scripts").and_then(Value::as_object);
+    for (name, script) in scripts.into_iter().flatten() {
+        for command in find_script_commands(script.as_str().unwrap_or_default()) {
+            if bins.contains(command) || SYSTEM_COMMANDS.contains(&command) {
+                continue;
+            }
+            let script_names = missing.entry(command.to_string()).or_default();
+            if !script_names.contains(name) {
+                script_names.push(name.clone());
+            }
+        }
+    }
+    for script_names in missing.values_mut() {
+        script_names.sort();
+    }
+    missing
+}
+
+/// The `package.json` fields naming a single entry point file.
+const ENTRY_POINT_FIELDS: [&str; 4] = ["main", "module", "types", "typings"];
+
+/// Checks whether a `main` target resolves like Node.js does: as is, with a `.js`, `.json` or
+/// `.node` extension, or as a directory with an `index` file.
+fn main_target_exists(path: &Path) -> bool {
+    path.is_file()
+        || ["js", "json", "node"].iter().any(|extension| {
+            let mut file = path.as_os_str().to_owned();
+            file.push(".");
+            file.push(extension);
+            Path::new(&file).is_file()
+        })
+        || ["index.js", "index.json", "index.node"]
+            .iter()
+            .any(|index| path.join(index).is_file())
+}
+
+/// Collects the file targets of an `exports` value, labelled with their path in the `exports` map.
+fn export_targets(value: &Value, label: String, targets: &mut Vec<(String, String)>) {
+    match value {
+        Value::String(target) => targets.push((label, target.clone())),
+        Value::Array(alternatives) => {
+            for alternative in alternatives {
+                export_targets(alternative, label.clone(), targets);
+            }
+        }
+        Value::Object(map) => {
+            for (key, value) in map {
+                export_targets(value, format!("{}[\"{}\"]", label, key), targets);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Finds the entry points of `package.json` whose target file does not exist.
+///
+/// The `main`, `module`, `types` and `typings` fields and every target of the `exports` map
+/// (conditions and subpaths included) are checked relative to the project directory. `main` is
+/// resolved like Node.js does, so `"main": "lib/index"` matches `lib/index.js`; other targets must
+/// exist as written. Subpath patterns (`./*`) are skipped, since they name no single file.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `package_json` - A reference to the parsed `package.json`.
+///
+/// # Returns
+///
+/// Returns a `BTreeMap` mapping each broken field (e.g., `main`, `exports["."]["import"]`) to its
+/// missing target.
+///
+/// # Examples
+///
+/// ```
+/// // dist/ has not been built
+/// let package_json = json!({ "main": "dist/index.js", "exports": { ".": "./dist/index.js" } });
+/// let broken = broken_entry_points(".", &package_json);
+/// assert_eq!(broken["main"], "dist/index.js");
+/// assert_eq!(broken["exports[\".\"]"], "./dist/index.js");
+/// ```
+pub fn broken_entry_points(dir_path: &str, package_json: &Value) -> BTreeMap<String, String> {
+    let mut targets = Vec::new();
+    for field in ENTRY_POINT_FIELDS {
+        if let Some(target) = package_json.get(field).and_then(Value::as_str) {
+            targets.push((field.to_string(), target.to_string()));
+        }
+    }
+    if let Some(exports) = package_json.get("exports") {
+        export_targets(exports, "exports".to_string(), &mut targets);
+    }
+
+    targets
+        .into_iter()
+        .filter(|(_, target)| !target.contains('*'))
+        .filter(|(field, target)| {
+            let path = Path::new(dir_path).join(target);
+            if field == "main" {
+                !main_target_exists(&path)
+            } else {
+                !path.exists()
+            }
+        })
+        .collect()
+}
+
+/// Lockfiles of the supported package managers, in detection order.
+pub const LOCKFILES: [&str; 4] = [
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lock",
+];
+
+/// Dependency fields of a locked package, whose entries are installed along with it.
+const LOCKED_DEPENDENCY_FIELDS: [&str; 3] =
+    ["dependencies", "optionalDependencies", "peerDependencies"];
+
+/// Lists the supported lockfiles present in a project directory.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+///
+/// # Returns
+///
+/// Returns a `Vec<&str>` with the names of the existing lockfiles, in `LOCKFILES` order.
+///
+/// # Examples
+///
+/// ```
+/// // The project only has a yarn.lock
+/// assert_eq!(existing_lockfiles("."), vec!["yarn.lock"]);
+/// ```
+pub fn existing_lockfiles(dir_path: &str) -> Vec<&'static str> {
+    LOCKFILES
+        .into_iter()
+        .filter(|lockfile| Path::new(dir_path).join(lockfile).exists())
+        .collect()
+}
+
+/// Lists the lockfiles of a project that are read by [`get_required_dependencies`].
+///
+/// Only the project directory itself is searched: lockfiles in subdirectories (e.g., an
+/// `examples/` app) never count. When a lockfile is named (with `--lockfile`), it is the only one
+/// considered, which settles projects holding several lockfiles.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `lockfile` - The lockfile to use, one of `LOCKFILES`, or `None` to detect it.
+///
+/// # Returns
+///
+/// Returns a `Vec<&str>` with the named lockfile if it exists, or every existing lockfile when
+/// none is named, in `LOCKFILES` order.
+///
+/// # Examples
+///
+/// ```
+/// // The project has both a package-lock.json and a yarn.lock
+/// assert_eq!(project_lockfiles(".", Some("yarn.lock")), vec!["yarn.lock"]);
+/// assert_eq!(project_lockfiles(".", None).len(), 2);
+/// ```
+pub fn project_lockfiles(dir_path: &str, lockfile: Option<&str>) -> Vec<&'static str> {
+    existing_lockfiles(dir_path)
+        .into_iter()
+        .filter(|existing| lockfile.is_none_or(|lockfile| lockfile == *existing))
+        .collect()
+}
+
+/// Checks whether the project's lockfile can be read by [`get_required_dependencies`].
+///
+/// A lockfile is readable when it is the only one in the project and its format parses:
+/// `package-lock.json` and `bun.lock` as JSON (trailing commas allowed in `bun.lock`) and
+/// `pnpm-lock.yaml` as YAML. `yarn.lock` is read line by line, so it is always readable.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `lockfile` - The lockfile to use, or `None` to detect it (see [`project_lockfiles`]).
+///
+/// # Returns
+///
+/// Returns `true` if exactly one lockfile is found and it parses, `false` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// if !is_lockfile_readable(".", None) {
+///     println!("Falling back to the package manager.");
+/// }
+/// ```
+pub fn is_lockfile_readable(dir_path: &str, lockfile: Option<&str>) -> bool {
+    let [lockfile] = project_lockfiles(dir_path, lockfile)[..] else {
+        return false;
+    };
+    let Ok(content) = fs::read_to_string(Path::new(dir_path).join(lockfile)) else {
+        return false;
+    };
+
+    match lockfile {
+        "package-lock.json" => serde_json::from_str::<Value>(&content).is_ok(),
+        "pnpm-lock.yaml" => serde_yaml::from_str::<serde_yaml::Value>(&content).is_ok(),
+        "bun.lock" => serde_json::from_str::<Value>(&strip_jsonc(&content)).is_ok(),
+        _ => true,
+    }
+}
+
+/// Finds the packages that resolve to more than one version in the project's lockfile.
+///
+/// The lockfile is found the same way as in [`get_required_dependencies`]: nothing is reported
+/// when there is no lockfile or when several lockfiles are present.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `lockfile` - The lockfile to use, or `None` to detect it (see [`project_lockfiles`]).
+///
+/// # Returns
+///
+/// Returns a `BTreeMap` mapping each duplicated package to its sorted resolved versions.
+///
+/// # Examples
+///
+/// ```
+/// // yarn.lock resolves lodash to both 3.10.1 and 4.17.21
+/// let duplicates = duplicate_versions(".", None);
+/// assert_eq!(duplicates["lodash"], vec!["3.10.1", "4.17.21"]);
+/// ```
+pub fn duplicate_versions(dir_path: &str, lockfile: Option<&str>) -> BTreeMap<String, Vec<String>> {
+    let [lockfile] = project_lockfiles(dir_path, lockfile)[..] else {
+        return BTreeMap::new();
+    };
+    let Ok(content) = fs::read_to_string(Path::new(dir_path).join(lockfile)) else {
+        return BTreeMap::new();
+    };
+
+    lockfile_versions(lockfile, &content)
+        .into_iter()
+        .filter(|(_, versions)| versions.len() > 1)
+        .map(|(package, versions)| (package, versions.into_iter().collect()))
+        .collect()
+}
+
+/// Finds the declared dependencies that the project's lockfile does not list.
+///
+/// The lockfile is found the same way as in [`get_required_dependencies`]: nothing is checked
+/// when there is no lockfile, when several lockfiles are present, or when the lockfile lists no
+/// package (e.g., it cannot be parsed). A dependency missing from the lockfile was added to
+/// `package.json` without running the package manager's install.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `lockfile` - The lockfile to use, or `None` to detect it (see [`project_lockfiles`]).
+/// * `dependencies` - A reference to the declared dependency names.
+///
+/// # Returns
+///
+/// Returns `Some` with the lockfile name and the sorted dependencies it does not list, or `None`
+/// if no single lockfile could be checked.
+///
+/// # Examples
+///
+/// ```
+/// // yarn.lock was not updated after `left-pad` was added to package.json
+/// let dependencies = HashSet::from(["left-pad".to_string(), "lodash".to_string()]);
+/// assert_eq!(
+///     unlocked_dependencies(".", None, &dependencies),
+///     Some(("yarn.lock", vec!["left-pad".to_string()]))
+/// );
+/// ```
+pub fn unlocked_dependencies(
+    dir_path: &str,
+    lockfile: Option<&str>,
+    dependencies: &HashSet<String>,
+) -> Option<(&'static str, Vec<String>)> {
+    let [lockfile] = project_lockfiles(dir_path, lockfile)[..] else {
+        return None;
+    };
+    let content = fs::read_to_string(Path::new(dir_path).join(lockfile)).ok()?;
+    let versions = lockfile_versions(lockfile, &content);
+    if versions.is_empty() {
+        return None;
+    }
+
+    let mut unlocked: Vec<String> = dependencies
+        .iter()
+        .filter(|dep| !versions.contains_key(*dep))
+        .cloned()
+        .collect();
+    unlocked.sort();
+    Some((lockfile, unlocked))
+}
+
+/// Collects every resolved version of every package listed in a lockfile.
+///
+/// # Arguments
+///
+/// * `lockfile` - The lockfile name, one of `LOCKFILES`.
+/// * `content` - A string slice containing the lockfile content.
+///
+/// # Returns
+///
+/// Returns a `BTreeMap` mapping each package name to the set of its resolved versions. Returns an
+/// empty map if the lockfile cannot be parsed.
+///
+/// # Examples
+///
+/// ```
+/// let content = "lodash@^4.0.0:\n  version \"4.17.21\"\n";
+/// let versions = lockfile_versions("yarn.lock", content);
+/// assert!(versions["lodash"].contains("4.17.21"));
+/// ```
+pub fn lockfile_versions(lockfile: &str, content: &str) -> BTreeMap<String, BTreeSet<String>> {
+    let mut versions: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut add = |package: &str, version: &str| {
+        versions
+            .entry(package.to_string())
+            .or_default()
+            .insert(version.to_string());
+    };
+
+    match lockfile {
+        // Keys are install paths: `node_modules/a/node_modules/b`
+        "package-lock.json" => {
+            let lock = serde_json::from_str::<Value>(content).unwrap_or_default();
+            for (key, entry) in lock
+                .get("packages")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+                .filter(|(key, _)| !key.is_empty())
+            {
+                let package = key.rsplit("node_modules/").next().unwrap_or_default();
+                if let Some(version) = entry.get("version").and_then(Value::as_str) {
+                    add(package, version);
+                }
+            }
+        }
+        // Entries are `"a@^1.0.0", "a@^1.1.0":` headers followed by an indented `version` line
+        "yarn.lock" => {
+            let mut packages: Vec<&str> = Vec::new();
+            for line in content.lines() {
+                if !line.starts_with([' ', '#']) && line.ends_with(':') {
+                    packages = line
+                        .trim_end_matches(':')
+                        .split(',')
+                        .filter_map(|specifier| {
+                            split_package_version(specifier.trim().trim_matches('"'))
+                        })
+                        .map(|(package, _)| package)
+                        .collect();
+                } else if let Some(version) = line
+                    .strip_prefix("  version ")
+                    .or_else(|| line.strip_prefix("  version:"))
+                    .map(|version| version.trim().trim_matches('"'))
+                {
+                    for package in packages.drain(..) {
+                        add(package, version);
+                    }
+                }
+            }
+        }
+        // Keys are `/a/1.0.0` (v5), `/a@1.0.0` (v6) or `a@1.0.0` (v9), with optional peer suffixes
+        "pnpm-lock.yaml" => {
+            let lock = serde_yaml::from_str::<serde_yaml::Value>(content).unwrap_or_default();
+            for key in lock
+                .get("packages")
+                .and_then(serde_yaml::Value::as_mapping)
+                .into_iter()
+                .flat_map(|packages| packages.keys())
+                .filter_map(serde_yaml::Value::as_str)
+            {
+                let key = key
+                    .trim_start_matches('/')
+                    .split('(')
+                    .next()
+                    .unwrap_or_default();
+                let resolved = split_package_version(key).or_else(|| key.rsplit_once('/'));
+                if let Some((package, version)) = resolved {
+                    add(package, version.split('_').next().unwrap_or(version));
+                }
+            }
+        }
+        // Entries are `"a": ["a@1.0.0", ...]`
+        "bun.lock" => {
+            let lock = serde_json::from_str::<Value>(&strip_jsonc(content)).unwrap_or_default();
+            for entry in lock
+                .get("packages")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flat_map(|packages| packages.values())
+            {
+                let resolved = entry
+                    .get(0)
+                    .and_then(Value::as_str)
+                    .and_then(split_package_version);
+                if let Some((package, version)) = resolved {
+                    add(package, version);
+                }
+            }
+        }
+        _ => {}
+    }
+
+    versions
+}
+
+/// Splits a `name@version` specifier at its last `@`, keeping the leading `@` of scoped names.
+fn split_package_version(specifier: &str) -> Option<(&str, &str)> {
+    let (package, version) = specifier.rsplit_once('@')?;
+    if package.is_empty() {
+        return None;
+    }
+    Some((package, version.trim_start_matches("npm:")))
 }
 
 /// Collects all required dependencies from `package.json` and supported lockfiles.
 ///
 /// This function checks for `package.json` and lockfiles (`package-lock.json`, `yarn.lock`,
-/// `pnpm-lock.yaml`, `bun.lock`) to gather dependencies. If multiple lockfiles are detected,
-/// it warns the user and returns an empty set to avoid ambiguity.
+/// `pnpm-lock.yaml`, `bun.lock`) in the project directory to gather dependencies. If multiple
+/// lockfiles are detected and none is named, it records a warning and returns an empty set to
+/// avoid ambiguity.
 ///
 /// # Arguments
 ///
 /// * `dir_path` - A string slice representing the path to the project directory.
+/// * `production_only` - If `true`, only keeps the packages that locked production packages
+///   depend on: the project's own `dependencies` and `devDependencies` are left out, as are the
+///   lockfile entries only installed for development (npm packages marked `dev` or
+///   `devOptional`, pnpm packages marked `dev` and the `devDependencies` of pnpm importers).
+///   `yarn.lock` and `bun.lock` do not mark dev-only packages, so all of theirs are read.
+/// * `lockfile` - The lockfile to read, or `None` to detect it (see [`project_lockfiles`]).
+/// * `warnings` - The `Warnings` the multiple lockfiles warning is added to.
 ///
 /// # Returns
 ///
@@ -51,14 +1497,19 @@ pub fn read_package_json(path: &str) -> Result<Value, String> {
 /// # Examples
 ///
 /// ```
-/// let deps = get_required_dependencies();
+/// let deps = get_required_dependencies(".", false, None, &mut Warnings::default());
 /// if !deps.is_empty() {
 ///     println!("Required dependencies: {:?}", deps);
 /// } else {
 ///     println!("No dependencies found or multiple lockfiles detected.");
 /// }
 /// ```
-pub fn get_required_dependencies(dir_path: &str) -> HashSet<String> {
+pub fn get_required_dependencies(
+    dir_path: &str,
+    production_only: bool,
+    lockfile: Option<&str>,
+    warnings: &mut Warnings,
+) -> HashSet<String> {
     let mut required = HashSet::new();
 
     // Define paths for lockfiles
@@ -68,44 +1519,39 @@ pub fn get_required_dependencies(dir_path: &str) -> HashSet<String> {
     let bun_lock_path = Path::new(dir_path).join("bun.lock");
 
     // Check for the existence of lockfiles
-    let lockfiles = [
-        (
-            "package-lock.json",
-            Path::new(&package_lock_json_path).exists(),
-        ),
-        ("yarn.lock", Path::new(&yarn_lock_path).exists()),
-        ("pnpm-lock.yaml", Path::new(&pnpm_lock_yaml_path).exists()),
-        ("bun.lock", Path::new(&bun_lock_path).exists()),
-    ];
-
-    let existing_lockfiles: Vec<&str> = lockfiles
-        .iter()
-        .filter_map(|(name, exists)| if *exists { Some(*name) } else { None })
-        .collect();
+    let existing_lockfiles = project_lockfiles(dir_path, lockfile);
+    if let Some(lockfile) = lockfile.filter(|_| existing_lockfiles.is_empty()) {
+        warnings.push(format!("Lockfile `{}` not found.", lockfile));
+    }
 
     if existing_lockfiles.len() > 1 {
-        eprintln!(
-            "{}: Multiple lockfiles detected ({}). Please use only one package manager.",
-            "Warning".yellow().bold(),
+        warnings.push(format!(
+            "Multiple lockfiles detected ({}). Please use only one package manager.",
             existing_lockfiles.join(", ")
-        );
+        ));
         return HashSet::new();
     }
 
-    // Process package.json first to ensure top-level dependencies are included
+    // Process package.json first to ensure top-level dependencies are included. A production-only
+    // run leaves them out, or a declared dependency could never be reported as unused: it is then
+    // only required when another production package depends on it
     let package_json_path = Path::new(dir_path).join("package.json");
     if let Ok(package_json) = read_package_json(package_json_path.to_str().unwrap()) {
-        if let Some(deps) = package_json.get("dependencies").and_then(Value::as_object) {
-            required.extend(deps.keys().cloned());
-        }
+        if !production_only {
+            if let Some(deps) = package_json.get("dependencies").and_then(Value::as_object) {
+                required.extend(deps.keys().cloned());
+            }
 
-        // TODO: review the devDependencies logic (handle them in a different case)
-        if let Some(dev_deps) = package_json
-            .get("devDependencies")
-            .and_then(Value::as_object)
-        {
-            required.extend(dev_deps.keys().cloned());
+            // TODO: review the devDependencies logic (handle them in a different case)
+            if let Some(dev_deps) = package_json
+                .get("devDependencies")
+                .and_then(Value::as_object)
+            {
+                required.extend(dev_deps.keys().cloned());
+            }
         }
+
+        required.extend(bundled_packages(&package_json));
     }
 
     // Process single lockfile
@@ -113,13 +1559,66 @@ pub fn get_required_dependencies(dir_path: &str) -> HashSet<String> {
         match *lockfile {
             // package-lock.json
             "package-lock.json" => {
-                if let Ok(content) = fs::read_to_string(package_lock_json_path) {
-                    if let Ok(lock) = serde_json::from_str::<Value>(&content) {
-                        if let Some(packages) = lock.get("packages").and_then(Value::as_object) {
-                            for key in packages.keys() {
-                                let package_name = key
-                                    .strip_prefix("node_modules/")
-                                    .unwrap_or(key)
+                let lock = fs::read_to_string(package_lock_json_path)
+                    .ok()
+                    .and_then(|content| serde_json::from_str::<Value>(&content).ok());
+                let packages = lock
+                    .as_ref()
+                    .and_then(|lock| lock.get("packages"))
+                    .and_then(Value::as_object);
+                for (key, entry) in packages.into_iter().flatten() {
+                    // `dev` and `devOptional` packages are only installed for development
+                    let is_dev = ["dev", "devOptional"]
+                        .iter()
+                        .any(|flag| entry.get(flag).and_then(Value::as_bool) == Some(true));
+                    if production_only && is_dev {
+                        continue;
+                    }
+                    // Only the packages that production packages depend on, not the root project's
+                    if production_only {
+                        if !key.is_empty() {
+                            required.extend(
+                                LOCKED_DEPENDENCY_FIELDS
+                                    .iter()
+                                    .filter_map(|field| entry.get(field)?.as_object())
+                                    .flat_map(|deps| deps.keys().cloned()),
+                            );
+                        }
+                        continue;
+                    }
+
+                    let package_name = key
+                        .strip_prefix("node_modules/")
+                        .unwrap_or(key)
+                        .split('@')
+                        .next()
+                        .unwrap_or("")
+                        .to_string();
+
+                    if !package_name.is_empty() {
+                        required.insert(package_name);
+                    }
+                }
+            }
+            // yarn.lock
+            "yarn.lock" => {
+                if let Ok(content) = fs::read_to_string(yarn_lock_path) {
+                    // yarn.lock does not mark dev-only packages: every locked package counts
+                    if production_only {
+                        required.extend(yarn_lock_transitive_packages(&content));
+                    } else {
+                        for line in content.lines() {
+                            if line.ends_with(':')
+                                && !line.starts_with('#')
+                                && !line.trim().is_empty()
+                                && !line.trim().contains("dependencies:")
+                            {
+                                let dep = line.trim_end_matches(':').trim();
+                                let package_name = dep
+                                    .split(',')
+                                    .next()
+                                    .unwrap_or(dep)
+                                    .trim()
                                     .split('@')
                                     .next()
                                     .unwrap_or("")
@@ -133,85 +1632,62 @@ pub fn get_required_dependencies(dir_path: &str) -> HashSet<String> {
                     }
                 }
             }
-            // yarn.lock
-            "yarn.lock" => {
-                if let Ok(content) = fs::read_to_string(yarn_lock_path) {
-                    for line in content.lines() {
-                        if line.ends_with(':')
-                            && !line.starts_with('#')
-                            && !line.trim().is_empty()
-                            && !line.trim().contains("dependencies:")
-                        {
-                            let dep = line.trim_end_matches(':').trim();
-                            let package_name = dep
-                                .split(',')
-                                .next()
-                                .unwrap_or(dep)
-                                .trim()
-                                .split('@')
-                                .next()
-                                .unwrap_or("")
-                                .to_string();
-
-                            if !package_name.is_empty() {
-                                required.insert(package_name);
-                            }
-                        }
-                    }
-                }
-            }
             // pnpm-lock.yaml
             "pnpm-lock.yaml" => {
-                if let Ok(content) = fs::read_to_string(pnpm_lock_yaml_path) {
-                    if let Ok(yaml) = serde_yaml::from_str::<serde_yaml::Value>(&content) {
-                        if let Some(deps) = yaml
-                            .get("dependencies")
-                            .or_else(|| yaml.get("devDependencies")) // TODO: review the devDependencies logic
-                            .and_then(|v| v.as_mapping())
-                        {
-                            for key in deps.keys() {
-                                if let Some(key_str) = key.as_str() {
-                                    if key_str.contains("dependencies")
-                                        || key_str.contains("devDependencies")
-                                    {
-                                        continue;
-                                    }
-                                    required.insert(key_str.to_string());
-                                }
-                            }
-                        }
+                let yaml = fs::read_to_string(pnpm_lock_yaml_path)
+                    .ok()
+                    .and_then(|content| serde_yaml::from_str::<serde_yaml::Value>(&content).ok())
+                    .unwrap_or_default();
+                let sections: &[&str] = if production_only {
+                    &["dependencies", "optionalDependencies"]
+                } else {
+                    &["dependencies", "optionalDependencies", "devDependencies"]
+                };
+
+                // Older lockfiles list the root project's packages at the top level, newer ones
+                // under each importer. A production-only run skips the root project's own ones,
+                // and reads those of the non-dev locked packages instead
+                let importers = yaml
+                    .get("importers")
+                    .and_then(|importers| importers.as_mapping())
+                    .into_iter()
+                    .flatten()
+                    .filter(|(path, _)| !production_only || path.as_str() != Some("."))
+                    .map(|(_, importer)| importer);
+                let locked = ["packages", "snapshots"]
+                    .iter()
+                    .filter_map(|section| yaml.get(*section)?.as_mapping())
+                    .flat_map(|packages| packages.values())
+                    .filter(|entry| {
+                        production_only
+                            && entry.get("dev").and_then(serde_yaml::Value::as_bool) != Some(true)
+                    });
+                let deps = std::iter::once(&yaml)
+                    .filter(|_| !production_only)
+                    .chain(importers)
+                    .chain(locked)
+                    .flat_map(|importer| {
+                        sections
+                            .iter()
+                            .filter_map(|section| importer.get(*section)?.as_mapping())
+                    });
+                for key in deps
+                    .flat_map(|deps| deps.keys())
+                    .filter_map(|key| key.as_str())
+                {
+                    if key.contains("dependencies") || key.contains("devDependencies") {
+                        continue;
                     }
+                    required.insert(key.to_string());
                 }
             }
             // bun.lock
             "bun.lock" => {
                 if let Ok(content) = fs::read_to_string(bun_lock_path) {
-                    let mut lines = content.lines();
-
-                    fn extract_packages<'a, I>(lines: &mut I, required: &mut HashSet<String>)
-                    where
-                        I: Iterator<Item = &'a str>,
-                    {
-                        while let Some(line) = lines.next() {
-                            if line.contains("}") {
-                                break;
-                            }
-
-                            if let Some((package, _)) = line.split_once(':') {
-                                let package_name = package.trim().trim_matches('"');
-                                if !package_name.is_empty() {
-                                    required.insert(package_name.to_string());
-                                }
-                            }
-                        }
-                    }
-
-                    while let Some(line) = lines.next() {
-                        if line.contains("dependencies") {
-                            extract_packages(&mut lines, &mut required);
-                        } else if line.contains("devDependencies") {
-                            extract_packages(&mut lines, &mut required);
-                        }
+                    if production_only {
+                        required.extend(bun_lock_transitive_packages(&content));
+                    } else {
+                        required.extend(parse_bun_lock(&content));
                     }
                 }
             }
@@ -223,10 +1699,199 @@ pub fn get_required_dependencies(dir_path: &str) -> HashSet<String> {
     required
 }
 
-/// Reads a `.cnpignore` file and returns its non-comment, non-empty lines as a set.
+/// Extracts the declared dependencies of every workspace from a text `bun.lock` file.
+///
+/// Bun's text lockfile is JSON with trailing commas, so it is parsed leniently (see
+/// [`strip_jsonc`]). Every entry of `workspaces` is read, not just the `""` root, and all of its
+/// dependency fields are collected. If the content still cannot be parsed (e.g., a truncated
+/// file), the dependency blocks of the workspaces are extracted line by line instead.
+///
+/// # Arguments
+///
+/// * `content` - A string slice containing the `bun.lock` content.
+///
+/// # Returns
+///
+/// Returns a `HashSet<String>` containing the dependency names declared by the workspaces.
+///
+/// # Examples
+///
+/// ```
+/// let content = r#"{ "workspaces": { "": { "dependencies": { "react": "^18.0.0", }, }, }, }"#;
+/// assert!(parse_bun_lock(content).contains("react"));
+/// ```
+pub fn parse_bun_lock(content: &str) -> HashSet<String> {
+    if let Ok(lockfile) = serde_json::from_str::<Value>(&strip_jsonc(content)) {
+        return lockfile
+            .get("workspaces")
+            .and_then(Value::as_object)
+            .map(|workspaces| workspaces.values().flat_map(declared_packages).collect())
+            .unwrap_or_default();
+    }
+
+    // Only the dependency fields of workspaces open a block: the ones inlined in `packages`
+    // entries (`["react@18.2.0", "", { "dependencies": { ... } }]`) belong to installed packages
+    let mut required = HashSet::new();
+    let mut in_block = false;
+    for line in content.lines().map(str::trim) {
+        if in_block {
+            if line.starts_with('}') {
+                in_block = false;
+            } else if let Some((package, _)) = line.split_once(':') {
+                let package_name = package.trim().trim_matches('"');
+                if !package_name.is_empty() {
+                    required.insert(package_name.to_string());
+                }
+            }
+            continue;
+        }
+
+        in_block = line.ends_with('{')
+            && line.split_once(':').is_some_and(|(key, _)| {
+                DECLARED_DEPENDENCY_FIELDS.contains(&key.trim().trim_matches('"'))
+            });
+    }
+
+    required
+}
+
+/// Collects the dependencies of the packages locked in a `yarn.lock` file.
+///
+/// Both the classic (`a "^1.0.0"`) and the Berry (`a: "npm:^1.0.0"`) entry syntaxes are read from
+/// the dependency blocks nested under each locked package, so the project's own dependencies only
+/// appear when another package depends on them.
+fn yarn_lock_transitive_packages(content: &str) -> HashSet<String> {
+    let mut packages = HashSet::new();
+    let mut block_indent = None;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        if block_indent.is_some_and(|block_indent| indent > block_indent) {
+            let name = match trimmed.strip_prefix('"') {
+                Some(quoted) => quoted.split('"').next(),
+                None => trimmed.split([' ', ':']).next(),
+            };
+            packages.extend(name.filter(|name| !name.is_empty()).map(str::to_string));
+            continue;
+        }
+        block_indent = trimmed
+            .strip_suffix(':')
+            .filter(|field| LOCKED_DEPENDENCY_FIELDS.contains(field))
+            .map(|_| indent);
+    }
+    packages
+}
+
+/// Collects the dependencies of the packages locked in a text `bun.lock` file.
+///
+/// Entries are `"a": ["a@1.0.0", "", { "dependencies": { ... } }, "sha512-..."]`, so the project's
+/// own dependencies only appear when another package depends on them.
+fn bun_lock_transitive_packages(content: &str) -> HashSet<String> {
+    let lock = serde_json::from_str::<Value>(&strip_jsonc(content)).unwrap_or_default();
+    lock.get("packages")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|packages| packages.values())
+        .filter_map(Value::as_array)
+        .flatten()
+        .flat_map(|info| {
+            LOCKED_DEPENDENCY_FIELDS
+                .iter()
+                .filter_map(|field| info.get(field)?.as_object())
+        })
+        .flat_map(|deps| deps.keys().cloned())
+        .collect()
+}
+
+/// Removes a `#` comment from a line: a `#` starts a comment at the start of the line or after
+/// whitespace only.
+fn strip_inline_comment(line: &str) -> &str {
+    let mut previous = None;
+    for (index, char) in line.char_indices() {
+        if char == '#' && previous.is_none_or(char::is_whitespace) {
+            return &line[..index];
+        }
+        previous = Some(char);
+    }
+    line
+}
+
+/// The entries of a `.cnpignore` file, split by section.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CnpIgnore {
+    /// Dependencies never reported as unused: the `[packages]` section and unsectioned lines.
+    pub packages: HashSet<String>,
+    /// Path globs, relative to the project directory, excluded from the scan (`[paths]` section).
+    pub paths: Vec<String>,
+}
+
+/// Reads a `.cnpignore` file and returns its entries, split into packages and paths.
+///
+/// Empty lines, lines starting with `#` and inline comments (text after a `#` preceded by
+/// whitespace, so `#` inside a value is kept) are skipped. A `[packages]` or `[paths]` header line
+/// starts a section; lines before any header are package names, so files without sections keep
+/// their meaning. Lines under an unknown header are skipped. LF, CRLF and lone CR line endings are
+/// all accepted. If the file is not found, no entries are returned.
+///
+/// # Arguments
+///
+/// * `path` - A string slice representing the path to the `.cnpignore` file.
+///
+/// # Returns
+///
+/// Returns a `CnpIgnore` with the trimmed package names and path globs, the latter in file order.
+/// Returns an empty `CnpIgnore` if the file does not exist or cannot be read.
+///
+/// # Examples
+///
+/// ```
+/// // [packages]
+/// // eslint
+/// // [paths]
+/// // src/generated/**
+/// let cnpignore = read_cnpignore_sections(".cnpignore");
+/// assert!(cnpignore.packages.contains("eslint"));
+/// assert_eq!(cnpignore.paths, vec!["src/generated/**".to_string()]);
+/// ```
+pub fn read_cnpignore_sections(path: &str) -> CnpIgnore {
+    let mut cnpignore = CnpIgnore::default();
+    let Ok(content) = fs::read_to_string(path) else {
+        return cnpignore;
+    };
+    // `Some(true)` for packages, `Some(false)` for paths, `None` under an unknown header
+    let mut in_packages = Some(true);
+    for line in content.split(['\n', '\r']) {
+        let line = strip_inline_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_packages = match section.trim().to_ascii_lowercase().as_str() {
+                "packages" => Some(true),
+                "paths" => Some(false),
+                _ => None,
+            };
+            continue;
+        }
+        match in_packages {
+            Some(true) => {
+                cnpignore.packages.insert(line.to_string());
+            }
+            Some(false) => cnpignore.paths.push(line.to_string()),
+            None => {}
+        }
+    }
+    cnpignore
+}
+
+/// Reads a `.cnpignore` file and returns the dependencies it excludes as a set.
 ///
-/// The function parses the `.cnpignore` file, ignoring empty lines, lines starting with `#`,
-/// and inline comments (text after `#`). If the file is not found, an empty set is returned.
+/// These are the entries of the `[packages]` section plus any line before the first section
+/// header; see [`read_cnpignore_sections`] for the format. If the file is not found, an empty set
+/// is returned.
 ///
 /// # Arguments
 ///
@@ -234,13 +1899,13 @@ pub fn get_required_dependencies(dir_path: &str) -> HashSet<String> {
 ///
 /// # Returns
 ///
-/// Returns a `HashSet<String>` containing the trimmed, non-empty, non-comment lines from
-/// the `.cnpignore` file. Returns an empty set if the file does not exist or cannot be read.
+/// Returns a `HashSet<String>` containing the trimmed package names from the `.cnpignore` file.
+/// Returns an empty set if the file does not exist or cannot be read.
 ///
 /// # Examples
 ///
 /// ```
-/// let ignore_patterns = read_cnpignore();
+/// let ignore_patterns = read_cnpignore(".cnpignore");
 /// if !ignore_patterns.is_empty() {
 ///     println!("Ignore patterns: {:?}", ignore_patterns);
 /// } else {
@@ -248,22 +1913,40 @@ pub fn get_required_dependencies(dir_path: &str) -> HashSet<String> {
 /// }
 /// ```
 pub fn read_cnpignore(path: &str) -> HashSet<String> {
-    fs::read_to_string(path)
-        .map(|content| {
-            content
-                .lines()
-                .map(|line| {
-                    let trimmed = line.trim();
-                    // Strip inline comments
-                    trimmed
-                        .split('#')
-                        .next()
-                        .unwrap_or(trimmed)
-                        .trim()
-                        .to_string()
-                })
-                .filter(|line| !line.is_empty() && !line.starts_with('#'))
-                .collect()
-        })
-        .unwrap_or_default()
+    read_cnpignore_sections(path).packages
+}
+
+/// Reads a newline-delimited list of dependency names, as given to `--dependencies-from`.
+///
+/// Lines are parsed like `.cnpignore`: empty lines and `#` comments are skipped, and names are
+/// trimmed. Duplicates are removed.
+///
+/// # Arguments
+///
+/// * `path` - A string slice representing the path to the list file.
+///
+/// # Returns
+///
+/// Returns `Ok(Vec<String>)` with the dependency names, sorted.
+/// Returns `Err(String)` with an error message if the file cannot be read.
+///
+/// # Examples
+///
+/// ```
+/// let dependencies = read_dependency_list("deps.txt")?;
+/// println!("Analyzing {} dependencies", dependencies.len());
+/// ```
+pub fn read_dependency_list(path: &str) -> Result<Vec<String>, String> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        format!(
+            "Error: Failed to read the dependency list `{}`: {}",
+            path, e
+        )
+    })?;
+    let names: BTreeSet<String> = content
+        .split(['\n', '\r'])
+        .map(|line| strip_inline_comment(line).trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    Ok(names.into_iter().collect())
 }
@@ -1,13 +1,24 @@
-use crate::config::{EXTENSIONS, IGNORE_FOLDERS, TYPESCRIPT_EXTENSIONS, is_typescript_project};
-use glob::glob;
-use indicatif::ProgressBar;
+use crate::analysis::AnalyzeOptions;
+use crate::config::{
+    SFC_EXTENSIONS, STYLESHEET_EXTENSIONS, TYPESCRIPT_EXTENSIONS, is_typescript_project,
+};
+use crate::progress::Progress;
+use crate::resolve::{concatenation_to_package, specifier_to_package, template_to_package};
+use crate::utils::run_with_timeout;
+use crate::warnings::Warnings;
+use glob::{MatchOptions, Pattern};
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::ffi::OsStr;
+use std::fmt;
 use std::fs::{self};
-use std::path::Path;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
 
 /// Normalizes a file path for consistent handling across platforms, especially macOS.
 ///
@@ -44,249 +55,1304 @@ pub fn normalize_path(path: &Path) -> String {
     }
 }
 
-/// Runs the TypeScript compiler (`tsc`) to detect unused imports (TS6133 errors).
+/// Runs the TypeScript compiler (`tsc`) to detect unused imports (TS6133 and TS6192 errors).
 ///
-/// This function executes `tsc` to collect diagnostics for unused imports
-/// in a TypeScript project. If `tsc` fails or no TypeScript project is detected, it returns an empty set.
+/// This function executes `tsc` on the given TypeScript files of a project to collect the imports
+/// whose bindings are never read. If `tsc` fails or no TypeScript project is detected, it returns
+/// an empty set. Once the timeout is exceeded, the running `tsc` is killed.
+///
+/// With `cache`, the diagnostics are reused from [`TSC_CACHE_PATH`] while the TypeScript files and
+/// `tsconfig.json` are unchanged (see [`cached_tsc_diagnostics`]).
 ///
 /// # Arguments
 ///
-/// * `dir_path` - A string slice representing the path to the `package.json` file.
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `files` - The paths of the TypeScript files to check.
+/// * `timeout` - The maximum time `tsc` may run.
+/// * `cache` - If `true`, reuses and stores the diagnostics in the tsc cache.
+/// * `warnings` - The `Warnings` failed `tsc` runs are added to.
 ///
 /// # Returns
 ///
-/// Returns a `HashSet<String>` containing the names of unused imports identified by TS6133 errors.
-/// Returns an empty set if the project is not TypeScript, `tsc` fails, or no unused imports are found.
+/// Returns `Ok(HashSet<(String, String)>)` containing the normalized file path and package name of
+/// each unused import. The set is empty if the project is not TypeScript, `tsc` fails, or no
+/// unused imports are found.
+/// Returns `Err(String)` with an error message if `tsc` cannot be run or did not finish within
+/// `timeout`.
 ///
 /// # Examples
 ///
 /// ```
-/// let unused = get_typescript_unused_imports();
-/// if !unused.is_empty() {
-///     println!("Unused imports: {:?}", unused);
-/// } else {
-///     println!("No unused imports detected.");
+/// let files = vec!["/app/src/index.ts".to_string()];
+/// match get_typescript_unused_imports("/app", &files, Duration::from_secs(60), true, &mut Warnings::default()) {
+///     Ok(unused) => println!("Unused imports: {:?}", unused),
+///     Err(e) => eprintln!("{}", e),
 /// }
 /// ```
-pub fn get_typescript_unused_imports(dir_path: &str) -> HashSet<String> {
+pub fn get_typescript_unused_imports(
+    dir_path: &str,
+    files: &[String],
+    timeout: Duration,
+    cache: bool,
+    warnings: &mut Warnings,
+) -> Result<HashSet<(String, String)>, String> {
+    if cache {
+        cached_tsc_diagnostics(dir_path, files, "tsc", timeout, warnings)
+    } else {
+        run_tsc_diagnostics(dir_path, files, "tsc", timeout, warnings)
+    }
+}
+
+/// Where the tsc diagnostics of a project are cached, relative to the project directory.
+pub const TSC_CACHE_PATH: &str = "node_modules/.cache/cnp/tsc.json";
+
+/// The cached outcome of a `tsc` run, valid while the hash of its inputs is unchanged.
+#[derive(Debug, Deserialize, Serialize)]
+struct TscCache {
+    /// The cnp version that wrote the cache, as hashes may differ between builds.
+    version: String,
+    /// The hash of the compiler, `tsconfig.json` and every TypeScript file, in hexadecimal.
+    hash: String,
+    /// The file and package of each unused import, sorted.
+    unused_imports: Vec<(String, String)>,
+}
+
+/// Hashes the inputs of a `tsc` run: the compiler, `tsconfig.json`, and the path and contents of
+/// every checked TypeScript file.
+fn tsc_inputs_hash(dir_path: &str, files: &[String], tsc: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    tsc.hash(&mut hasher);
+    fs::read(Path::new(dir_path).join("tsconfig.json"))
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    let mut files: Vec<&String> = files.iter().collect();
+    files.sort();
+    for file in files {
+        file.hash(&mut hasher);
+        fs::read(file).unwrap_or_default().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Runs [`run_tsc_diagnostics`] unless the diagnostics cached in [`TSC_CACHE_PATH`] were computed
+/// from the same compiler, `tsconfig.json` and TypeScript files.
+///
+/// Runs that raise warnings (e.g., `tsc` failures) are not cached. The cache is written to a
+/// temporary file renamed into place, so concurrent runs never read a partial cache; failing to
+/// write it is not an error.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `files` - The paths of the TypeScript files to check.
+/// * `tsc` - The TypeScript compiler program to run (e.g., `tsc`).
+/// * `timeout` - The maximum time the compiler may run.
+/// * `warnings` - The `Warnings` failed compiler runs are added to.
+///
+/// # Returns
+///
+/// Returns `Ok(HashSet<(String, String)>)` with the file and package of each unused import, cached
+/// or not, or `Err(String)` with an error message if the compiler cannot be run or did not finish
+/// within `timeout`.
+///
+/// # Examples
+///
+/// ```
+/// let mut warnings = Warnings::default();
+/// let files = vec!["/app/src/index.ts".to_string()];
+/// // The second run reuses the diagnostics of the first one
+/// let first = cached_tsc_diagnostics("/app", &files, "tsc", Duration::from_secs(60), &mut warnings)?;
+/// let second = cached_tsc_diagnostics("/app", &files, "tsc", Duration::from_secs(60), &mut warnings)?;
+/// assert_eq!(first, second);
+/// ```
+pub fn cached_tsc_diagnostics(
+    dir_path: &str,
+    files: &[String],
+    tsc: &str,
+    timeout: Duration,
+    warnings: &mut Warnings,
+) -> Result<HashSet<(String, String)>, String> {
+    if files.is_empty() || !is_typescript_project(dir_path) {
+        return Ok(HashSet::new());
+    }
+    let cache_path = Path::new(dir_path).join(TSC_CACHE_PATH);
+    let hash = format!("{:016x}", tsc_inputs_hash(dir_path, files, tsc));
+    let cached = fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<TscCache>(&content).ok())
+        .filter(|cache| cache.version == env!("CARGO_PKG_VERSION") && cache.hash == hash);
+    if let Some(cache) = cached {
+        return Ok(cache.unused_imports.into_iter().collect());
+    }
+
+    let warning_count = warnings.len();
+    let unused_imports = run_tsc_diagnostics(dir_path, files, tsc, timeout, warnings)?;
+    if warnings.len() == warning_count {
+        let mut sorted: Vec<(String, String)> = unused_imports.iter().cloned().collect();
+        sorted.sort();
+        let cache = TscCache {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            hash,
+            unused_imports: sorted,
+        };
+        let temp_path = cache_path.with_extension(format!("json.{}.tmp", std::process::id()));
+        let written = cache_path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                fs::write(
+                    &temp_path,
+                    serde_json::to_string(&cache).unwrap_or_default(),
+                )
+            })
+            .and_then(|_| fs::rename(&temp_path, &cache_path));
+        if written.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+    }
+    Ok(unused_imports)
+}
+
+/// Runs a TypeScript compiler once on the given TypeScript files of a project and collects their
+/// unused imports, within a timeout.
+///
+/// Files given on the command line make `tsc` ignore `tsconfig.json`, so `--noUnusedLocals` is
+/// passed to report unused imports. Each TS6133 (`'x' is declared but its value is never read`)
+/// and TS6192 (`All imports in import declaration are unused`) diagnostic is mapped back to the
+/// package of its import declaration (see [`unused_import_package`]). `tsc` exits with an error
+/// code whenever it reports diagnostics, so a failure is only warned about when it reports none.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `files` - The paths of the TypeScript files to check.
+/// * `tsc` - The TypeScript compiler program to run (e.g., `tsc`).
+/// * `timeout` - The maximum time the compiler may run.
+/// * `warnings` - The `Warnings` failed compiler runs are added to.
+///
+/// # Returns
+///
+/// Returns `Ok(HashSet<(String, String)>)` with the normalized file path and package name of each
+/// unused import, or `Err(String)` with an error message if the compiler cannot be run or did not
+/// finish within `timeout`.
+///
+/// # Examples
+///
+/// ```
+/// let mut warnings = Warnings::default();
+/// let files = vec!["/app/src/index.ts".to_string()];
+/// let unused = run_tsc_diagnostics("/app", &files, "node_modules/.bin/tsc", Duration::from_secs(60), &mut warnings)?;
+/// println!("Unused imports: {:?}", unused);
+/// ```
+pub fn run_tsc_diagnostics(
+    dir_path: &str,
+    files: &[String],
+    tsc: &str,
+    timeout: Duration,
+    warnings: &mut Warnings,
+) -> Result<HashSet<(String, String)>, String> {
     let mut unused_imports = HashSet::new();
-    if !is_typescript_project(&dir_path) {
-        return unused_imports;
+    if files.is_empty() || !is_typescript_project(dir_path) {
+        return Ok(unused_imports);
     }
 
-    // Search for all files in the directory matching with typescript extensions
-    for ext in &TYPESCRIPT_EXTENSIONS {
-        let pattern = format!("{}/**/*.{}", dir_path, ext);
-
-        // Walk through the directory matching the pattern
-        for entry in glob(&pattern).unwrap() {
-            match entry {
-                Ok(path) if !path.is_dir() && !path.is_symlink() => {
-                    // TODO
-                    let output = Command::new("tsc")
-                        .args(["--noEmit", "--pretty", "false", &path.to_str().unwrap()])
-                        .stderr(std::process::Stdio::piped())
-                        .current_dir(&path.parent().unwrap_or(Path::new("./")))
-                        .output()
-                        .expect("Failed to run tsc");
-
-                    if output.status.success() {
-                        let stderr = String::from_utf8_lossy(&output.stderr);
-                        for line in stderr.lines() {
-                            if line.contains("TS6133") {
-                                // Example: "file.ts(1,8): error TS6133: 'analytics' is declared but its value is never read."
-                                if let Some((file_path, _line_number)) = extract_file_and_line(line)
-                                {
-                                    unused_imports.insert(file_path);
-                                }
-                            }
-                        }
-                    } else {
-                        eprintln!("tsc failed with exit code: {}", output.status);
-                    }
-                }
+    let output = run_with_timeout(
+        Command::new(tsc)
+            .args(["--noEmit", "--pretty", "false", "--noUnusedLocals"])
+            .args(files)
+            .current_dir(dir_path),
+        timeout,
+    )
+    .map_err(|e| format!("Failed to run {}: {}", tsc, e))?
+    .ok_or_else(|| format!("tsc did not finish within {:?}", timeout))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let diagnostics: Vec<&str> = stdout
+        .lines()
+        .chain(stderr.lines())
+        .filter(|line| line.contains("error TS"))
+        .collect();
+    if !output.status.success() && diagnostics.is_empty() {
+        warnings.push(format!("tsc failed with exit code: {}", output.status));
+    }
+
+    for diagnostic in diagnostics {
+        let whole_declaration = diagnostic.contains("TS6192");
+        if !whole_declaration && !diagnostic.contains("TS6133") {
+            continue;
+        }
+        // Example: "src/file.ts(1,8): error TS6133: 'analytics' is declared but its value is never read."
+        let Some((file_path, line_number)) = extract_file_and_line(diagnostic) else {
+            continue;
+        };
+        let path = normalize_path(&Path::new(dir_path).join(file_path));
+        if let Some(package) = unused_import_package(&path, line_number, whole_declaration) {
+            unused_imports.insert((path, package));
+        }
+    }
+
+    Ok(unused_imports)
+}
+
+/// Matches the `from '...'` clause ending an import declaration, capturing the specifier.
+static IMPORT_FROM_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\bfrom\s*['"]([^'"]+)['"]"#).expect("Failed to compile regex"));
+
+/// The most lines an import declaration is searched over, around a diagnostic.
+const MAX_IMPORT_DECLARATION_LINES: usize = 20;
+
+/// Resolves a `tsc` unused-binding diagnostic to the package of the import declaration it points
+/// into.
+///
+/// The declaration is the closest line starting with `import` at or above the diagnostic, up to
+/// its `from '...'` clause. A TS6133 diagnostic only makes the whole import unused when the
+/// declaration has a single binding: `import { a, b } from 'pkg'` still uses `pkg` when only `a`
+/// is unused. Diagnostics outside of an import declaration (unused local variables) resolve to
+/// nothing.
+///
+/// # Arguments
+///
+/// * `path` - A string slice representing the path to the TypeScript file.
+/// * `line_number` - The 1-based line of the diagnostic.
+/// * `whole_declaration` - If `true`, the diagnostic (TS6192) reports every binding unused.
+///
+/// # Returns
+///
+/// Returns `Some(String)` with the package of the unused import, or `None` if the diagnostic does
+/// not make a whole package import unused.
+fn unused_import_package(
+    path: &str,
+    line_number: usize,
+    whole_declaration: bool,
+) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let lines: Vec<&str> = content.lines().collect();
+    let index = line_number.checked_sub(1)?;
+    let start = (index.saturating_sub(MAX_IMPORT_DECLARATION_LINES)..=index)
+        .rev()
+        .find(|&i| {
+            lines
+                .get(i)
+                .is_some_and(|line| line.trim_start().starts_with("import"))
+        })?;
+
+    let mut declaration = String::new();
+    let mut end = start;
+    for (i, line) in lines
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(MAX_IMPORT_DECLARATION_LINES)
+    {
+        declaration.push_str(line);
+        declaration.push('\n');
+        end = i;
+        if IMPORT_FROM_REGEX.is_match(line) || line.contains(';') {
+            break;
+        }
+    }
+    if index > end {
+        return None;
+    }
+
+    let caps = IMPORT_FROM_REGEX.captures(&declaration)?;
+    let clause = declaration[..caps.get(0)?.start()]
+        .trim_start()
+        .strip_prefix("import")?;
+    let bindings = clause
+        .replace(['{', '}'], ",")
+        .split(',')
+        .map(str::trim)
+        .filter(|binding| !binding.is_empty() && *binding != "type")
+        .count();
+    if whole_declaration || bindings == 1 {
+        specifier_to_package(&caps[1])
+    } else {
+        None
+    }
+}
+
+/// Checks whether the TypeScript compiler (`tsc`) can be run.
+///
+/// # Returns
+///
+/// Returns `true` if `tsc --version` runs successfully, `false` otherwise (e.g., TypeScript is not
+/// installed or not on the `PATH`).
+///
+/// # Examples
+///
+/// ```
+/// if !is_tsc_available() {
+///     println!("Falling back to import matching for TypeScript files.");
+/// }
+/// ```
+pub fn is_tsc_available() -> bool {
+    Command::new("tsc")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
 
-                Ok(_) => continue,
+/// The outcome of scanning a project's source files.
+#[derive(Debug, Default)]
+pub struct ScanResult {
+    /// Declared dependencies referenced by at least one scanned file.
+    pub used_packages: HashSet<String>,
+    /// Every package name imported or required by the scanned files, declared or not.
+    pub imported_packages: HashSet<String>,
+    /// Normalized paths of the files that were scanned, sorted.
+    pub explored_files: Vec<String>,
+    /// Normalized paths of the files or directories that were skipped by ignore rules, sorted.
+    pub ignored_files: Vec<String>,
+    /// The rule that skipped each ignored path, keyed by normalized path.
+    pub ignore_reasons: BTreeMap<String, IgnoreReason>,
+    /// Normalized paths of the files referencing each used dependency.
+    pub dependency_files: BTreeMap<String, Vec<String>>,
+    /// The bare specifiers each imported package was imported with (`@app/ui/button` for
+    /// `@app/ui`), only collected for a resolver script.
+    pub import_specifiers: BTreeMap<String, BTreeSet<String>>,
+    /// Warnings raised while scanning (e.g., `tsc` failures or timeouts).
+    pub warnings: Warnings,
+    /// Time spent running `tsc`, included in the scan.
+    pub tsc_duration: Duration,
+    /// Counters collected while scanning, reported by `--stats`.
+    pub stats: ScanStats,
+}
 
-                Err(e) => eprintln!("Failed to read entry: {}", e),
+impl ScanResult {
+    /// Records the bare specifiers among the literal specifiers of a file under their package.
+    fn add_import_specifiers(&mut self, specifiers: Vec<String>) {
+        for specifier in specifiers {
+            if let Some(package) = specifier_to_package(&specifier) {
+                self.import_specifiers
+                    .entry(package)
+                    .or_default()
+                    .insert(specifier);
             }
         }
     }
+}
+
+/// Counters describing what a scan covered, reported by `--stats`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ScanStats {
+    /// Number of scanned files per extension (without the leading dot, empty for files without
+    /// an extension).
+    pub files_per_extension: BTreeMap<String, usize>,
+    /// Total size in bytes of the scanned files that could be read.
+    pub bytes_read: u64,
+    /// Number of import statements found, counted once per imported package and file.
+    pub imports: usize,
+    /// Average number of declared dependencies matched per scanned file.
+    pub average_matches_per_file: f64,
+}
+
+/// The rule that caused a file or directory to be skipped during a scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IgnoreReason {
+    /// A path component matches an `IGNORE_FOLDERS` entry (e.g., `node_modules`).
+    Folder(String),
+    /// The path is inside a vendored directory (e.g., `vendor`), whose imports are not the project's.
+    Vendored(String),
+    /// The path matches a glob of the `[paths]` section of `.cnpignore`.
+    Path(String),
+    /// The path is a symbolic link, which is never followed.
+    Symlink,
+}
+
+impl fmt::Display for IgnoreReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IgnoreReason::Folder(folder) => write!(f, "inside ignored folder `{}`", folder),
+            IgnoreReason::Vendored(folder) => write!(f, "inside vendored folder `{}`", folder),
+            IgnoreReason::Path(pattern) => write!(f, "matches ignored path `{}`", pattern),
+            IgnoreReason::Symlink => write!(f, "symbolic link"),
+        }
+    }
+}
+
+/// Enumerates every path under a project directory whose extension is one of the configured
+/// extensions.
+///
+/// The project is walked once, in sorted order, and paths are filtered by extension, so each path
+/// is yielded exactly once no matter how many extensions are configured. Symbolic links are yielded
+/// but never followed, and each directory is entered at most once, so symlink or bind-mount cycles
+/// cannot make the walk loop. Ignore rules are not applied here.
+///
+/// # Arguments
+///
+/// * `options` - A reference to the `AnalyzeOptions` holding the project directory, the extensions
+///   and the maximum number of directory levels to descend into (`Some(1)` only lists the project
+///   directory itself, `None` sets no limit).
+///
+/// # Returns
+///
+/// Returns an iterator over the matching file or directory paths.
+///
+/// # Examples
+///
+/// ```
+/// for path in source_files(&AnalyzeOptions::new(".").max_depth(Some(10))) {
+///     println!("{}", path.display());
+/// }
+/// ```
+pub fn source_files(options: &AnalyzeOptions) -> impl Iterator<Item = PathBuf> + '_ {
+    let mut paths = Vec::new();
+    walk_directory(
+        Path::new(&options.root),
+        1,
+        options.max_depth,
+        &mut HashSet::new(),
+        &mut paths,
+    );
+    paths.into_iter().filter(|path| {
+        path.extension()
+            .and_then(OsStr::to_str)
+            .is_some_and(|ext| options.extensions.iter().any(|extension| extension == ext))
+    })
+}
+
+/// Collects the paths under a directory in pre-order, without following symbolic links.
+///
+/// `visited` holds the canonical paths of the directories already entered, which stops the walk
+/// on filesystem loops that are not symbolic links (e.g., bind mounts).
+fn walk_directory(
+    dir: &Path,
+    depth: usize,
+    max_depth: Option<usize>,
+    visited: &mut HashSet<PathBuf>,
+    paths: &mut Vec<PathBuf>,
+) {
+    let Ok(canonical) = fs::canonicalize(dir) else {
+        return;
+    };
+    if !visited.insert(canonical) {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
 
-    unused_imports
+    let mut entries: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    entries.sort();
+    for path in entries {
+        // `symlink_metadata` does not follow links, so symlinked directories are never entered
+        let is_dir = fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.is_dir());
+        paths.push(path);
+        if is_dir && max_depth.is_none_or(|max_depth| depth < max_depth) {
+            let path = paths.last().expect("Path was just pushed").clone();
+            walk_directory(&path, depth + 1, max_depth, visited, paths);
+        }
+    }
 }
 
 /// Scans project files to identify used dependencies, explored files, and ignored files.
 ///
-/// This function searches for files matching configured extensions (e.g., `.js`, `.ts`) using glob
-/// patterns, processes their content to find dependency usage, and respects ignore rules (e.g., for
-/// folders like `node_modules`). For TypeScript files, it integrates with `tsc` to exclude unused imports.
+/// This function searches for files matching configured extensions (e.g., `.js`, `.ts`) under the
+/// project directory (see [`source_files`]), processes their content to find dependency usage, and
+/// respects ignore rules (e.g., for folders like `node_modules`). For TypeScript files, it
+/// integrates with `tsc` to exclude unused imports, unless `use_tsc` is `false`, in which case
+/// TypeScript files are matched like JavaScript files. If `tsc` exceeds `tsc_timeout`, a warning is
+/// recorded and TypeScript files are matched like JavaScript files as well.
 ///
 /// # Arguments
 ///
+/// * `options` - A reference to the `AnalyzeOptions` holding the project directory, the extensions
+///   and ignored folders, whether to run `tsc` and for how long, the maximum scan depth and the
+///   number of threads reading the files.
 /// * `dependencies` - A reference to a `HashSet<String>` containing the project's dependencies.
-/// * `pb` - A reference to a `ProgressBar` for tracking scanning progress.
+/// * `progress` - Receives an update for each visited path.
 ///
 /// # Returns
 ///
-/// Returns a tuple `(HashSet<String>, Vec<String>, Vec<String>)` containing:
+/// Returns a `ScanResult` containing:
 /// - A `HashSet<String>` of used dependency names.
-/// - A `Vec<String>` of explored file paths (normalized).
-/// - A `Vec<String>` of ignored file or directory paths (normalized).
+/// - A `HashSet<String>` of every imported package name.
+/// - A sorted `Vec<String>` of explored file paths (normalized).
+/// - A sorted `Vec<String>` of ignored file or directory paths (normalized).
+/// - A `BTreeMap<String, IgnoreReason>` of the rule that skipped each ignored path.
+/// - A `BTreeMap<String, Vec<String>>` of the sorted files referencing each used dependency.
+/// - The `Warnings` raised while scanning.
 ///
 /// # Examples
 ///
 /// ```
 /// let dependencies = HashSet::new();
-/// let pb = ProgressBar::new(100);
-/// let (used, explored, ignored) = scan_files(&dependencies, &pb);
-/// println!("Used dependencies: {:?}", used);
-/// println!("Explored files: {:?}", explored);
-/// println!("Ignored files: {:?}", ignored);
+/// let options = AnalyzeOptions::new(".").use_tsc(true);
+/// let result = scan_files(&options, &dependencies, &NoProgress);
+/// println!("Used dependencies: {:?}", result.used_packages);
+/// println!("Explored files: {:?}", result.explored_files);
+/// println!("Ignored files: {:?}", result.ignored_files);
 /// ```
 pub fn scan_files(
+    options: &AnalyzeOptions,
     dependencies: &HashSet<String>,
-    pb: &ProgressBar,
-) -> (HashSet<String>, Vec<String>, Vec<String>) {
-    let patterns: Vec<String> = EXTENSIONS
-        .iter()
-        .map(|ext| format!("**/*.{}", ext))
-        .collect();
-    let mut used_packages = HashSet::new();
-    let mut ignored_files = Vec::new();
-    let mut explored_files = Vec::new();
+    progress: &dyn Progress,
+) -> ScanResult {
+    let root = Path::new(&options.root);
+    let mut result = ScanResult::default();
+    // Canonical paths already classified, in case a file is reachable through several paths
     let mut seen_paths = HashSet::new();
+    let mut javascript_files = Vec::new();
     let mut typescript_files = Vec::new();
 
-    for pattern in patterns {
-        for entry in glob(&pattern).expect("Failed to read glob pattern") {
-            pb.inc(1);
-
-            match entry {
-                Ok(path) if !path.is_dir() && !path.is_symlink() => {
-                    let abs_path = normalize_path(&path);
-                    if seen_paths.contains(&abs_path) {
-                        continue;
-                    }
-                    seen_paths.insert(abs_path.clone());
-
-                    if should_ignore(&path) {
-                        ignored_files.push(abs_path);
-                        continue;
-                    }
-
-                    let extension = path.extension().and_then(OsStr::to_str);
-                    if extension.map_or(false, |ext| TYPESCRIPT_EXTENSIONS.contains(&ext)) {
-                        typescript_files.push(abs_path.clone());
-                    } else if let Ok(content) = fs::read_to_string(&path) {
-                        used_packages.extend(find_dependencies_in_content(&content, dependencies));
-                        // deps from package.json only
-                    }
-
-                    explored_files.push(abs_path);
-                }
+    for path in source_files(options) {
+        progress.inc();
 
-                Ok(path) => {
-                    let abs_path = normalize_path(&path);
-                    if should_ignore(&path) && !seen_paths.contains(&abs_path) {
-                        ignored_files.push(abs_path.clone());
-                        seen_paths.insert(abs_path);
-                    }
-                }
+        // Classify each path exactly once: symlinks are keyed by their own path rather than
+        // their target
+        let is_symlink = path.is_symlink();
+        let abs_path = if is_symlink {
+            path.display().to_string()
+        } else {
+            normalize_path(&path)
+        };
+        if !seen_paths.insert(abs_path.clone()) {
+            continue;
+        }
 
-                Err(_) => {}
+        let relative_path = path.strip_prefix(root).unwrap_or(&path);
+        let reason = should_ignore(relative_path, &options.ignore_folders)
+            .or_else(|| vendored_folder(relative_path, &options.vendored_folders))
+            .or_else(|| ignored_path(relative_path, &options.ignore_paths))
+            .or_else(|| is_symlink.then_some(IgnoreReason::Symlink));
+        if let Some(reason) = reason {
+            result.ignored_files.push(abs_path.clone());
+            result.ignore_reasons.insert(abs_path, reason);
+        } else if !path.is_dir() {
+            let extension = path.extension().and_then(OsStr::to_str);
+            if extension.is_some_and(|ext| TYPESCRIPT_EXTENSIONS.contains(&ext)) {
+                typescript_files.push(abs_path.clone());
+            } else {
+                javascript_files.push(abs_path.clone());
             }
 
-            pb.tick();
+            *result
+                .stats
+                .files_per_extension
+                .entry(extension.unwrap_or_default().to_string())
+                .or_default() += 1;
+            result.explored_files.push(abs_path);
         }
     }
 
+    let mut matches = 0;
+    let findings = read_findings(&javascript_files, dependencies, options);
+    for (path, findings) in javascript_files.iter().zip(findings) {
+        let Some((found, imported, specifiers, bytes)) = findings else {
+            continue;
+        };
+        result.stats.bytes_read += bytes;
+        result.stats.imports += imported.len();
+        matches += found.len();
+        for dep in found {
+            result
+                .dependency_files
+                .entry(dep.clone())
+                .or_default()
+                .push(path.clone());
+            result.used_packages.insert(dep);
+        }
+        result.imported_packages.extend(imported);
+        result.add_import_specifiers(specifiers);
+    }
+
     // Process TypeScript files with tsc
-    let unused_imports = get_typescript_unused_imports("package.json");
-    for path in &typescript_files {
-        if let Ok(content) = fs::read_to_string(path) {
-            let found = find_dependencies_in_content(&content, dependencies);
-
-            for dep in found {
-                if !unused_imports.contains(&dep) {
-                    used_packages.insert(dep);
-                }
+    let tsc_start = Instant::now();
+    let unused_imports = if options.use_tsc {
+        get_typescript_unused_imports(
+            &options.root,
+            &typescript_files,
+            options.tsc_timeout,
+            options.cache,
+            &mut result.warnings,
+        )
+        .unwrap_or_else(|err| {
+            result.warnings.push(format!(
+                "{}. Matching TypeScript imports like JavaScript ones instead.",
+                err
+            ));
+            HashSet::new()
+        })
+    } else {
+        HashSet::new()
+    };
+    result.tsc_duration = tsc_start.elapsed();
+    let findings = read_findings(&typescript_files, dependencies, options);
+    for (path, findings) in typescript_files.iter().zip(findings) {
+        let Some((found, imported, specifiers, bytes)) = findings else {
+            continue;
+        };
+        result.stats.bytes_read += bytes;
+        result.stats.imports += imported.len();
+        matches += found.len();
+        for dep in found {
+            if !unused_imports.contains(&(path.clone(), dep.clone())) {
+                result
+                    .dependency_files
+                    .entry(dep.clone())
+                    .or_default()
+                    .push(path.clone());
+                result.used_packages.insert(dep);
             }
         }
+        result.imported_packages.extend(imported);
+        result.add_import_specifiers(specifiers);
+    }
+    if !result.explored_files.is_empty() {
+        result.stats.average_matches_per_file = matches as f64 / result.explored_files.len() as f64;
+    }
+
+    // Walk order differs between filesystems: sort the file lists so output is reproducible
+    result.explored_files.sort();
+    result.ignored_files.sort();
+    for files in result.dependency_files.values_mut() {
+        files.sort();
+    }
+
+    result
+}
+
+/// Matches import and require statements and dynamic `import()` calls (allowed in both CommonJS and
+/// ES modules), capturing the specifier. Import clauses may combine default, namespace
+/// (`* as name`) and named bindings. Block comments such as webpack magic comments
+/// (`import(/* webpackChunkName: "x" */ 'pkg')`) may precede the specifier of a call.
+/// The match ends at the specifier, so whatever follows a call (`require('pkg').default`,
+/// `require('pkg')()`) does not affect it.
+/// webpack's `require.context('./dir', ...)` is not a call of `require` and never matches: its
+/// argument is a directory, not a package. A specifier followed by `+` is only the start of a
+/// concatenation, resolved from [`CONCATENATION_IMPORT_REGEX`] instead.
+static IMPORT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?m)(?:import\s*(?:[\w$*\s,]|\{[^}]*\})*?\s*from\s*|(?:require|import)\s*\(\s*(?:/\*(?s:.*?)\*/\s*)*|import\s*)['"]([^'"]+)['"]"#,
+    )
+    .expect("Failed to compile regex")
+});
+
+/// Matches `require` and dynamic `import` calls whose argument is a template literal, capturing the
+/// template contents.
+static TEMPLATE_IMPORT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:require|import)\s*\(\s*(?:/\*(?s:.*?)\*/\s*)*`([^`]*)`"#)
+        .expect("Failed to compile regex")
+});
+
+/// Matches `require` and dynamic `import` calls whose argument concatenates a string literal with
+/// other operands (`require('lodash' + '/merge')`), capturing the expression.
+static CONCATENATION_IMPORT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:require|import)\s*\(\s*(?:/\*(?s:.*?)\*/\s*)*(['"][^'"]*['"]\s*\+[^),;]*)"#)
+        .expect("Failed to compile regex")
+});
+
+/// Matches static `import` declarations whose specifier is a backtick-quoted string without
+/// interpolation (``import x from `pkg` ``, as emitted by some transpilers), capturing the
+/// specifier. Templates with `${...}` are never static and are not matched.
+static STATIC_TEMPLATE_IMPORT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?m)(?:import\s*(?:[\w$*\s,]|\{[^}]*\})*?\s*from\s*|import\s*)`([^`$]+)`"#)
+        .expect("Failed to compile regex")
+});
+
+/// Matches the module mocking calls of Jest and Vitest (`jest.mock('pkg')`,
+/// `jest.requireActual('pkg')`, `vi.importActual('pkg')`, ...), capturing the specifier.
+static MOCK_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"\b(?:jest|vi)\s*\.\s*(?:mock|doMock|unmock|dontMock|requireActual|requireMock|importActual|importMock)\s*\(\s*['"]([^'"]+)['"]"#,
+    )
+    .expect("Failed to compile regex")
+});
+
+/// Matches AMD module definitions and requires with a dependency array
+/// (`define(['jquery'], function ($) {})`, `define('name', ['dep'], ...)`, `require(['dep'], ...)`),
+/// capturing the array contents.
+static AMD_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\b(?:define|require)\s*\(\s*(?:['"][^'"]*['"]\s*,\s*)?\[([^\]]*)\]"#)
+        .expect("Failed to compile regex")
+});
+
+/// Matches a quoted string, capturing its contents.
+static QUOTED_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"['"]([^'"]+)['"]"#).expect("Failed to compile regex"));
+
+/// The special dependencies of AMD modules, provided by the loader rather than by a package.
+const AMD_SPECIAL_DEPENDENCIES: [&str; 3] = ["require", "exports", "module"];
+
+/// Collects the specifiers of the dependency arrays of AMD `define` and `require` calls.
+fn amd_specifiers(content: &str) -> Vec<String> {
+    AMD_REGEX
+        .captures_iter(content)
+        .filter_map(|caps| caps.get(1))
+        .flat_map(|array| {
+            QUOTED_REGEX
+                .captures_iter(array.as_str())
+                .filter_map(|caps| Some(caps.get(1)?.as_str().to_string()))
+                .collect::<Vec<_>>()
+        })
+        .filter(|spec| !AMD_SPECIAL_DEPENDENCIES.contains(&spec.as_str()))
+        .collect()
+}
+
+/// Matches `createRequire` bindings (`const req = createRequire(import.meta.url)`), capturing the
+/// name of the bound require function.
+static CREATE_REQUIRE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:const|let|var)\s+([A-Za-z_$][\w$]*)\s*=\s*(?:\w+\.)?createRequire\s*\("#)
+        .expect("Failed to compile regex")
+});
+
+/// Matches calls of any function with a string literal first argument (`req("pkg")`), capturing
+/// the function name and the string.
+static STRING_CALL_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:^|[^\w$.])([A-Za-z_$][\w$]*)\s*\(\s*['"]([^'"]+)['"]"#)
+        .expect("Failed to compile regex")
+});
+
+/// Collects the specifiers passed to require functions created with `createRequire`.
+///
+/// A binding named `require` is already covered by the import regex, so only aliased bindings
+/// (`const req = createRequire(import.meta.url); req("pkg")`) are searched. Calls of a function
+/// with the same name but no `createRequire` binding are not matched.
+fn create_require_specifiers(content: &str) -> Vec<String> {
+    let bindings: HashSet<&str> = CREATE_REQUIRE_REGEX
+        .captures_iter(content)
+        .filter_map(|caps| caps.get(1))
+        .map(|name| name.as_str())
+        .filter(|name| *name != "require")
+        .collect();
+    if bindings.is_empty() {
+        return Vec::new();
     }
 
-    (used_packages, explored_files, ignored_files)
+    STRING_CALL_REGEX
+        .captures_iter(content)
+        .filter(|caps| bindings.contains(&caps[1]))
+        .filter_map(|caps| Some(caps.get(2)?.as_str().to_string()))
+        .collect()
 }
 
-/// Searches file content for references to project dependencies using regex patterns.
+/// Collects the package names of every bare import or require specifier in file content.
 ///
-/// This function builds regex patterns to match common import/require statements for each dependency
-/// and checks if they appear in the provided content.
+/// Each specifier is resolved with [`specifier_to_package`], so relative and absolute paths and
+/// URL-like specifiers (e.g. `node:fs`) are skipped, bare built-in names (`fs`) are kept, and subpath
+/// specifiers are reduced to their package name (`lodash/merge` -> `lodash`, `@scope/pkg/sub` ->
+/// `@scope/pkg`). Template literal arguments of `require` and `import()` are resolved with
+/// [`template_to_package`], so only templates with a static package prefix count, and so are string
+/// concatenations (`require('lodash' + '/merge')`, see [`concatenation_to_package`]); static
+/// `import` declarations may use backticks too, without interpolation. Calls of require
+/// functions created with `createRequire` count as requires, whatever the binding is named, and so
+/// do the module mocking calls of Jest and Vitest (`jest.mock('axios')`, `jest.requireActual`).
+/// Legacy module loaders are covered too: SystemJS `System.import('pkg')` calls, and the dependency
+/// arrays of AMD `define(['pkg'], ...)` and `require(['pkg'], ...)` calls, whose loader-provided
+/// `require`, `exports` and `module` entries are skipped.
 ///
 /// # Arguments
 ///
 /// * `content` - A string slice containing the file content to search.
-/// * `dependencies` - A reference to a `HashSet<String>` containing dependency names to look for.
 ///
 /// # Returns
 ///
-/// Returns a `HashSet<String>` containing the names of dependencies found in the content.
+/// Returns a `HashSet<String>` containing the package names imported by the content.
 ///
 /// # Examples
 ///
 /// ```
-/// let content = r#"import { foo } from "lodash"; require("moment");"#;
-/// let mut deps = HashSet::new();
-/// deps.insert("lodash".to_string());
-/// deps.insert("moment".to_string());
-/// let found = find_dependencies_in_content(content, &deps);
-/// assert!(found.contains("lodash"));
-/// assert!(found.contains("moment"));
+/// let content = r#"import fs from "node:fs"; import merge from "lodash/merge"; require("./local");"#;
+/// let found = find_imported_packages(content);
+/// assert_eq!(found, HashSet::from(["lodash".to_string()]));
 /// ```
-fn find_dependencies_in_content(content: &str, dependencies: &HashSet<String>) -> HashSet<String> {
-    let mut found = HashSet::new();
+pub fn find_imported_packages(content: &str) -> HashSet<String> {
+    imported_packages(content, &find_import_specifiers(content))
+}
+
+/// Collects the literal specifiers of every import or require in file content, as written.
+///
+/// The same statements as in [`find_imported_packages`] are searched, but specifiers are not
+/// resolved to package names (`@app/ui/button` stays whole) and relative paths are kept. Template
+/// literals with interpolations and string concatenations have no literal specifier and are left
+/// out.
+///
+/// # Arguments
+///
+/// * `content` - A string slice containing the file content to search.
+///
+/// # Returns
+///
+/// Returns a `Vec<String>` with the specifiers, in the order of their statements' kinds.
+///
+/// # Examples
+///
+/// ```
+/// let content = r#"import { Button } from "@app/ui/button"; require("./local");"#;
+/// assert_eq!(find_import_specifiers(content), vec!["@app/ui/button", "./local"]);
+/// ```
+pub fn find_import_specifiers(content: &str) -> Vec<String> {
+    let specifiers = IMPORT_REGEX
+        .captures_iter(content)
+        .filter(|caps| {
+            !content[caps.get(0).map_or(0, |m| m.end())..]
+                .trim_start()
+                .starts_with('+')
+        })
+        .filter_map(|caps| Some(caps.get(1)?.as_str().to_string()));
+    let static_template_specifiers = STATIC_TEMPLATE_IMPORT_REGEX
+        .captures_iter(content)
+        .filter_map(|caps| Some(caps.get(1)?.as_str().to_string()));
+    let mocked_specifiers = MOCK_REGEX
+        .captures_iter(content)
+        .filter_map(|caps| Some(caps.get(1)?.as_str().to_string()));
+
+    specifiers
+        .chain(static_template_specifiers)
+        .chain(mocked_specifiers)
+        .chain(create_require_specifiers(content))
+        .chain(amd_specifiers(content))
+        .collect()
+}
+
+/// Resolves the literal specifiers found in file content to package names, along with the
+/// packages of its template literal and concatenated specifiers.
+fn imported_packages(content: &str, specifiers: &[String]) -> HashSet<String> {
+    let concatenated_packages = CONCATENATION_IMPORT_REGEX
+        .captures_iter(content)
+        .filter_map(|caps| concatenation_to_package(caps.get(1)?.as_str()));
+    let template_packages = TEMPLATE_IMPORT_REGEX
+        .captures_iter(content)
+        .filter_map(|caps| template_to_package(caps.get(1)?.as_str()));
+
+    specifiers
+        .iter()
+        .filter_map(|spec| specifier_to_package(spec))
+        .chain(concatenated_packages)
+        .chain(template_packages)
+        .collect()
+}
+
+/// Matches the `@import` rules and the Tailwind CSS `@plugin` and `@config` directives of
+/// stylesheets (`@plugin "@tailwindcss/typography";`), capturing the directive and the specifier.
+static STYLESHEET_DIRECTIVE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"@(import|plugin|config)\s+(?:url\(\s*)?['"]([^'"]+)['"]"#)
+        .expect("Failed to compile regex")
+});
+
+/// Collects the package names referenced by the directives of a stylesheet.
+///
+/// `@plugin` and `@config` specifiers resolve like imports: Tailwind CSS loads bare specifiers from
+/// `node_modules`, while a relative `@config "./tailwind.config.js"` points at a project file that
+/// is scanned on its own. In an `@import`, a bare file name (`@import "theme.css"`) is relative in
+/// CSS, so only specifiers with a path or without an extension (`@import "tailwindcss"`) count, and
+/// the webpack `~` prefix (`@import "~bootstrap/dist/css/bootstrap.css"`) is dropped.
+///
+/// # Arguments
+///
+/// * `content` - A string slice containing the stylesheet content to search.
+///
+/// # Returns
+///
+/// Returns a `HashSet<String>` containing the package names referenced by the stylesheet.
+///
+/// # Examples
+///
+/// ```
+/// let content = r#"@import "tailwindcss"; @import "theme.css"; @plugin "@tailwindcss/typography";"#;
+/// let found = find_stylesheet_packages(content);
+/// assert_eq!(found, HashSet::from(["tailwindcss".to_string(), "@tailwindcss/typography".to_string()]));
+/// ```
+pub fn find_stylesheet_packages(content: &str) -> HashSet<String> {
+    STYLESHEET_DIRECTIVE_REGEX
+        .captures_iter(content)
+        .filter_map(|caps| {
+            let spec = caps.get(2)?.as_str();
+            if &caps[1] != "import" {
+                return specifier_to_package(spec);
+            }
+            let spec = spec.strip_prefix('~').unwrap_or(spec);
+            if !spec.contains('/') && spec.contains('.') {
+                return None;
+            }
+            specifier_to_package(spec)
+        })
+        .collect()
+}
+
+/// Matches the `<style>` blocks of single-file components, capturing their contents.
+static STYLE_BLOCK_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?s)<style(?:\s[^>]*)?>(.*?)</style\s*>").expect("Failed to compile regex")
+});
+
+/// Collects the package names referenced by the `<style>` blocks of a Vue or Svelte component,
+/// with [`find_stylesheet_packages`].
+///
+/// # Arguments
+///
+/// * `content` - A string slice containing the component source.
+///
+/// # Returns
+///
+/// Returns a `HashSet<String>` containing the package names referenced by the style blocks.
+///
+/// # Examples
+///
+/// ```
+/// let content = "<template><div /></template>\n<style scoped>\n@import 'normalize.css/normalize';\n</style>";
+/// assert_eq!(find_style_block_packages(content), HashSet::from(["normalize.css".to_string()]));
+/// ```
+pub fn find_style_block_packages(content: &str) -> HashSet<String> {
+    STYLE_BLOCK_REGEX
+        .captures_iter(content)
+        .flat_map(|caps| find_stylesheet_packages(&caps[1]))
+        .collect()
+}
+
+/// Checks whether a path has one of the `STYLESHEET_EXTENSIONS`.
+fn is_stylesheet(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|ext| STYLESHEET_EXTENSIONS.contains(&ext))
+}
 
-    for dep in dependencies {
-        let dep_pattern = regex::escape(dep);
-        let regex_str = format!(
-            r#"(?m)(?:import\s*(?:\{{[^}}]*\}}|\w*)\s*from\s*['"]{}['"]|require\s*\(\s*['"]{}['"]\s*\)|import\s*['"]{}['"]\s*;)"#,
-            dep_pattern, dep_pattern, dep_pattern
-        );
-        let regex = Regex::new(&regex_str).unwrap();
+/// Checks whether a path is an MDX document.
+fn is_mdx(path: &str) -> bool {
+    Path::new(path).extension().and_then(OsStr::to_str) == Some("mdx")
+}
 
-        if regex.is_match(content) {
-            found.insert(dep.clone());
+/// Isolates the ESM `import` and `export` statements of an MDX document from its markdown body.
+///
+/// As in MDX, an ESM block is a paragraph whose first line starts with `import` or `export`, and
+/// runs until the next blank line. YAML frontmatter and fenced code blocks are skipped, so package
+/// names mentioned in prose or in example code do not count as imports. JSX components only refer
+/// to packages through these imports.
+///
+/// # Arguments
+///
+/// * `content` - A string slice containing the MDX document.
+///
+/// # Returns
+///
+/// Returns the lines of the document's ESM blocks, joined with newlines.
+///
+/// # Examples
+///
+/// ```
+/// let content = "import { Chart } from 'recharts';\n\n# Charts\n\nWe used to import d3 from 'd3'.\n\n<Chart />";
+/// assert_eq!(mdx_module_code(content), "import { Chart } from 'recharts';");
+/// ```
+pub fn mdx_module_code(content: &str) -> String {
+    let mut lines = content.lines().peekable();
+    if lines.peek().is_some_and(|line| line.trim_end() == "---") {
+        lines.next();
+        for line in lines.by_ref() {
+            if line.trim_end() == "---" {
+                break;
+            }
         }
     }
 
-    found
+    let mut code = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut in_esm = false;
+    let mut paragraph_start = true;
+    for line in lines {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = Some(marker);
+            in_esm = false;
+            paragraph_start = false;
+            continue;
+        }
+        if trimmed.is_empty() {
+            in_esm = false;
+            paragraph_start = true;
+            continue;
+        }
+        if paragraph_start {
+            in_esm = ["import", "export"].iter().any(|keyword| {
+                line.strip_prefix(keyword).is_some_and(|rest| {
+                    rest.starts_with(|c: char| c.is_whitespace() || c == '{' || c == '*')
+                })
+            });
+        }
+        paragraph_start = false;
+        if in_esm {
+            code.push(line);
+        }
+    }
+    code.join("\n")
+}
+
+/// Matches a `cnp-keep`, `cnp-ignore-next-line` or `cnp-disable` pragma in a `//`, `/* */` or
+/// `<!-- -->` comment, capturing the pragma name and the rest of the line. The name must end at
+/// whitespace, the end of the comment or the end of the line, so `cnp-disable-next-line` or
+/// `cnp-keep-alive` are not taken for `cnp-disable` or `cnp-keep`.
+static PRAGMA_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?://|/\*|<!--)\s*cnp-(keep|ignore-next-line|disable)(?:\s|\*/|-->|$)([^\n]*)"#)
+        .expect("Failed to compile regex")
+});
+
+/// Applies the `cnp-*` pragma comments of a source file, from `--scan-comments-for-pragmas`.
+///
+/// Three pragmas are recognized in `//`, `/* */` and `<!-- -->` comments:
+/// - `cnp-keep <package>...` counts the listed packages (separated by spaces or commas, optionally
+///   quoted) as imported by the file, even without an import.
+/// - `cnp-ignore-next-line` drops the imports of the line that follows the comment.
+/// - `cnp-disable` drops every import of the file; `cnp-keep` pragmas still apply.
+///
+/// # Arguments
+///
+/// * `content` - A string slice containing the file content.
+///
+/// # Returns
+///
+/// Returns the content with the suppressed lines blanked out (or emptied by `cnp-disable`), and the
+/// package names listed by `cnp-keep` pragmas, resolved like import specifiers.
+///
+/// # Examples
+///
+/// ```
+/// let content = "// cnp-keep lodash\n// cnp-ignore-next-line\nimport dayjs from 'dayjs';";
+/// let (content, kept) = apply_pragmas(content);
+/// assert!(find_imported_packages(&content).is_empty());
+/// assert_eq!(kept, HashSet::from(["lodash".to_string()]));
+/// ```
+pub fn apply_pragmas(content: &str) -> (String, HashSet<String>) {
+    let mut kept = HashSet::new();
+    let mut disabled = false;
+    let mut ignored_lines = HashSet::new();
+    for (index, line) in content.lines().enumerate() {
+        for caps in PRAGMA_REGEX.captures_iter(line) {
+            match &caps[1] {
+                "keep" => {
+                    let names = caps[2].trim_end();
+                    let names = names
+                        .strip_suffix("*/")
+                        .or_else(|| names.strip_suffix("-->"))
+                        .unwrap_or(names);
+                    kept.extend(
+                        names
+                            .split([' ', '\t', ','])
+                            .map(|name| name.trim_matches(['\'', '"', '`']))
+                            .filter_map(specifier_to_package),
+                    );
+                }
+                "ignore-next-line" => {
+                    ignored_lines.insert(index + 1);
+                }
+                _ => disabled = true,
+            }
+        }
+    }
+
+    let content = if disabled {
+        String::new()
+    } else {
+        content
+            .lines()
+            .enumerate()
+            .map(|(index, line)| {
+                if ignored_lines.contains(&index) {
+                    ""
+                } else {
+                    line
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    (content, kept)
+}
+
+/// Checks whether a path has one of the `SFC_EXTENSIONS`.
+fn is_single_file_component(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|ext| SFC_EXTENSIONS.contains(&ext))
+}
+
+/// The declared dependencies and every package imported by one file, the literal specifiers of its
+/// imports when a resolver script needs them, and its size in bytes.
+type FileFindings = (HashSet<String>, HashSet<String>, Vec<String>, u64);
+
+/// Reads each file and finds the dependencies and packages it imports, on up to `threads` threads.
+///
+/// Stylesheets are searched with [`find_stylesheet_packages`], other files for JavaScript imports,
+/// and Vue and Svelte components also have their `<style>` blocks searched like stylesheets. Only
+/// the ESM statements of MDX documents are searched (see [`mdx_module_code`]).
+/// With `pragmas`, the `cnp-*` comments of non-stylesheet files are applied first (see
+/// [`apply_pragmas`]). Files are split into contiguous chunks, one per `threads`, and the findings
+/// are returned in the order of `files` (`None` for unreadable files), so the result does not
+/// depend on the thread count. Each file is parsed once: the dependencies it uses are the declared
+/// ones among its imported packages.
+fn read_findings(
+    files: &[String],
+    dependencies: &HashSet<String>,
+    options: &AnalyzeOptions,
+) -> Vec<Option<FileFindings>> {
+    let threads = options.threads;
+    let read = |path: &String| {
+        let content = fs::read_to_string(path).ok()?;
+        let bytes = content.len() as u64;
+        if is_stylesheet(path) {
+            let imported = find_stylesheet_packages(&content);
+            let found = imported
+                .iter()
+                .filter(|package| dependencies.contains(*package))
+                .cloned()
+                .collect();
+            return Some((found, imported, Vec::new(), bytes));
+        }
+        let (content, kept) = if options.pragmas {
+            apply_pragmas(&content)
+        } else {
+            (content, HashSet::new())
+        };
+        let content = if is_mdx(path) {
+            mdx_module_code(&content)
+        } else {
+            content
+        };
+        let specifiers = find_import_specifiers(&content);
+        let mut imported = imported_packages(&content, &specifiers);
+        imported.extend(kept);
+        if is_single_file_component(path) {
+            imported.extend(find_style_block_packages(&content));
+        }
+        let found = imported
+            .iter()
+            .filter(|package| dependencies.contains(*package))
+            .cloned()
+            .collect();
+        let specifiers = if options.resolver_script.is_some() {
+            specifiers
+        } else {
+            Vec::new()
+        };
+        Some((found, imported, specifiers, bytes))
+    };
+    if threads <= 1 || files.len() <= 1 {
+        return files.iter().map(read).collect();
+    }
+
+    let chunk_size = files.len().div_ceil(threads);
+    thread::scope(|scope| {
+        let workers: Vec<_> = files
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(read).collect::<Vec<_>>()))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("Scan thread panicked"))
+            .collect()
+    })
 }
 
 /// Determines if a path should be ignored based on configured ignore folders.
 ///
-/// Checks if any component of the path matches a folder in the `IGNORE_FOLDERS` list (e.g., `node_modules`).
+/// Checks if any component of the path matches one of the ignored folders (e.g., `node_modules`).
 ///
 /// # Arguments
 ///
-/// * `path` - A reference to a `Path` to check.
+/// * `path` - A reference to a `Path` to check, relative to the project directory.
+/// * `ignore_folders` - The ignored folder names, such as `IGNORE_FOLDERS`.
 ///
 /// # Returns
 ///
-/// Returns `true` if the path contains an ignored folder, `false` otherwise.
+/// Returns `Some(IgnoreReason::Folder)` naming the first ignored folder the path contains, `None`
+/// otherwise.
 ///
 /// # Examples
 ///
 /// ```
 /// let path = Path::new("node_modules/package/file.js");
-/// assert!(should_ignore(&path)); // node_modules is ignored
+/// assert_eq!(should_ignore(&path, &IGNORE_FOLDERS), Some(IgnoreReason::Folder("node_modules".to_string())));
 /// let path = Path::new("src/file.js");
-/// assert!(!should_ignore(&path)); // src is not ignored
+/// assert_eq!(should_ignore(&path, &IGNORE_FOLDERS), None); // src is not ignored
 /// ```
-fn should_ignore(path: &Path) -> bool {
-    path.components().any(|component| {
-        IGNORE_FOLDERS
+pub fn should_ignore<S: AsRef<str>>(path: &Path, ignore_folders: &[S]) -> Option<IgnoreReason> {
+    path.components().find_map(|component| {
+        ignore_folders
             .iter()
-            .any(|folder| component.as_os_str() == OsStr::new(folder))
+            .map(AsRef::as_ref)
+            .find(|folder| component.as_os_str() == OsStr::new(folder))
+            .map(|folder| IgnoreReason::Folder(folder.to_string()))
+    })
+}
+
+/// Determines if a path is inside a vendored directory.
+///
+/// A vendored entry without a `/` (e.g., `vendor`) matches a folder of that name anywhere in the
+/// project; an entry with a `/` (e.g., `libs/legacy`) matches that path from the project directory.
+///
+/// # Arguments
+///
+/// * `path` - A reference to a `Path` to check, relative to the project directory.
+/// * `vendored` - The vendored folder names or paths, such as `VENDORED_FOLDERS`.
+///
+/// # Returns
+///
+/// Returns `Some(IgnoreReason::Vendored)` naming the matching entry, `None` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// let vendored = ["vendor", "libs/legacy"];
+/// assert!(vendored_folder(Path::new("packages/ui/vendor/jquery.js"), &vendored).is_some());
+/// assert!(vendored_folder(Path::new("libs/legacy/index.js"), &vendored).is_some());
+/// assert!(vendored_folder(Path::new("src/libs/legacy/index.js"), &vendored).is_none());
+/// ```
+pub fn vendored_folder<S: AsRef<str>>(path: &Path, vendored: &[S]) -> Option<IgnoreReason> {
+    vendored
+        .iter()
+        .map(|entry| entry.as_ref().trim_matches('/'))
+        .find(|entry| {
+            if entry.contains('/') {
+                path.starts_with(entry)
+            } else {
+                path.components()
+                    .any(|component| component.as_os_str() == OsStr::new(entry))
+            }
+        })
+        .map(|entry| IgnoreReason::Vendored(entry.to_string()))
+}
+
+/// Determines if a path, or one of its parent directories, matches an ignored path glob.
+///
+/// Globs are matched from the project directory: `*` stays within one path component while `**`
+/// spans several, so `src/generated` and `src/generated/**` both exclude everything below
+/// `src/generated`. Invalid globs never match.
+///
+/// # Arguments
+///
+/// * `path` - A reference to a `Path` to check, relative to the project directory.
+/// * `patterns` - The path globs, such as the `[paths]` section of `.cnpignore`.
+///
+/// # Returns
+///
+/// Returns `Some(IgnoreReason::Path)` naming the first matching glob, `None` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// let patterns = ["src/generated", "**/*.stories.js"];
+/// assert!(ignored_path(Path::new("src/generated/api.js"), &patterns).is_some());
+/// assert!(ignored_path(Path::new("src/button.stories.js"), &patterns).is_some());
+/// assert!(ignored_path(Path::new("src/button.js"), &patterns).is_none());
+/// ```
+pub fn ignored_path<S: AsRef<str>>(path: &Path, patterns: &[S]) -> Option<IgnoreReason> {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    patterns.iter().map(AsRef::as_ref).find_map(|entry| {
+        let pattern = Pattern::new(entry.trim_matches('/')).ok()?;
+        path.ancestors()
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .any(|ancestor| pattern.matches_path_with(ancestor, options))
+            .then(|| IgnoreReason::Path(entry.to_string()))
     })
 }
 
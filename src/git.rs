@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Checks whether a directory is inside a git work tree.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the directory.
+///
+/// # Returns
+///
+/// Returns `true` if `git rev-parse --is-inside-work-tree` succeeds in the directory, `false`
+/// otherwise (e.g., not a repository or git is not installed).
+///
+/// # Examples
+///
+/// ```
+/// if is_git_repository(".") {
+///     println!("Git history is available.");
+/// }
+/// ```
+pub fn is_git_repository(dir_path: &str) -> bool {
+    Command::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .current_dir(dir_path)
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Returns the timestamp of the latest commit touching any of the given files.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the repository directory.
+/// * `files` - A slice of `String` containing the file paths to look at.
+///
+/// # Returns
+///
+/// Returns `Some(u64)` with the commit time in seconds since the Unix epoch, or `None` if none of
+/// the files has been committed or git fails.
+///
+/// # Examples
+///
+/// ```
+/// let timestamp = last_commit_timestamp(".", &["src/index.js".to_string()]);
+/// println!("Last commit: {:?}", timestamp);
+/// ```
+pub fn last_commit_timestamp(dir_path: &str, files: &[String]) -> Option<u64> {
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%ct", "--"])
+        .args(files)
+        .current_dir(dir_path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// Estimates how recently each used dependency was last referenced, from git history.
+///
+/// For every dependency, the age is the number of whole days since the latest commit touching
+/// one of the files that reference it. This runs one `git log` per dependency, so it is
+/// expensive and only meant for the opt-in `--git-age` mode. Outside a git repository, nothing is
+/// computed.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `dependency_files` - A reference to the map of used dependencies to the files referencing
+///   them.
+///
+/// # Returns
+///
+/// Returns a `BTreeMap<String, u64>` mapping dependencies to their age in days. Dependencies
+/// whose files were never committed are left out.
+///
+/// # Examples
+///
+/// ```
+/// let ages = dependency_ages(".", &analysis.dependency_files);
+/// for (dep, days) in &ages {
+///     println!("{} last used {} days ago", dep, days);
+/// }
+/// ```
+pub fn dependency_ages(
+    dir_path: &str,
+    dependency_files: &BTreeMap<String, Vec<String>>,
+) -> BTreeMap<String, u64> {
+    if !is_git_repository(dir_path) {
+        return BTreeMap::new();
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+
+    dependency_files
+        .iter()
+        .filter_map(|(dep, files)| {
+            let timestamp = last_commit_timestamp(dir_path, files)?;
+            Some((dep.clone(), now.saturating_sub(timestamp) / SECONDS_PER_DAY))
+        })
+        .collect()
+}
@@ -0,0 +1,132 @@
+use crate::config::{
+    CONFIG_FILE_NAME, EXTENSIONS, IGNORE_FOLDERS, SIDE_EFFECT_PACKAGES, VENDORED_FOLDERS,
+};
+use std::fs;
+use std::path::Path;
+
+/// The ignore file listing dependencies that are never reported as unused.
+pub const CNPIGNORE_FILE_NAME: &str = ".cnpignore";
+
+/// Renders a commented starter `cnp.toml` showing every setting with its default value.
+///
+/// The scanned extensions and ignored folders are built in, so they are listed as comments for
+/// reference only.
+///
+/// # Returns
+///
+/// Returns a `String` with the TOML document.
+///
+/// # Examples
+///
+/// ```
+/// let config: Config = toml::from_str(&starter_config()).unwrap();
+/// assert!(config.strict_manifest);
+/// ```
+pub fn starter_config() -> String {
+    let quote = |values: &[&str]| {
+        values
+            .iter()
+            .map(|value| format!("\"{}\"", value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    format!(
+        r#"# cnp configuration. Every setting is optional; the values below are the defaults.
+
+# Scanned file extensions (built in): {extensions}
+# Ignored folders (built in): {folders}
+
+# Packages loaded only for their side effects (polyfills). Never reported as unused.
+side_effect_packages = [{side_effects}]
+
+# Set to false to tolerate comments and trailing commas in package.json.
+strict_manifest = true
+
+# Packages required at runtime without any import (e.g. preloaded with `node -r dotenv/config`).
+runtime_required = []
+
+# Set to false to report `@types/node` as unused when nothing needs it.
+keep_types_node = true
+
+# Vendored third-party code whose imports never count as usage (folder names or project paths).
+vendored = [{vendored}]
+
+# Additional file extensions to scan for import/require strings (e.g. template files).
+extra_extensions = []
+
+# Name prefixes of workspace-internal packages (e.g. "@myorg/*"). Never reported as unused.
+workspace_packages = []
+
+# Groups of packages serving the same purpose, reported together by --check-duplicates-in-code
+# (e.g. [["zod", "yup"]]), in addition to the built-in groups.
+duplicate_purpose_groups = []
+"#,
+        extensions = EXTENSIONS.join(", "),
+        folders = IGNORE_FOLDERS.join(", "),
+        side_effects = quote(&SIDE_EFFECT_PACKAGES),
+        vendored = quote(&VENDORED_FOLDERS),
+    )
+}
+
+/// The example `.cnpignore` written by `--init`.
+pub const STARTER_CNPIGNORE: &str =
+    "# Dependencies listed here are never reported as unused (one per line).
+# Use it for packages that are only loaded at runtime or by tooling, e.g.:
+# react-dom
+# eslint
+#
+# Files matching the globs of a [paths] section are not scanned, e.g.:
+# [paths]
+# src/generated
+";
+
+/// Writes a starter `cnp.toml` and an example `.cnpignore` into a project directory.
+///
+/// Existing files are never overwritten unless `force` is set; in that case nothing is written at
+/// all, so a project is never left half-initialized.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `force` - If `true`, overwrites existing files.
+///
+/// # Returns
+///
+/// Returns `Ok(Vec<String>)` with the names of the written files.
+/// Returns `Err(String)` with an error message if a file already exists or cannot be written.
+///
+/// # Examples
+///
+/// ```
+/// match init_project(".", false) {
+///     Ok(files) => println!("Created {}", files.join(", ")),
+///     Err(e) => eprintln!("{}", e),
+/// }
+/// ```
+pub fn init_project(dir_path: &str, force: bool) -> Result<Vec<String>, String> {
+    let files = [
+        (CONFIG_FILE_NAME, starter_config()),
+        (CNPIGNORE_FILE_NAME, STARTER_CNPIGNORE.to_string()),
+    ];
+
+    let existing = files
+        .iter()
+        .filter(|_| !force)
+        .find(|(name, _)| Path::new(dir_path).join(name).exists());
+    if let Some((name, _)) = existing {
+        return Err(format!(
+            "Error: `{}` already exists. Use --force to overwrite it.",
+            name
+        ));
+    }
+
+    files
+        .iter()
+        .map(|(name, content)| {
+            fs::write(Path::new(dir_path).join(name), content)
+                .map(|_| name.to_string())
+                .map_err(|e| format!("Error: Failed to write `{}`: {}", name, e))
+        })
+        .collect()
+}
@@ -0,0 +1,42 @@
+//! Checks which node packages a JavaScript or TypeScript project declares but never uses.
+//!
+//! The `cnp` binary is built with the default `cli` feature. Without it, the crate is a plain
+//! library that does not depend on `clap`, `dialoguer`, `indicatif` or `notify`:
+//!
+//! ```toml
+//! cnp = { version = "1", default-features = false }
+//! ```
+//!
+//! ```
+//! use cnp::analysis::{AnalyzeOptions, analyze};
+//! use cnp::progress::NoProgress;
+//!
+//! let options = AnalyzeOptions::new("./app").max_depth(Some(8));
+//! let analysis = analyze(&options, &NoProgress)?;
+//! println!("Unused: {:?}", analysis.unused_dependencies);
+//! ```
+
+pub mod analysis;
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod compare;
+pub mod config;
+pub mod dependency;
+pub mod file_scanner;
+pub mod git;
+pub mod init;
+pub mod package_manager;
+pub mod progress;
+pub mod report;
+pub mod resolve;
+pub mod tarball;
+#[cfg(feature = "cli")]
+pub mod uninstall;
+pub mod utils;
+pub mod warnings;
+#[cfg(feature = "cli")]
+pub mod watch;
+pub mod workspace;
+
+#[cfg(test)]
+mod tests;
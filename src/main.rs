@@ -1,41 +1,146 @@
-mod config;
-mod dependency;
-mod file_scanner;
-mod package_manager;
-mod report;
-mod uninstall;
-mod utils;
-
-#[cfg(test)]
-mod tests;
-
-use clap::{Arg, ArgAction, Command};
-use colored::*;
-use config::PACKAGE_JSON_PATH;
-use dependency::read_package_json;
-use file_scanner::scan_files;
-use report::print_dependency_report;
-use std::collections::HashSet;
-use uninstall::handle_unused_dependencies;
+use cnp::cli::{Options, build_command, run};
+use cnp::watch::watch;
+use std::io;
 
 /// Entry point for the dependency analysis tool.
 ///
-/// This function orchestrates the process of analyzing a project's dependencies by:
-/// - Parsing command-line arguments to determine modes (`--dry-run`, `--interactive`, `--all`).
+/// This function orchestrates the process of analyzing one or more projects' dependencies by:
+/// - Parsing command-line arguments to determine the project paths and modes (`--dry-run`,
+///   `--interactive`, `--format`, `--count`).
 /// - Reading the `package.json` file to extract dependencies.
 /// - Scanning project files to identify used dependencies.
-/// - Comparing used and declared dependencies to find unused ones, respecting required and ignored dependencies.
-/// - Printing a dependency report.
-/// - Handling unused dependencies (e.g., prompting for removal) based on the provided flags.
+/// - Comparing used and declared dependencies to find unused ones, respecting required, ignored and
+///   side-effect-only dependencies (configured in `cnp.toml`).
+/// - Printing a dependency report in the requested format.
+/// - Handling unused dependencies (e.g., prompting for removal) based on the provided flags. This
+///   step only runs for the full console report.
 ///
-/// The program exits with a status code of 1 if `package.json` cannot be read or parsed.
+/// The program exits with a status code of 1 if any project path is not a directory or its
+/// `package.json` cannot be read or parsed.
 /// A progress bar provides visual feedback during initialization and file scanning.
 ///
 /// # Command-line Arguments
 ///
+/// - `[PATHS]...`: Project directories to analyze (defaults to the current directory). With several
+///   paths, the report is sectioned by project.
 /// - `--dry-run`: Simulates actions without making changes (e.g., no uninstalls).
 /// - `--interactive` or `-i`: Prompts the user before taking actions on unused dependencies.
-/// - `--all` or `-a`: Automatically processes all unused dependencies without prompting.
+/// - `--preselect-all`: Starts the interactive prompt with every unused dependency selected (`a`
+///   selects or unselects them all in the prompt).
+/// - `--format <console|json|github|junit|html|toml>`: Selects the report format (defaults to
+///   `console`). The `github` format prints GitHub Actions workflow commands pointing at
+///   `package.json` lines, the `junit` format prints JUnit XML where unused and missing dependencies
+///   are failed tests, the `html` format prints a self-contained HTML page with collapsible
+///   dependency lists, and the `toml` format prints a `[summary]` table and `[[used]]`,
+///   `[[unused]]`, `[[unused_dev]]` and `[[missing]]` arrays of tables (under `[[projects]]` when
+///   several projects are analyzed).
+/// - `--color <always|never|auto>`: Colors the output always, never, or only when stdout is a
+///   terminal and `NO_COLOR` is not set (`auto`, the default).
+/// - `--count`: Prints only the `total`, `used`, `unused` and `missing` dependency counts.
+/// - `--print-config`: Prints the effective configuration and resolved flags (as TOML, or JSON with `--format json`)
+///   and exits.
+/// - `--types-only`: Only reports `@types/*` packages whose base package is no longer declared.
+/// - `--audit-scripts`: Flags unused dependencies whose installed package declares install
+///   lifecycle scripts (`preinstall`, `install`, `postinstall`).
+/// - `--reinstall <always|never|auto>`: Whether `node_modules` is reinstalled after removing unused
+///   dependencies (`always` by default). `auto` runs a plain install only if a removed package is
+///   still in `node_modules`. A failed install shows the install command's stderr.
+/// - `--frozen-install`: Reinstalls with the install variant that never updates the lockfile
+///   (`npm ci`, `yarn install --immutable`, `pnpm install --frozen-lockfile`), for deterministic CI
+///   runs. npm projects without a `package-lock.json` still run `npm install`.
+/// - `--report-file <FORMAT=PATH>`: Also writes the report in `json`, `github`, `junit`, `html` or
+///   `toml` format to `PATH`, alongside the report printed in `--format`. Repeatable.
+/// - `--threads <N>`: Reads and matches source files on `N` threads. The default, `1`, runs the
+///   whole analysis sequentially for reproducible logs; results are identical for any `N`.
+/// - `--profile`: Prints the time spent reading the manifest, parsing lockfiles, scanning files,
+///   running `tsc` and reporting (a `timings` object in JSON).
+/// - `--stats`: Prints the scanned files per extension, the bytes read, the import statements found
+///   and the average matches per file (a `stats` object in JSON). Nothing is sent anywhere.
+/// - `--scan-comments-for-pragmas`: Honors pragma comments in source files: `cnp-keep <package>...`
+///   counts the listed packages as used, `cnp-ignore-next-line` drops the imports of the next line
+///   and `cnp-disable` drops every import of the file.
+/// - `--max-warnings <N>`: Exits with a non-zero code when more than `N` warnings are raised
+///   (unlimited by default), even if they are silenced with `--no-warnings`.
+/// - `--dedupe-check`: Lists packages resolved to more than one version in the lockfile.
+/// - `--bin`: Lists the commands run by `package.json` scripts that no dependency installed in
+///   `node_modules` provides in its `bin` field.
+/// - `--check-entrypoints`: Lists the `main`, `module`, `types`, `typings` and `exports` entries of
+///   `package.json` whose target file does not exist (`main` is resolved like Node.js does, with
+///   `.js` and `index.js` fallbacks; subpath patterns are skipped).
+/// - `--check-duplicates-in-code`: Lists groups of packages serving the same purpose (`moment`
+///   and `dayjs`, `lodash` and `lodash-es`) that are imported together. Groups are extended with
+///   `duplicate_purpose_groups` in `cnp.toml`.
+/// - `--ignore-dev`: Only counts the packages that production lockfile entries depend on as
+///   required, leaving out the declared dependencies themselves and development-only entries (npm
+///   packages marked `dev`, pnpm `devDependencies`), so an unused production dependency is
+///   reported.
+/// - `--report-types-node`: Reports `@types/node` as unused like any other package, even when
+///   `keep_types_node` is on in `cnp.toml` (the default).
+/// - `--use-pm-ls`: When the lockfile is missing or cannot be parsed, reads the installed packages
+///   from `npm ls --all --json` or `pnpm ls --json` instead. This is slower and needs
+///   `node_modules`.
+/// - `--fail-empty`: Exits with a non-zero code when a project declares dependencies but no source
+///   file was scanned. Without it, such projects only get a warning; in both cases nothing is
+///   reported as unused.
+/// - `--manifest-only`: Skips the file scan for a quick check: the declared dependencies are
+///   counted and those missing from the lockfile are reported as warnings, but nothing is reported
+///   as unused or missing.
+/// - `--dependencies-from <FILE>`: Analyzes the package names listed in `FILE` (one per line, `#`
+///   for comments) instead of the dependencies of `package.json`. Neither the manifest nor the
+///   lockfile is read.
+/// - `--tarball <PATH>`: Extracts a package tarball (e.g., from `npm pack`) with the system `tar`
+///   into a temporary directory, analyzes it in place of the project paths and removes it afterwards.
+///   Nothing is uninstalled from the extracted package.
+/// - `--lockfile <NAME>`: Reads `NAME` (`package-lock.json`, `yarn.lock`, `pnpm-lock.yaml` or
+///   `bun.lock`) when the project directory holds several lockfiles. Lockfiles in subdirectories
+///   are never read.
+/// - `--allow-empty`: Prints a single line instead of the console report for projects that declare
+///   no dependencies.
+/// - `--report-used-only` / `--report-unused-only`: Only lists the used (or unused) dependencies in
+///   the console report; JSON output stays complete.
+/// - `--reporter-columns <METRICS>`: Selects and orders the rows of the console metrics table
+///   (comma-separated names such as `project,explored-files,unused`). `bytes-scanned` and
+///   `scan-duration` are filled by `--stats` and `--profile`; unknown names are rejected.
+/// - `--summary-json-stderr`: Also prints the JSON report on stderr while the console report goes
+///   to stdout.
+/// - `--watch` or `-w`: Re-runs the analysis whenever source files, `package.json`, `cnp.toml` or
+///   `.cnpignore` change, without ever removing packages.
+/// - `--packages <NAMES>`: Only analyzes the given packages (comma-separated or repeated); the report
+///   and exit code ignore every other package.
+/// - `--max-depth <N>`: Scans at most `N` directory levels below the project directory. Symbolic
+///   links are never followed, so the scan always terminates.
+/// - `--compare <FILE>`: Prints the dependencies newly unused, newly used and resolved since a
+///   JSON report saved with `--format json`, and exits with code 1 on newly unused ones.
+/// - `--fail-on <all|production>`: Exits with code 1 when unused dependencies are found; `production`
+///   only fails on `dependencies` and reports unused `devDependencies` for information.
+/// - `--diff-exit-code`: Exits with a code telling the findings apart: `0` when clean, `3` when
+///   only unused dependencies are found, `4` when only missing ones are, `5` for both. Fatal errors
+///   (and `--max-warnings`, `--fail-empty` or `--compare` failures) keep exit code `1`. Unused
+///   devDependencies do not count. Conflicts with `--fail-on`.
+/// - `--summary-line-format <TEMPLATE>`: Prints a single line at the end of the run, rendered from
+///   `{{name}}` variables summed over every project: `projects`, `total`, `used`, `unused`,
+///   `unused_dev`, `missing`, `files` and `ignored`. Unknown variables are rejected. The line goes
+///   to stderr beside the JSON, JUnit, HTML and TOML formats.
+/// - `--init`: Writes a commented starter `cnp.toml` and an example `.cnpignore` into the project,
+///   refusing to overwrite existing files unless `--force` is given.
+/// - `--workspaces`: Also analyzes every workspace package of each project, discovered from
+///   `pnpm-workspace.yaml` and the `workspaces` field of `package.json`.
+/// - `--git-age`: Shows how many days ago each used dependency was last referenced in a commit
+///   (skipped outside a git repository).
+/// - `--explain-ignored`: Lists every ignored file with the rule that skipped it.
+/// - `--no-warnings`: Silences the warnings raised during the analysis, which are otherwise printed
+///   to stderr, or included in the JSON report and GitHub annotations.
+/// - `--no-tsc`: Skips `tsc` and matches TypeScript imports like JavaScript ones. This is the
+///   default when `tsc` is not found, and in CI (when the `CI` environment variable is set) unless
+///   `--tsc` is given.
+/// - `--tsc-timeout <SECS>`: Seconds `tsc` may run (60 by default) before cnp warns and matches
+///   TypeScript imports like JavaScript ones.
+/// - `--no-cache`: Runs `tsc` even when its diagnostics are cached in
+///   `node_modules/.cache/cnp/tsc.json` and the TypeScript files and `tsconfig.json` are unchanged.
+/// - `--resolver-script <PATH>`: Runs `PATH` in the project directory with the specifiers of the
+///   undeclared imports on stdin, as written (`@app/ui/button`), one per line. It prints one line
+///   per specifier: the package it resolves to, `-` if it is not a package, or an empty line to
+///   leave it as is. Resolved packages count as used; a failing script only raises a warning.
 ///
 /// # Examples
 ///
@@ -49,75 +154,22 @@ use uninstall::handle_unused_dependencies;
 /// # Run in interactive mode to confirm actions
 /// cargo run -- --interactive
 ///
-/// # Process all unused dependencies automatically
-/// cargo run -- --all
+/// # Analyze several sibling projects at once
+/// cargo run -- ./app ./api ./shared
+///
+/// # Print only the dependency counts as JSON
+/// cargo run -- --count --format json
 /// ```
 fn main() {
     // Parse command-line arguments
-    let matches = Command::new("Check Node Packages")
-        .about("A utility tool written in Rust to check unused node packages.")
-        .arg(
-            Arg::new("dry-run")
-                .long("dry-run")
-                .help("Simulate actions without making changes (e.g., no uninstalls)")
-                .action(ArgAction::SetTrue),
-        )
-        .arg(
-            Arg::new("interactive")
-                .short('i')
-                .long("interactive")
-                .help("Prompt the user before taking actions on unused dependencies")
-                .action(ArgAction::SetTrue),
-        )
-        .get_matches();
-
-    // Parse the arguments
-    let dry_run: bool = *matches.get_one("dry-run").unwrap_or(&false);
-    let interactive: bool = *matches.get_one("interactive").unwrap_or(&false);
-
-    // Initialize progress bar
-    let pb = utils::create_spinner("Initializing...");
-
-    // Read package.json
-    let package_json = read_package_json(PACKAGE_JSON_PATH).unwrap_or_else(|err| {
-        eprintln!("{}", err.red());
-        std::process::exit(1);
-    });
-
-    // Collect dependencies
-    let dependencies: HashSet<String> = package_json
-        .get("dependencies")
-        .and_then(serde_json::Value::as_object)
-        .map_or_else(HashSet::new, |map| map.keys().cloned().collect());
-
-    // Scan for used dependencies
-    pb.set_message("Scanning files...");
-    let (used_packages, explored_files, ignored_files) = scan_files(&dependencies, &pb);
-
-    pb.finish_with_message("Scanning complete!".green().to_string());
-
-    // Identify unused dependencies
-    let dir_path = std::env::current_dir().unwrap_or_default();
-    let required_deps = dependency::get_required_dependencies(dir_path.to_str().unwrap());
-    let cnp_ignore_path = dir_path.join(".cnpignore");
-    let ignored_deps = dependency::read_cnpignore(cnp_ignore_path.to_str().unwrap_or_default());
-    let unused_dependencies: Vec<_> = dependencies
-        .difference(&used_packages)
-        .filter(|dep| !required_deps.contains(*dep) && !ignored_deps.contains(*dep))
-        .cloned()
-        .collect();
-
-    // Print report
-    print_dependency_report(
-        &dependencies,
-        &used_packages,
-        &unused_dependencies,
-        &explored_files,
-        &ignored_files,
-    );
+    let matches = build_command().get_matches();
+    let options = Options::from_matches(&matches);
+    options.color.apply();
 
-    // Process unused dependencies
-    if !unused_dependencies.is_empty() {
-        handle_unused_dependencies(&unused_dependencies, dry_run, interactive);
-    }
+    let exit_code = if options.watch {
+        watch(&options)
+    } else {
+        run(&options, &mut io::stdout(), &mut io::stderr())
+    };
+    std::process::exit(exit_code);
 }
@@ -1,10 +1,17 @@
+use serde_json::Value;
+use std::collections::HashSet;
 use std::path::Path;
+use std::process::Command;
 
-/// Detects the package manager used in the current project based on lockfile presence.
+/// Detects the package manager used in a project based on lockfile presence.
 ///
 /// This function checks for specific lockfiles (`pnpm-lock.yaml`, `yarn.lock`, `bun.lock`) to
 /// determine the package manager. If none are found, it defaults to `npm`.
 ///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+///
 /// # Returns
 ///
 /// Returns a `String` representing the detected package manager:
@@ -16,18 +23,121 @@ use std::path::Path;
 /// # Examples
 ///
 /// ```
-/// let package_manager = detect_package_manager();
+/// let package_manager = detect_package_manager(".");
 /// println!("Detected package manager: {}", package_manager);
 /// // If `yarn.lock` exists, prints: "Detected package manager: yarn"
 /// ```
-pub fn detect_package_manager() -> String {
-    if Path::new("pnpm-lock.yaml").exists() {
+pub fn detect_package_manager(dir_path: &str) -> String {
+    let dir_path = Path::new(dir_path);
+    if dir_path.join("pnpm-lock.yaml").exists() {
         "pnpm".to_string()
-    } else if Path::new("yarn.lock").exists() {
+    } else if dir_path.join("yarn.lock").exists() {
         "yarn".to_string()
-    } else if Path::new("bun.lock").exists() {
+    } else if dir_path.join("bun.lock").exists() {
         "bun".to_string()
     } else {
         "npm".to_string()
     }
 }
+
+/// Lists every package installed in a project by asking its package manager.
+///
+/// This runs `npm ls --all --json` or `pnpm ls --json --depth Infinity`, depending on the
+/// detected package manager, and parses the output with [`parse_package_manager_ls`]. It is
+/// slower than reading the lockfile but reflects what is actually installed, so it requires
+/// `node_modules`. npm exits with a failure status when the tree has problems (e.g., extraneous
+/// packages) but still prints it, so its output is used whenever it parses.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+///
+/// # Returns
+///
+/// Returns `Ok(HashSet<String>)` with the name of every installed package, at any depth.
+/// Returns `Err(String)` with an error message if `node_modules` does not exist, the package
+/// manager is neither npm nor pnpm, or it cannot be run or prints no valid tree.
+///
+/// # Examples
+///
+/// ```
+/// match package_manager_ls(".") {
+///     Ok(installed) => println!("{} packages installed", installed.len()),
+///     Err(e) => eprintln!("{}", e),
+/// }
+/// ```
+pub fn package_manager_ls(dir_path: &str) -> Result<HashSet<String>, String> {
+    if !Path::new(dir_path).join("node_modules").is_dir() {
+        return Err("Listing installed packages requires node_modules.".to_string());
+    }
+
+    let package_manager = detect_package_manager(dir_path);
+    let args: &[&str] = match package_manager.as_str() {
+        "npm" => &["ls", "--all", "--json"],
+        "pnpm" => &["ls", "--json", "--depth", "Infinity"],
+        other => {
+            return Err(format!(
+                "Listing installed packages is not supported with {}.",
+                other
+            ));
+        }
+    };
+
+    let output = Command::new(&package_manager)
+        .args(args)
+        .current_dir(dir_path)
+        .output()
+        .map_err(|e| format!("Failed to run {} ls: {}", package_manager, e))?;
+    parse_package_manager_ls(&String::from_utf8_lossy(&output.stdout))
+        .map_err(|e| format!("{} ({} ls)", e, package_manager))
+}
+
+/// Extracts the installed package names from the JSON output of `npm ls` or `pnpm ls`.
+///
+/// npm prints the project as an object whose `dependencies` map nests the tree. pnpm prints an
+/// array of projects with `dependencies`, `devDependencies` and `optionalDependencies` maps whose
+/// entries nest the same way. Every package of the tree is collected, however deep.
+///
+/// # Arguments
+///
+/// * `content` - A string slice containing the JSON output.
+///
+/// # Returns
+///
+/// Returns `Ok(HashSet<String>)` with the package names.
+/// Returns `Err(String)` if the output is not valid JSON.
+///
+/// # Examples
+///
+/// ```
+/// let output = r#"{ "dependencies": { "react": { "version": "18.2.0",
+///     "dependencies": { "loose-envify": { "version": "1.4.0" } } } } }"#;
+/// let installed = parse_package_manager_ls(output)?;
+/// assert!(installed.contains("loose-envify"));
+/// ```
+pub fn parse_package_manager_ls(content: &str) -> Result<HashSet<String>, String> {
+    fn collect(node: &Value, packages: &mut HashSet<String>) {
+        for field in ["dependencies", "devDependencies", "optionalDependencies"] {
+            for (name, dependency) in node
+                .get(field)
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+            {
+                packages.insert(name.clone());
+                collect(dependency, packages);
+            }
+        }
+    }
+
+    let tree: Value = serde_json::from_str(content)
+        .map_err(|e| format!("Invalid package manager output: {}", e))?;
+    let mut packages = HashSet::new();
+    match &tree {
+        Value::Array(projects) => projects
+            .iter()
+            .for_each(|project| collect(project, &mut packages)),
+        project => collect(project, &mut packages),
+    }
+    Ok(packages)
+}
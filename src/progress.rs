@@ -0,0 +1,42 @@
+/// Receives progress updates while a project is analyzed.
+///
+/// The command-line tool reports progress with an `indicatif` spinner; library users can pass
+/// [`NoProgress`] or implement this trait to forward the updates elsewhere.
+pub trait Progress {
+    /// Shows what the analysis is currently doing.
+    fn set_message(&self, message: &str);
+
+    /// Records that one more path was visited while scanning.
+    fn inc(&self);
+
+    /// Marks the analysis as complete with a final message.
+    fn finish(&self, message: &str);
+}
+
+/// Progress reporter that ignores every update.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn set_message(&self, _message: &str) {}
+
+    fn inc(&self) {}
+
+    fn finish(&self, _message: &str) {}
+}
+
+#[cfg(feature = "cli")]
+impl Progress for indicatif::ProgressBar {
+    fn set_message(&self, message: &str) {
+        indicatif::ProgressBar::set_message(self, message.to_string());
+    }
+
+    fn inc(&self) {
+        indicatif::ProgressBar::inc(self, 1);
+        self.tick();
+    }
+
+    fn finish(&self, message: &str) {
+        self.finish_with_message(message.to_string());
+    }
+}
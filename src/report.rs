@@ -1,48 +1,532 @@
+use crate::analysis::{Analysis, Timings};
 use crate::config::{EXTENSIONS, IGNORE_FOLDERS, PACKAGE_JSON_PATH};
+use crate::dependency::find_dependency_line;
+use crate::file_scanner::{IgnoreReason, ScanStats};
+use crate::utils::format_size;
+use crate::warnings::Warnings;
 use colored::*;
 use comfy_table::{Cell, Color, Table};
-use std::collections::HashSet;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
 
-/// Prints a formatted dependency usage report to the console.
+/// The output format of the dependency report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable tables and lists.
+    Console,
+    /// Machine-readable JSON printed to stdout.
+    Json,
+    /// GitHub Actions workflow commands (`::error` / `::warning`) for CI annotations.
+    Github,
+    /// JUnit XML, with each unused or missing dependency as a failed test case.
+    Junit,
+    /// A self-contained HTML page with a summary table and collapsible dependency lists.
+    Html,
+    /// TOML tables (`[summary]`, `[[unused]]`, ...) for configuration-file friendly tooling.
+    Toml,
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Console => write!(f, "console"),
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Github => write!(f, "github"),
+            OutputFormat::Junit => write!(f, "junit"),
+            OutputFormat::Html => write!(f, "html"),
+            OutputFormat::Toml => write!(f, "toml"),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "console" => Ok(OutputFormat::Console),
+            "json" => Ok(OutputFormat::Json),
+            "github" => Ok(OutputFormat::Github),
+            "junit" => Ok(OutputFormat::Junit),
+            "html" => Ok(OutputFormat::Html),
+            "toml" => Ok(OutputFormat::Toml),
+            _ => Err(format!("Error: Unknown output format `{}`.", s)),
+        }
+    }
+}
+
+/// The dependency lists shown in the console report, chosen with `--report-used-only` or
+/// `--report-unused-only`. Machine-readable formats always include every list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportSections {
+    /// Both the used and the unused dependencies.
+    #[default]
+    All,
+    /// Only the used dependencies.
+    UsedOnly,
+    /// Only the unused dependencies and devDependencies.
+    UnusedOnly,
+}
+
+impl fmt::Display for ReportSections {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportSections::All => write!(f, "all"),
+            ReportSections::UsedOnly => write!(f, "used-only"),
+            ReportSections::UnusedOnly => write!(f, "unused-only"),
+        }
+    }
+}
+
+impl ReportSections {
+    /// Returns `true` if the used dependencies are listed.
+    pub fn shows_used(self) -> bool {
+        self != ReportSections::UnusedOnly
+    }
+
+    /// Returns `true` if the unused dependencies and devDependencies are listed.
+    pub fn shows_unused(self) -> bool {
+        self != ReportSections::UsedOnly
+    }
+}
+
+/// A row of the console metrics table, selected with `--reporter-columns`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// The path to the project's `package.json`.
+    Project,
+    /// The scanned file extensions.
+    Extensions,
+    /// The folders skipped during the scan.
+    IgnoredFolders,
+    /// The number of scanned files.
+    ExploredFiles,
+    /// The number of files and folders skipped by ignore rules.
+    IgnoredFiles,
+    /// The number of declared dependencies.
+    Total,
+    /// The number of used dependencies.
+    Used,
+    /// The number of unused dependencies.
+    Unused,
+    /// The number of missing dependencies.
+    Missing,
+    /// The bytes read from the scanned files, recorded with `--stats`.
+    BytesScanned,
+    /// The time spent scanning files, recorded with `--profile`.
+    ScanDuration,
+}
+
+impl Metric {
+    /// Every metric, in the order of their names in error messages.
+    pub const ALL: [Metric; 11] = [
+        Metric::Project,
+        Metric::Extensions,
+        Metric::IgnoredFolders,
+        Metric::ExploredFiles,
+        Metric::IgnoredFiles,
+        Metric::Total,
+        Metric::Used,
+        Metric::Unused,
+        Metric::Missing,
+        Metric::BytesScanned,
+        Metric::ScanDuration,
+    ];
+
+    /// The rows of the metrics table when `--reporter-columns` is not given.
+    pub const DEFAULT: [Metric; 9] = [
+        Metric::Project,
+        Metric::Extensions,
+        Metric::IgnoredFolders,
+        Metric::ExploredFiles,
+        Metric::IgnoredFiles,
+        Metric::Total,
+        Metric::Used,
+        Metric::Unused,
+        Metric::Missing,
+    ];
+
+    /// Returns the name of the metric on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Project => "project",
+            Metric::Extensions => "extensions",
+            Metric::IgnoredFolders => "ignored-folders",
+            Metric::ExploredFiles => "explored-files",
+            Metric::IgnoredFiles => "ignored-files",
+            Metric::Total => "total",
+            Metric::Used => "used",
+            Metric::Unused => "unused",
+            Metric::Missing => "missing",
+            Metric::BytesScanned => "bytes-scanned",
+            Metric::ScanDuration => "scan-duration",
+        }
+    }
+
+    /// Returns the label of the metric in the table.
+    pub fn label(self) -> &'static str {
+        match self {
+            Metric::Project => "Project",
+            Metric::Extensions => "Extensions",
+            Metric::IgnoredFolders => "Ignored Folders",
+            Metric::ExploredFiles => "Explored Files",
+            Metric::IgnoredFiles => "Ignored Files",
+            Metric::Total => "Total Dependencies",
+            Metric::Used => "Used Dependencies",
+            Metric::Unused => "Unused Dependencies",
+            Metric::Missing => "Missing Dependencies",
+            Metric::BytesScanned => "Bytes Scanned",
+            Metric::ScanDuration => "Scan Duration",
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Metric::ALL
+            .into_iter()
+            .find(|metric| metric.name() == s.trim())
+            .ok_or_else(|| {
+                let names: Vec<&str> = Metric::ALL.iter().map(|metric| metric.name()).collect();
+                format!(
+                    "Error: Unknown metric `{}`. Expected one of: {}.",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Dependency counts of an analysis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    pub total: usize,
+    pub used: usize,
+    pub unused: usize,
+    pub missing: usize,
+}
+
+impl Summary {
+    /// Computes the dependency counts of an analysis.
+    pub fn from_analysis(analysis: &Analysis) -> Self {
+        Summary {
+            total: analysis.dependencies.len(),
+            used: analysis.used_packages.len(),
+            unused: analysis.unused_dependencies.len(),
+            missing: analysis.missing_dependencies.len(),
+        }
+    }
+}
+
+/// The JSON representation of a full dependency report.
+///
+/// The JSON object contains the `project` path, a `summary` with dependency counts, the sorted
+/// `used`, `unused`, `missing` and `orphaned_types` dependency lists (plus `unused_dev` when
+/// devDependencies are unused, and `case_mismatches` when an import differs only by case from a
+/// declared package), and the `explored_files` and
+/// `ignored_files` paths. With `--audit-scripts`, `lifecycle_scripts` maps unused dependencies to
+/// their install lifecycle scripts. With `--git-age`, `git_ages` maps used dependencies to the days
+/// since they were last referenced in a commit. `package_sizes` maps installed unused dependencies
+/// to their `node_modules` size in bytes. With `--dedupe-check`, `duplicate_versions` maps packages
+/// to the versions they resolve to in the lockfile. With `--bin`, `missing_script_commands` maps
+/// script commands that no installed dependency provides to the scripts running them. With
+/// `--check-entrypoints`, `broken_entry_points` maps `package.json` entry points to their missing
+/// target file. With `--check-duplicates-in-code`, `duplicate_purposes` lists the groups of packages serving the same
+/// purpose that are imported together. `warnings` lists the warnings raised during the
+/// analysis, unless silenced with `--no-warnings`. With `--profile`, `timings` maps each analysis
+/// phase to the milliseconds spent in it. With `--stats`, `stats` holds the scan counters (files per
+/// extension, bytes read, import statements and average matches per file).
+#[derive(Debug, Serialize)]
+pub struct JsonReport<'a> {
+    project: &'a str,
+    summary: Summary,
+    used: Vec<&'a str>,
+    unused: Vec<&'a str>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    unused_dev: &'a [String],
+    missing: Vec<&'a str>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    case_mismatches: &'a BTreeMap<String, String>,
+    orphaned_types: &'a [String],
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    lifecycle_scripts: &'a BTreeMap<String, Vec<String>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    git_ages: &'a BTreeMap<String, u64>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    package_sizes: &'a BTreeMap<String, u64>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    duplicate_versions: &'a BTreeMap<String, Vec<String>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    missing_script_commands: &'a BTreeMap<String, Vec<String>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    broken_entry_points: &'a BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "<[Vec<String>]>::is_empty")]
+    duplicate_purposes: &'a [Vec<String>],
+    #[serde(skip_serializing_if = "Warnings::is_empty")]
+    warnings: &'a Warnings,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timings: Option<&'a Timings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<&'a ScanStats>,
+    explored_files: &'a [String],
+    ignored_files: &'a [String],
+}
+
+impl<'a> JsonReport<'a> {
+    /// Builds the JSON report of a project's analysis, with sorted dependency lists.
+    pub fn new(project: &'a str, analysis: &'a Analysis) -> Self {
+        let mut used: Vec<&str> = analysis.used_packages.iter().map(String::as_str).collect();
+        used.sort();
+        let mut unused: Vec<&str> = analysis
+            .unused_dependencies
+            .iter()
+            .map(String::as_str)
+            .collect();
+        unused.sort();
+
+        JsonReport {
+            project,
+            summary: Summary::from_analysis(analysis),
+            used,
+            unused,
+            unused_dev: &analysis.unused_dev_dependencies,
+            missing: analysis
+                .missing_dependencies
+                .iter()
+                .map(String::as_str)
+                .collect(),
+            case_mismatches: &analysis.case_mismatches,
+            orphaned_types: &analysis.orphaned_types,
+            lifecycle_scripts: &analysis.lifecycle_scripts,
+            git_ages: &analysis.git_ages,
+            package_sizes: &analysis.package_sizes,
+            duplicate_versions: &analysis.duplicate_versions,
+            missing_script_commands: &analysis.missing_script_commands,
+            broken_entry_points: &analysis.broken_entry_points,
+            duplicate_purposes: &analysis.duplicate_purposes,
+            warnings: &analysis.warnings,
+            timings: analysis.timings.as_ref(),
+            stats: analysis.stats.as_ref(),
+            explored_files: &analysis.explored_files,
+            ignored_files: &analysis.ignored_files,
+        }
+    }
+}
+
+/// The JSON representation of a project that could not be analyzed.
+///
+/// The object contains the `project` path, the `error` message and a stable `code` (e.g.,
+/// `manifest_not_found`), so that JSON consumers always receive valid JSON.
+#[derive(Debug, Serialize)]
+pub struct JsonError<'a> {
+    project: &'a str,
+    error: &'a str,
+    code: &'static str,
+}
+
+impl<'a> JsonError<'a> {
+    /// Builds the JSON error of a project, without the `Error: ` prefix of the message.
+    pub fn new(project: &'a str, error: &'a str, code: &'static str) -> Self {
+        JsonError {
+            project,
+            error: error.strip_prefix("Error: ").unwrap_or(error),
+            code,
+        }
+    }
+}
+
+/// An entry of the JSON output: the report of a project, or the error that stopped its analysis.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum JsonEntry<'a> {
+    Report(Box<JsonReport<'a>>),
+    Error(JsonError<'a>),
+}
+
+/// A package of the TOML report, with the files importing it when they are known.
+#[derive(Debug, Serialize)]
+struct TomlPackage<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    files: &'a [String],
+}
+
+/// The TOML representation of a project's analysis, printed with `--format toml`.
+///
+/// Top-level values (`project`, `warnings`) come first, then the `[summary]` counts and one array
+/// of tables per dependency list (`[[used]]`, `[[unused]]`, `[[unused_dev]]`, `[[missing]]`), each
+/// entry holding the package `name` and, for used packages, the `files` importing it. Lists are
+/// sorted, so the output is deterministic; strings are escaped by the `toml` serializer.
+#[derive(Debug, Serialize)]
+pub struct TomlReport<'a> {
+    project: &'a str,
+    #[serde(skip_serializing_if = "Warnings::is_empty")]
+    warnings: &'a Warnings,
+    summary: Summary,
+    used: Vec<TomlPackage<'a>>,
+    unused: Vec<TomlPackage<'a>>,
+    unused_dev: Vec<TomlPackage<'a>>,
+    missing: Vec<TomlPackage<'a>>,
+}
+
+impl<'a> TomlReport<'a> {
+    /// Builds the TOML report of a project's analysis, with sorted dependency lists.
+    pub fn new(project: &'a str, analysis: &'a Analysis) -> Self {
+        let packages = |names: &mut dyn Iterator<Item = &'a String>| {
+            let mut packages: Vec<TomlPackage> = names
+                .map(|name| TomlPackage {
+                    name,
+                    files: analysis
+                        .dependency_files
+                        .get(name)
+                        .map_or(&[][..], Vec::as_slice),
+                })
+                .collect();
+            packages.sort_by_key(|package| package.name);
+            packages
+        };
+
+        TomlReport {
+            project,
+            warnings: &analysis.warnings,
+            summary: Summary::from_analysis(analysis),
+            used: packages(&mut analysis.used_packages.iter()),
+            unused: packages(&mut analysis.unused_dependencies.iter()),
+            unused_dev: packages(&mut analysis.unused_dev_dependencies.iter()),
+            missing: packages(&mut analysis.missing_dependencies.iter()),
+        }
+    }
+}
+
+/// The TOML document of several projects, as a `[[projects]]` array of tables (TOML documents
+/// cannot be arrays).
+#[derive(Debug, Serialize)]
+pub struct TomlProjects<T> {
+    pub projects: Vec<T>,
+}
+
+/// Writes the TOML report of one or more projects.
+///
+/// A single project is written as a [`TomlReport`] document, and nothing is written if it could
+/// not be analyzed; with `multiple`, the reports are written as a `[[projects]]` array of tables,
+/// in the order given.
+///
+/// # Arguments
+///
+/// * `out` - The writer the report is written to.
+/// * `projects` - A slice of `(project, analysis)` pairs, one per analyzed project.
+/// * `multiple` - Whether several projects were analyzed.
+///
+/// # Returns
+///
+/// Returns an `io::Result<()>` indicating whether writing succeeded.
+///
+/// # Examples
+///
+/// ```
+/// let mut file = File::create("cnp.toml")?;
+/// write_toml_report(&mut file, &[(".", &analysis)], false)?;
+/// ```
+pub fn write_toml_report(
+    out: &mut dyn Write,
+    projects: &[(&str, &Analysis)],
+    multiple: bool,
+) -> io::Result<()> {
+    let mut reports: Vec<_> = projects
+        .iter()
+        .map(|(project, analysis)| TomlReport::new(project, analysis))
+        .collect();
+    let toml = if multiple {
+        toml::to_string(&TomlProjects { projects: reports })
+    } else if let Some(report) = reports.pop() {
+        toml::to_string(&report)
+    } else {
+        return Ok(());
+    };
+    let toml = toml.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    write!(out, "{}", toml)
+}
+
+/// The JSON representation of a project's dependency counts, used when several projects are
+/// analyzed at once.
+#[derive(Debug, Serialize)]
+pub struct ProjectSummary<'a> {
+    pub project: &'a str,
+    #[serde(flatten)]
+    pub summary: Summary,
+}
+
+/// Writes a formatted dependency usage report for the console.
 ///
 /// This function generates a tabular report summarizing dependency analysis results, including
-/// project details, file scanning metrics, and dependency usage. It also lists used and unused
-/// dependencies with color-coded formatting. If no unused dependencies are found, a success message
-/// is displayed. A note about potential runtime-required dependencies (e.g., `react-dom`) is included
-/// when unused dependencies are listed.
+/// project details, file scanning metrics, and dependency usage. It also lists used, unused and
+/// missing dependencies with color-coded formatting. If no unused dependencies are found, a success
+/// message is displayed. A note about potential runtime-required dependencies (e.g., `react-dom`) is
+/// included when unused dependencies are listed.
 ///
 /// # Arguments
 ///
-/// * `dependencies` - A reference to a `HashSet<String>` containing all declared dependencies.
-/// * `used_packages` - A reference to a `HashSet<String>` containing dependencies found in use.
-/// * `unused_dependencies` - A slice of `String` containing unused dependency names.
-/// * `explored_files` - A slice of `String` containing paths of explored files.
-/// * `ignored_files` - A slice of `String` containing paths of ignored files.
+/// * `out` - The writer the report is written to (e.g. stdout).
+/// * `project` - A string slice representing the path to the project directory.
+/// * `analysis` - A reference to the `Analysis` holding the declared, used, unused and missing
+///   dependencies, and the explored and ignored files.
+/// * `sections` - Which of the used and unused dependency lists are written.
+/// * `metrics` - The rows of the metrics table, in order (see [`Metric::DEFAULT`]).
 ///
 /// # Output
 ///
-/// Prints to the console:
-/// - A table with metrics (project path, extensions, ignored folders, file counts, dependency counts).
-/// - A sorted list of used dependencies (in green).
-/// - A sorted list of unused dependencies (in red) with a warning about runtime requirements, or a
+/// Writes:
+/// - A table with the selected metrics (by default the project path, extensions, ignored folders,
+///   file counts and dependency counts). Bytes scanned and scan duration show `-` unless recorded
+///   with `--stats` and `--profile`.
+/// - A sorted list of used dependencies (in green), with their age in days when `--git-age` is set.
+/// - A sorted list of unused dependencies (in red) with their `node_modules` size when installed,
+///   and a warning about runtime requirements, or a
 ///   success message if none are found.
+/// - A sorted list of unused devDependencies (in yellow), for information, if any.
+/// - The packages resolved to several lockfile versions (in yellow), with `--dedupe-check`.
+/// - The groups of duplicate-purpose packages imported together (in yellow), with
+///   `--check-duplicates-in-code`.
+/// - The install lifecycle scripts of unused dependencies (in red), if audited and any are found.
+/// - A sorted list of missing dependencies (in yellow), if any.
+/// - The script commands that no installed dependency provides (in yellow), with `--bin`.
+/// - The entry points whose target file is missing (in red), with `--check-entrypoints`.
+/// - A sorted list of orphaned `@types/*` packages (in magenta), if any.
 ///
 /// # Examples
 ///
 /// ```
-/// let dependencies: HashSet<String> = ["lodash", "react"].into_iter().map(String::from).collect();
-/// let used_packages: HashSet<String> = ["lodash"].into_iter().map(String::from).collect();
-/// let unused_dependencies = vec!["react".to_string()];
-/// let explored_files = vec!["src/index.js".to_string()];
-/// let ignored_files = vec!["node_modules/lodash/index.js".to_string()];
+/// let analysis = Analysis {
+///     dependencies: ["lodash", "react"].into_iter().map(String::from).collect(),
+///     used_packages: ["lodash"].into_iter().map(String::from).collect(),
+///     unused_dependencies: vec!["react".to_string()],
+///     unused_dev_dependencies: vec![],
+///     missing_dependencies: vec![],
+///     case_mismatches: BTreeMap::new(),
+///     orphaned_types: vec![],
+///     lifecycle_scripts: BTreeMap::new(),
+///     explored_files: vec!["src/index.js".to_string()],
+///     ignored_files: vec!["node_modules/lodash/index.js".to_string()],
+///     ignore_reasons: BTreeMap::new(),
+///     dependency_files: BTreeMap::new(),
+///     git_ages: BTreeMap::new(),
+///     package_sizes: BTreeMap::new(),
+///     duplicate_versions: BTreeMap::new(),
+///     missing_script_commands: BTreeMap::new(),
+///     broken_entry_points: BTreeMap::new(),
+///     duplicate_purposes: vec![],
+///     warnings: Warnings::default(),
+///     timings: None,
+///     stats: None,
+/// };
 ///
-/// print_dependency_report(
-///     &dependencies,
-///     &used_packages,
-///     &unused_dependencies,
-///     &explored_files,
-///     &ignored_files,
-/// );
+/// write_dependency_report(&mut io::stdout(), ".", &analysis, ReportSections::All, &Metric::DEFAULT)?;
 /// // Prints a table with metrics, followed by:
 /// // Used Dependencies:
 /// // - lodash (in green)
@@ -50,68 +534,969 @@ use std::collections::HashSet;
 /// // Note: Some may be required at runtime (e.g., react-dom).
 /// // - react (in red)
 /// ```
-pub fn print_dependency_report(
-    dependencies: &HashSet<String>,
-    used_packages: &HashSet<String>,
-    unused_dependencies: &[String],
-    explored_files: &[String],
-    ignored_files: &[String],
-) {
+pub fn write_dependency_report(
+    out: &mut dyn Write,
+    project: &str,
+    analysis: &Analysis,
+    sections: ReportSections,
+    metrics: &[Metric],
+) -> io::Result<()> {
+    let Analysis {
+        dependencies,
+        used_packages,
+        unused_dependencies,
+        unused_dev_dependencies,
+        missing_dependencies,
+        case_mismatches: _,
+        orphaned_types,
+        lifecycle_scripts,
+        explored_files,
+        ignored_files,
+        ignore_reasons: _,
+        dependency_files: _,
+        git_ages,
+        package_sizes,
+        duplicate_versions,
+        missing_script_commands,
+        broken_entry_points,
+        duplicate_purposes,
+        warnings: _,
+        timings,
+        stats,
+    } = analysis;
+
     let mut table = Table::new();
     table.set_header(vec!["Metric", "Value"]);
-    table.add_row(vec![Cell::new("Project"), Cell::new(PACKAGE_JSON_PATH)]);
-    table.add_row(vec![
-        Cell::new("Extensions"),
-        Cell::new(EXTENSIONS.join(", ")),
-    ]);
-    table.add_row(vec![
-        Cell::new("Ignored Folders"),
-        Cell::new(IGNORE_FOLDERS.join(", ")),
-    ]);
-    table.add_row(vec![
-        Cell::new("Explored Files"),
-        Cell::new(explored_files.len().to_string()),
-    ]);
-    table.add_row(vec![
-        Cell::new("Ignored Files"),
-        Cell::new(ignored_files.len().to_string()),
-    ]);
-    table.add_row(vec![
-        Cell::new("Total Dependencies"),
-        Cell::new(dependencies.len().to_string()),
-    ]);
-    table.add_row(vec![
-        Cell::new("Used Dependencies"),
-        Cell::new(used_packages.len().to_string()).fg(Color::Green),
-    ]);
-    table.add_row(vec![
-        Cell::new("Unused Dependencies"),
-        Cell::new(unused_dependencies.len().to_string()).fg(Color::Red),
-    ]);
-    println!("\n{}", "Dependency Usage Report".bold().blue());
-    println!("{}", table);
-
-    if !used_packages.is_empty() {
-        println!("\n{}", "Used Dependencies:".green().bold());
+    for metric in metrics {
+        let value = match metric {
+            Metric::Project => Cell::new(Path::new(project).join(PACKAGE_JSON_PATH).display()),
+            Metric::Extensions => Cell::new(EXTENSIONS.join(", ")),
+            Metric::IgnoredFolders => Cell::new(IGNORE_FOLDERS.join(", ")),
+            Metric::ExploredFiles => Cell::new(explored_files.len().to_string()),
+            Metric::IgnoredFiles => Cell::new(ignored_files.len().to_string()),
+            Metric::Total => Cell::new(dependencies.len().to_string()),
+            Metric::Used => Cell::new(used_packages.len().to_string()).fg(Color::Green),
+            Metric::Unused => Cell::new(unused_dependencies.len().to_string()).fg(Color::Red),
+            Metric::Missing => Cell::new(missing_dependencies.len().to_string()).fg(Color::Yellow),
+            Metric::BytesScanned => Cell::new(
+                stats
+                    .as_ref()
+                    .map_or("-".to_string(), |stats| stats.bytes_read.to_string()),
+            ),
+            Metric::ScanDuration => {
+                Cell::new(timings.as_ref().map_or("-".to_string(), |timings| {
+                    format!("{:.1}ms", timings.scan.as_secs_f64() * 1000.0)
+                }))
+            }
+        };
+        table.add_row(vec![Cell::new(metric.label()), value]);
+    }
+    writeln!(out, "\n{}", "Dependency Usage Report".bold().blue())?;
+    writeln!(out, "{}", table)?;
+
+    if sections.shows_used() && !used_packages.is_empty() {
+        writeln!(out, "\n{}", "Used Dependencies:".green().bold())?;
         let mut used = used_packages.iter().collect::<Vec<_>>();
         used.sort();
         for dep in used {
-            println!("- {}", dep.green());
+            match git_ages.get(dep) {
+                Some(days) => writeln!(
+                    out,
+                    "- {} {}",
+                    dep.green(),
+                    format!("(last used {} days ago)", days).dimmed()
+                )?,
+                None => writeln!(out, "- {}", dep.green())?,
+            }
         }
     }
 
-    if !unused_dependencies.is_empty() {
-        println!("\n{}", "Unused Dependencies:".red().bold());
-        println!(
+    if sections.shows_unused() && !unused_dependencies.is_empty() {
+        writeln!(out, "\n{}", "Unused Dependencies:".red().bold())?;
+        writeln!(
+            out,
             "{}",
             "Note: Some may be required at runtime (e.g., react-dom).".yellow()
-        );
+        )?;
         let mut unused = unused_dependencies.to_vec();
         unused.sort();
         for dep in unused {
-            println!("- {}", dep.red());
+            match package_sizes.get(&dep) {
+                Some(size) => writeln!(
+                    out,
+                    "- {} {}",
+                    dep.red(),
+                    format!("({})", format_size(*size)).dimmed()
+                )?,
+                None => writeln!(out, "- {}", dep.red())?,
+            }
+        }
+    } else if sections.shows_unused() {
+        writeln!(out, "\n{}", "No unused dependencies found!".green().bold())?;
+    }
+
+    if sections.shows_unused() && !unused_dev_dependencies.is_empty() {
+        writeln!(out, "\n{}", "Unused Dev Dependencies:".yellow().bold())?;
+        writeln!(
+            out,
+            "{}",
+            "Note: Informational only; tools loaded from config files are not detected.".yellow()
+        )?;
+        for dep in unused_dev_dependencies {
+            writeln!(out, "- {}", dep.yellow())?;
+        }
+    }
+
+    if !lifecycle_scripts.is_empty() {
+        writeln!(
+            out,
+            "\n{}",
+            "Unused Dependencies With Install Scripts:".red().bold()
+        )?;
+        writeln!(
+            out,
+            "{}",
+            "Note: These run code on every install, so they are worth removing first.".yellow()
+        )?;
+        for (dep, scripts) in lifecycle_scripts {
+            writeln!(out, "- {} ({})", dep.red(), scripts.join(", "))?;
+        }
+    }
+
+    if !duplicate_versions.is_empty() {
+        writeln!(out, "\n{}", "Duplicate Packages:".yellow().bold())?;
+        writeln!(
+            out,
+            "{}",
+            "Note: These resolve to several versions in the lockfile; your package manager's dedupe command (e.g., `npm dedupe`) may merge them.".yellow()
+        )?;
+        for (package, versions) in duplicate_versions {
+            writeln!(out, "- {} ({})", package.yellow(), versions.join(", "))?;
+        }
+    }
+
+    if !duplicate_purposes.is_empty() {
+        writeln!(out, "\n{}", "Duplicate-Purpose Packages:".yellow().bold())?;
+        writeln!(
+            out,
+            "{}",
+            "Note: These serve the same purpose; using only one of each group avoids shipping both."
+                .yellow()
+        )?;
+        for group in duplicate_purposes {
+            let members: Vec<String> = group
+                .iter()
+                .map(|package| package.yellow().to_string())
+                .collect();
+            writeln!(out, "- {}", members.join(", "))?;
+        }
+    }
+
+    if !missing_dependencies.is_empty() {
+        writeln!(out, "\n{}", "Missing Dependencies:".yellow().bold())?;
+        writeln!(
+            out,
+            "{}",
+            "Note: These are imported but not declared in package.json.".yellow()
+        )?;
+        for dep in missing_dependencies {
+            writeln!(out, "- {}", dep.yellow())?;
+        }
+    }
+
+    if !missing_script_commands.is_empty() {
+        writeln!(out, "\n{}", "Missing Script Commands:".yellow().bold())?;
+        writeln!(
+            out,
+            "{}",
+            "Note: These run from package.json scripts but no installed dependency provides them."
+                .yellow()
+        )?;
+        for (command, scripts) in missing_script_commands {
+            writeln!(out, "- {} ({})", command.yellow(), scripts.join(", "))?;
+        }
+    }
+
+    if !broken_entry_points.is_empty() {
+        writeln!(out, "\n{}", "Broken Entry Points:".red().bold())?;
+        writeln!(
+            out,
+            "{}",
+            "Note: These package.json entry points name files that do not exist.".red()
+        )?;
+        for (field, target) in broken_entry_points {
+            writeln!(out, "- {} -> {}", field.red(), target)?;
+        }
+    }
+
+    if !orphaned_types.is_empty() {
+        write_orphaned_types(out, orphaned_types)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the warnings raised during an analysis, one per line with a `Warning:` prefix.
+///
+/// # Arguments
+///
+/// * `out` - The writer the warnings are written to (e.g. stderr).
+/// * `warnings` - A reference to the `Warnings` to write.
+///
+/// # Examples
+///
+/// ```
+/// write_warnings(&mut io::stderr(), &analysis.warnings)?;
+/// // Warning: Multiple lockfiles detected (package-lock.json, yarn.lock). Please use only one package manager.
+/// ```
+pub fn write_warnings(out: &mut dyn Write, warnings: &Warnings) -> io::Result<()> {
+    for warning in warnings.iter() {
+        writeln!(out, "{}: {}", "Warning".yellow().bold(), warning)?;
+    }
+    Ok(())
+}
+
+/// Writes the time spent in each phase of an analysis, recorded with `--profile`.
+///
+/// # Arguments
+///
+/// * `out` - The writer the timings are written to (e.g. stdout).
+/// * `timings` - A reference to the recorded `Timings`.
+///
+/// # Examples
+///
+/// ```
+/// write_timings(&mut io::stdout(), &timings)?;
+/// // Timings:
+/// // - manifest: 0.4ms
+/// // - scan: 12.1ms
+/// ```
+pub fn write_timings(out: &mut dyn Write, timings: &Timings) -> io::Result<()> {
+    writeln!(out, "\n{}", "Timings:".blue().bold())?;
+    for (phase, duration) in timings.phases() {
+        writeln!(
+            out,
+            "- {}: {}",
+            phase,
+            format!("{:.1}ms", duration.as_secs_f64() * 1000.0).dimmed()
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the counters of a scan, recorded with `--stats`.
+///
+/// # Arguments
+///
+/// * `out` - The writer the statistics are written to (e.g. stdout).
+/// * `stats` - A reference to the recorded `ScanStats`.
+///
+/// # Examples
+///
+/// ```
+/// write_stats(&mut io::stdout(), &stats)?;
+/// // Scan Statistics:
+/// // - .js files: 12
+/// // - .ts files: 30
+/// // - bytes read: 184320
+/// // - import statements: 96
+/// // - average matches per file: 1.8
+/// ```
+pub fn write_stats(out: &mut dyn Write, stats: &ScanStats) -> io::Result<()> {
+    writeln!(out, "\n{}", "Scan Statistics:".blue().bold())?;
+    for (extension, count) in &stats.files_per_extension {
+        let label = if extension.is_empty() {
+            "files without extension".to_string()
+        } else {
+            format!(".{} files", extension)
+        };
+        writeln!(out, "- {}: {}", label, count.to_string().dimmed())?;
+    }
+    writeln!(
+        out,
+        "- bytes read: {}",
+        stats.bytes_read.to_string().dimmed()
+    )?;
+    writeln!(
+        out,
+        "- import statements: {}",
+        stats.imports.to_string().dimmed()
+    )?;
+    writeln!(
+        out,
+        "- average matches per file: {}",
+        format!("{:.1}", stats.average_matches_per_file).dimmed()
+    )?;
+    Ok(())
+}
+
+/// Writes every ignored file or directory with the rule that skipped it, for the console.
+///
+/// # Arguments
+///
+/// * `out` - The writer the section is written to (e.g. stdout).
+/// * `ignore_reasons` - A reference to the map of ignored paths to their `IgnoreReason`.
+///
+/// # Examples
+///
+/// ```
+/// write_ignored_files(&mut io::stdout(), &analysis.ignore_reasons)?;
+/// // Ignored Files:
+/// // - /project/node_modules/lodash/index.js — inside ignored folder `node_modules`
+/// ```
+pub fn write_ignored_files(
+    out: &mut dyn Write,
+    ignore_reasons: &BTreeMap<String, IgnoreReason>,
+) -> io::Result<()> {
+    if ignore_reasons.is_empty() {
+        return writeln!(out, "\n{}", "No ignored files.".green().bold());
+    }
+
+    writeln!(out, "\n{}", "Ignored Files:".cyan().bold())?;
+    for (path, reason) in ignore_reasons {
+        writeln!(out, "- {} — {}", path, reason.to_string().dimmed())?;
+    }
+
+    Ok(())
+}
+
+/// Writes the list of orphaned `@types/*` packages for the console.
+///
+/// A types package is orphaned when its base package is no longer declared in `package.json`,
+/// which makes it always safe to remove. If the list is empty, a success message is written.
+///
+/// # Arguments
+///
+/// * `out` - The writer the section is written to (e.g. stdout).
+/// * `orphaned_types` - A slice of `String` containing the orphaned `@types/*` package names.
+///
+/// # Examples
+///
+/// ```
+/// write_orphaned_types(&mut io::stdout(), &["@types/lodash".to_string()])?;
+/// // Orphaned @types Packages:
+/// // Note: Their base package is no longer declared, so they are safe to remove.
+/// // - @types/lodash
+/// ```
+pub fn write_orphaned_types(out: &mut dyn Write, orphaned_types: &[String]) -> io::Result<()> {
+    if orphaned_types.is_empty() {
+        return writeln!(
+            out,
+            "\n{}",
+            "No orphaned @types packages found!".green().bold()
+        );
+    }
+
+    writeln!(out, "\n{}", "Orphaned @types Packages:".magenta().bold())?;
+    writeln!(
+        out,
+        "{}",
+        "Note: Their base package is no longer declared, so they are safe to remove.".yellow()
+    )?;
+    for types_package in orphaned_types {
+        writeln!(out, "- {}", types_package.magenta())?;
+    }
+
+    Ok(())
+}
+
+/// Returns the path of a project's `package.json` as shown in reports and annotations.
+///
+/// # Arguments
+///
+/// * `project` - A string slice representing the path to the project directory.
+///
+/// # Returns
+///
+/// Returns a `String` with the manifest path, without a leading `./`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(manifest_display_path("."), "package.json");
+/// assert_eq!(manifest_display_path("apps/web"), "apps/web/package.json");
+/// ```
+pub fn manifest_display_path(project: &str) -> String {
+    let manifest_path = Path::new(project).join(PACKAGE_JSON_PATH);
+    manifest_path
+        .strip_prefix(".")
+        .unwrap_or(&manifest_path)
+        .display()
+        .to_string()
+}
+
+/// Escapes the message of a GitHub Actions workflow command: `%`, `\r` and `\n`.
+fn escape_command_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a property value of a GitHub Actions workflow command, which also ends at `:` and `,`.
+fn escape_command_property(value: &str) -> String {
+    escape_command_data(value)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+/// Writes a single GitHub Actions workflow command.
+///
+/// The message and the file are escaped as workflow commands require, so multi-line messages and
+/// paths containing `,` or `:` stay in a single annotation.
+///
+/// # Arguments
+///
+/// * `out` - The writer the command is written to (e.g. stdout).
+/// * `level` - The annotation level (`error` or `warning`).
+/// * `file` - The path of the annotated file.
+/// * `line` - The 1-based line of the annotation, if known.
+/// * `message` - The annotation message.
+///
+/// # Examples
+///
+/// ```
+/// write_github_command(&mut io::stdout(), "error", "package.json", Some(14), "Unused dependency 'lodash'")?;
+/// // ::error file=package.json,line=14::Unused dependency 'lodash'
+/// ```
+pub fn write_github_command(
+    out: &mut dyn Write,
+    level: &str,
+    file: &str,
+    line: Option<usize>,
+    message: &str,
+) -> io::Result<()> {
+    let file = escape_command_property(file);
+    let message = escape_command_data(message);
+    match line {
+        Some(line) => writeln!(out, "::{} file={},line={}::{}", level, file, line, message),
+        None => writeln!(out, "::{} file={}::{}", level, file, message),
+    }
+}
+
+/// Writes the findings of an analysis as GitHub Actions workflow commands.
+///
+/// Unused dependencies are reported as errors, unused devDependencies as notices and orphaned
+/// `@types/*` packages as warnings, all pointing at the line of `package.json` declaring them.
+/// Missing dependencies and the warnings raised during the analysis are reported as warnings on
+/// `package.json` itself.
+///
+/// # Arguments
+///
+/// * `out` - The writer the commands are written to (e.g. stdout).
+/// * `project` - A string slice representing the path to the project directory.
+/// * `manifest` - A string slice containing the raw `package.json` content, used for line numbers.
+/// * `analysis` - A reference to the `Analysis` holding the findings.
+///
+/// # Examples
+///
+/// ```
+/// let manifest = fs::read_to_string("package.json")?;
+/// write_github_annotations(&mut io::stdout(), ".", &manifest, &analysis)?;
+/// // ::error file=package.json,line=14::Unused dependency 'lodash'
+/// ```
+pub fn write_github_annotations(
+    out: &mut dyn Write,
+    project: &str,
+    manifest: &str,
+    analysis: &Analysis,
+) -> io::Result<()> {
+    let file = manifest_display_path(project);
+
+    let mut unused = analysis.unused_dependencies.to_vec();
+    unused.sort();
+    for dep in &unused {
+        write_github_command(
+            out,
+            "error",
+            &file,
+            find_dependency_line(manifest, dep),
+            &format!("Unused dependency '{}'", dep),
+        )?;
+    }
+
+    for dep in &analysis.unused_dev_dependencies {
+        write_github_command(
+            out,
+            "notice",
+            &file,
+            find_dependency_line(manifest, dep),
+            &format!("Unused devDependency '{}'", dep),
+        )?;
+    }
+
+    for warning in analysis.warnings.iter() {
+        write_github_command(out, "warning", &file, None, warning)?;
+    }
+
+    for dep in &analysis.missing_dependencies {
+        write_github_command(
+            out,
+            "warning",
+            &file,
+            None,
+            &format!("Missing dependency '{}'", dep),
+        )?;
+    }
+
+    for types_package in &analysis.orphaned_types {
+        write_github_command(
+            out,
+            "warning",
+            &file,
+            find_dependency_line(manifest, types_package),
+            &format!("Orphaned types package '{}'", types_package),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// A JUnit test case describing one dependency check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JunitCase {
+    /// The kind of check (e.g., `used`, `unused`, `missing`), rendered as the test class.
+    pub classname: &'static str,
+    /// The package name, rendered as the test name.
+    pub name: String,
+    /// The failure message, or `None` if the check passed.
+    pub failure: Option<String>,
+}
+
+/// Builds the JUnit test cases of a project's analysis.
+///
+/// Used dependencies are passing test cases; unused and missing dependencies are failed ones, so
+/// the suite passes when there are none.
+///
+/// # Arguments
+///
+/// * `analysis` - A reference to the project's `Analysis`.
+///
+/// # Returns
+///
+/// Returns a `Vec<JunitCase>` with the used, unused and missing dependencies, each sorted by name.
+///
+/// # Examples
+///
+/// ```
+/// let cases = junit_cases(&analysis);
+/// let failures = cases.iter().filter(|case| case.failure.is_some()).count();
+/// println!("{} failing dependency checks", failures);
+/// ```
+pub fn junit_cases(analysis: &Analysis) -> Vec<JunitCase> {
+    let mut used: Vec<&String> = analysis.used_packages.iter().collect();
+    used.sort();
+    let mut unused: Vec<&String> = analysis.unused_dependencies.iter().collect();
+    unused.sort();
+
+    let used = used.into_iter().map(|dep| JunitCase {
+        classname: "used",
+        name: dep.clone(),
+        failure: None,
+    });
+    let unused = unused.into_iter().map(|dep| JunitCase {
+        classname: "unused",
+        name: dep.clone(),
+        failure: Some(format!("Unused dependency '{}'", dep)),
+    });
+    let missing = analysis.missing_dependencies.iter().map(|dep| JunitCase {
+        classname: "missing",
+        name: dep.clone(),
+        failure: Some(format!("Missing dependency '{}'", dep)),
+    });
+
+    used.chain(unused).chain(missing).collect()
+}
+
+/// Escapes the characters of a string that are special in double-quoted XML attributes and text.
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Writes a JUnit XML report with one test suite per project.
+///
+/// # Arguments
+///
+/// * `out` - The writer the report is written to.
+/// * `suites` - A slice of `(project, cases)` pairs, one per analyzed project.
+///
+/// # Returns
+///
+/// Returns an `io::Result<()>` indicating whether writing succeeded.
+///
+/// # Examples
+///
+/// ```
+/// write_junit_report(&mut io::stdout(), &[(".", junit_cases(&analysis))])?;
+/// // <?xml version="1.0" encoding="UTF-8"?>
+/// // <testsuites name="cnp" tests="2" failures="1">
+/// //   <testsuite name="." tests="2" failures="1">
+/// //     <testcase classname="used" name="react"/>
+/// //     <testcase classname="unused" name="lodash">
+/// //       <failure message="Unused dependency 'lodash'" type="unused"/>
+/// //     </testcase>
+/// //   </testsuite>
+/// // </testsuites>
+/// ```
+pub fn write_junit_report(
+    out: &mut dyn Write,
+    suites: &[(&str, Vec<JunitCase>)],
+) -> io::Result<()> {
+    let count_failures =
+        |cases: &[JunitCase]| cases.iter().filter(|case| case.failure.is_some()).count();
+    let tests: usize = suites.iter().map(|(_, cases)| cases.len()).sum();
+    let failures: usize = suites.iter().map(|(_, cases)| count_failures(cases)).sum();
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<testsuites name="cnp" tests="{}" failures="{}">"#,
+        tests, failures
+    )?;
+    for (project, cases) in suites {
+        writeln!(
+            out,
+            r#"  <testsuite name="{}" tests="{}" failures="{}">"#,
+            escape_xml(project),
+            cases.len(),
+            count_failures(cases)
+        )?;
+        for case in cases {
+            let attributes = format!(
+                r#"classname="{}" name="{}""#,
+                case.classname,
+                escape_xml(&case.name)
+            );
+            match &case.failure {
+                Some(message) => {
+                    writeln!(out, "    <testcase {}>", attributes)?;
+                    writeln!(
+                        out,
+                        r#"      <failure message="{}" type="{}"/>"#,
+                        escape_xml(message),
+                        case.classname
+                    )?;
+                    writeln!(out, "    </testcase>")?;
+                }
+                None => writeln!(out, "    <testcase {}/>", attributes)?,
+            }
         }
+        writeln!(out, "  </testsuite>")?;
+    }
+    writeln!(out, "</testsuites>")
+}
+
+/// Inline styles of the HTML report, which loads no external assets.
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2rem auto;max-width:60rem;color:#1f2328}\
+table{border-collapse:collapse;width:100%}\
+th,td{border:1px solid #d0d7de;padding:.4rem .8rem;text-align:left}\
+th{background:#f6f8fa}\
+td.unused,td.missing{color:#cf222e;font-weight:600}\
+summary{cursor:pointer;font-weight:600;margin:.5rem 0}\
+ul{columns:2;margin:.25rem 0 1rem}\
+p.empty{color:#656d76;margin:.25rem 0 1rem}";
+
+/// Escapes the characters of a string that are special in HTML text and attributes.
+fn escape_html(value: &str) -> String {
+    escape_xml(value).replace('\'', "&#39;")
+}
+
+/// Writes one collapsible list of packages of the HTML report.
+fn write_html_list<'a>(
+    out: &mut dyn Write,
+    title: &str,
+    packages: impl IntoIterator<Item = &'a String>,
+    open: bool,
+) -> io::Result<()> {
+    let mut packages: Vec<&String> = packages.into_iter().collect();
+    packages.sort();
+
+    writeln!(
+        out,
+        "<details{}><summary>{} ({})</summary>",
+        if open { " open" } else { "" },
+        title,
+        packages.len()
+    )?;
+    if packages.is_empty() {
+        writeln!(out, r#"<p class="empty">None</p>"#)?;
     } else {
-        println!("\n{}", "No unused dependencies found!".green().bold());
+        writeln!(out, "<ul>")?;
+        for package in packages {
+            writeln!(out, "<li><code>{}</code></li>", escape_html(package))?;
+        }
+        writeln!(out, "</ul>")?;
+    }
+    writeln!(out, "</details>")
+}
+
+/// Writes a self-contained HTML report of one or more projects.
+///
+/// The page starts with a table of the dependency counts of every project, followed by the
+/// collapsible unused, missing, unused dev and used dependency lists of each project. Styles are
+/// inlined and no external asset is loaded, so the file can be shared on its own. Project paths
+/// and package names are HTML-escaped.
+///
+/// # Arguments
+///
+/// * `out` - The writer the report is written to.
+/// * `projects` - A slice of `(project, analysis)` pairs, one per analyzed project.
+///
+/// # Returns
+///
+/// Returns an `io::Result<()>` indicating whether writing succeeded.
+///
+/// # Examples
+///
+/// ```
+/// let mut file = File::create("cnp.html")?;
+/// write_html_report(&mut file, &[(".", &analysis)])?;
+/// ```
+pub fn write_html_report(out: &mut dyn Write, projects: &[(&str, &Analysis)]) -> io::Result<()> {
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, r#"<html lang="en">"#)?;
+    writeln!(
+        out,
+        r#"<head><meta charset="utf-8"><title>cnp report</title>"#
+    )?;
+    writeln!(out, "<style>{}</style></head>", HTML_STYLE)?;
+    writeln!(out, "<body>\n<h1>Dependency report</h1>")?;
+
+    writeln!(
+        out,
+        "<table>\n<thead><tr><th>Project</th><th>Total</th><th>Used</th><th>Unused</th><th>Missing</th></tr></thead>\n<tbody>"
+    )?;
+    // Non-zero unused and missing counts are highlighted
+    let count_cell = |class: &str, count: usize| match count {
+        0 => "<td>0</td>".to_string(),
+        count => format!(r#"<td class="{}">{}</td>"#, class, count),
+    };
+    for (project, analysis) in projects {
+        let summary = Summary::from_analysis(analysis);
+        writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{}</td>{}{}</tr>",
+            escape_html(project),
+            summary.total,
+            summary.used,
+            count_cell("unused", summary.unused),
+            count_cell("missing", summary.missing)
+        )?;
+    }
+    writeln!(out, "</tbody>\n</table>")?;
+
+    for (project, analysis) in projects {
+        writeln!(out, "<section>\n<h2>{}</h2>", escape_html(project))?;
+        write_html_list(
+            out,
+            "Unused dependencies",
+            &analysis.unused_dependencies,
+            true,
+        )?;
+        write_html_list(
+            out,
+            "Missing dependencies",
+            &analysis.missing_dependencies,
+            true,
+        )?;
+        write_html_list(
+            out,
+            "Unused devDependencies",
+            &analysis.unused_dev_dependencies,
+            false,
+        )?;
+        write_html_list(out, "Used dependencies", &analysis.used_packages, false)?;
+        writeln!(out, "</section>")?;
+    }
+
+    writeln!(out, "</body>\n</html>")
+}
+
+/// Formats the dependency counts of an analysis as a single line.
+///
+/// In console, GitHub, JUnit and HTML formats, the counts are rendered as `key=value` pairs. In
+/// JSON format, they are rendered as a compact JSON object with the `total`, `used`, `unused` and
+/// `missing` keys, and in TOML format as one `key = value` line per count.
+///
+/// # Arguments
+///
+/// * `summary` - The `Summary` holding the dependency counts.
+/// * `format` - The `OutputFormat` to render the counts in.
+///
+/// # Returns
+///
+/// Returns a `String` containing the formatted counts, without a trailing newline.
+///
+/// # Examples
+///
+/// ```
+/// let summary = Summary { total: 3, used: 2, unused: 1, missing: 0 };
+/// assert_eq!(format_count(&summary, OutputFormat::Console), "total=3 used=2 unused=1 missing=0");
+/// ```
+pub fn format_count(summary: &Summary, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Console | OutputFormat::Github | OutputFormat::Junit | OutputFormat::Html => {
+            format!(
+                "total={} used={} unused={} missing={}",
+                summary.total, summary.used, summary.unused, summary.missing
+            )
+        }
+        OutputFormat::Json => serde_json::to_string(summary).expect("Failed to serialize summary"),
+        OutputFormat::Toml => toml::to_string(summary)
+            .expect("Failed to serialize summary")
+            .trim_end()
+            .to_string(),
+    }
+}
+
+/// Counts of a run rendered by `--summary-line-format`, summed over every analyzed project.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunCounts {
+    pub projects: usize,
+    pub total: usize,
+    pub used: usize,
+    pub unused: usize,
+    pub unused_dev: usize,
+    pub missing: usize,
+    pub files: usize,
+    pub ignored: usize,
+}
+
+impl RunCounts {
+    /// The variables a summary line template can reference.
+    pub const VARIABLES: [&'static str; 8] = [
+        "projects",
+        "total",
+        "used",
+        "unused",
+        "unused_dev",
+        "missing",
+        "files",
+        "ignored",
+    ];
+
+    /// Adds the counts of a project's analysis.
+    pub fn add(&mut self, analysis: &Analysis) {
+        self.projects += 1;
+        self.total += analysis.dependencies.len();
+        self.used += analysis.used_packages.len();
+        self.unused += analysis.unused_dependencies.len();
+        self.unused_dev += analysis.unused_dev_dependencies.len();
+        self.missing += analysis.missing_dependencies.len();
+        self.files += analysis.explored_files.len();
+        self.ignored += analysis.ignored_files.len();
+    }
+
+    /// Returns the count named by a template variable, or `None` for an unknown name.
+    pub fn get(&self, name: &str) -> Option<usize> {
+        match name {
+            "projects" => Some(self.projects),
+            "total" => Some(self.total),
+            "used" => Some(self.used),
+            "unused" => Some(self.unused),
+            "unused_dev" => Some(self.unused_dev),
+            "missing" => Some(self.missing),
+            "files" => Some(self.files),
+            "ignored" => Some(self.ignored),
+            _ => None,
+        }
+    }
+}
+
+/// A piece of a summary line template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Text(String),
+    Variable(String),
+}
+
+/// A one-line summary template given with `--summary-line-format`, such as
+/// `unused={{unused}} missing={{missing}} in {{files}} files`.
+///
+/// Variables are written `{{name}}` and validated when the template is parsed, against
+/// [`RunCounts::VARIABLES`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SummaryTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+impl SummaryTemplate {
+    /// Renders the template against the counts of a run.
+    ///
+    /// # Arguments
+    ///
+    /// * `counts` - The `RunCounts` of every analyzed project.
+    ///
+    /// # Returns
+    ///
+    /// Returns the rendered line, without a trailing newline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let template: SummaryTemplate = "unused={{unused}} in {{files}} files".parse()?;
+    /// let counts = RunCounts { unused: 2, files: 14, ..RunCounts::default() };
+    /// assert_eq!(template.render(&counts), "unused=2 in 14 files");
+    /// ```
+    pub fn render(&self, counts: &RunCounts) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Text(text) => text.clone(),
+                TemplatePart::Variable(name) => counts
+                    .get(name)
+                    .expect("Variables are validated when parsing")
+                    .to_string(),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for SummaryTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in &self.parts {
+            match part {
+                TemplatePart::Text(text) => write!(f, "{}", text)?,
+                TemplatePart::Variable(name) => write!(f, "{{{{{}}}}}", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SummaryTemplate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut rest = s;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                parts.push(TemplatePart::Text(rest[..start].to_string()));
+            }
+            let Some(end) = rest[start + 2..].find("}}") else {
+                return Err(format!(
+                    "Error: Unclosed `{{{{` in summary line format `{}`.",
+                    s
+                ));
+            };
+            let name = rest[start + 2..start + 2 + end].trim();
+            if !RunCounts::VARIABLES.contains(&name) {
+                return Err(format!(
+                    "Error: Unknown summary variable `{}`. Expected one of: {}.",
+                    name,
+                    RunCounts::VARIABLES.join(", ")
+                ));
+            }
+            parts.push(TemplatePart::Variable(name.to_string()));
+            rest = &rest[start + 2 + end + 2..];
+        }
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Text(rest.to_string()));
+        }
+        Ok(SummaryTemplate { parts })
     }
 }
@@ -0,0 +1,276 @@
+use crate::config::NODE_BUILTINS;
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Resolves a bare import specifier to the name of the package it refers to.
+///
+/// Relative (`./x`, `../x`) and absolute (`/x`) paths, URL-like specifiers (`node:fs`,
+/// `https://...`) and subpath imports (`#internal`) do not refer to a package. Bare Node.js
+/// built-in names (`fs`, `buffer`) are kept, since npm packages such as `buffer` and `events`
+/// shadow their built-in when declared: callers check [`is_node_builtin`] before reporting an
+/// undeclared one. Subpath specifiers are reduced to their package name
+/// (`lodash/merge` -> `lodash`, `@scope/pkg/sub` -> `@scope/pkg`). Bundler suffixes are stripped
+/// first: webpack inline loaders resolve to the resource after the last `!`
+/// (`worker-loader!pkg` -> `pkg`) and `?query` strings are dropped (`pkg/logo.svg?url` -> `pkg`).
+///
+/// # Arguments
+///
+/// * `spec` - The import or require specifier, without quotes.
+///
+/// # Returns
+///
+/// Returns `Some(String)` with the canonical package name, or `None` if the specifier does not
+/// refer to a package.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(specifier_to_package("lodash/merge"), Some("lodash".to_string()));
+/// assert_eq!(specifier_to_package("@vercel/analytics/react"), Some("@vercel/analytics".to_string()));
+/// assert_eq!(specifier_to_package("./utils"), None);
+/// assert_eq!(specifier_to_package("node:path"), None);
+/// assert_eq!(specifier_to_package("fs/promises"), Some("fs".to_string()));
+/// assert_eq!(specifier_to_package("some-pkg/logo.svg?url"), Some("some-pkg".to_string()));
+/// ```
+pub fn specifier_to_package(spec: &str) -> Option<String> {
+    let spec = spec.rsplit('!').next().unwrap_or(spec);
+    let spec = spec.split('?').next().unwrap_or(spec).trim();
+    if spec.is_empty()
+        || spec.starts_with('.')
+        || spec.starts_with('/')
+        || spec.starts_with('#')
+        || spec.contains(':')
+    {
+        return None;
+    }
+
+    let mut segments = spec.split('/');
+    let package_name = match segments.next()? {
+        "@" => return None,
+        scope if scope.starts_with('@') => {
+            let name = segments.next().filter(|name| !name.is_empty())?;
+            format!("{}/{}", scope, name)
+        }
+        name => name.to_string(),
+    };
+
+    Some(package_name)
+}
+
+/// Checks whether a package name is also the name of a Node.js built-in module.
+///
+/// # Arguments
+///
+/// * `package` - The package name resolved from an import specifier (see [`specifier_to_package`]).
+///
+/// # Returns
+///
+/// Returns `true` if the name is one of `NODE_BUILTINS`, `false` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// assert!(is_node_builtin("fs"));
+/// assert!(is_node_builtin("buffer"));
+/// assert!(!is_node_builtin("lodash"));
+/// ```
+pub fn is_node_builtin(package: &str) -> bool {
+    NODE_BUILTINS.contains(&package)
+}
+
+/// Resolves the contents of a template literal specifier to the name of the package it refers to.
+///
+/// Fully static templates (`` `lodash` ``) resolve like plain specifiers. Templates with
+/// interpolations only resolve when their static prefix ends a complete package name with a `/`
+/// (`` `lodash/${name}` `` -> `lodash`); fully dynamic templates (`` `${name}` ``) and prefixes that
+/// could still be extended (`` `lodash${suffix}` ``) cannot be resolved.
+///
+/// # Arguments
+///
+/// * `template` - The template literal contents, without backticks.
+///
+/// # Returns
+///
+/// Returns `Some(String)` with the canonical package name, or `None` if the template cannot be
+/// resolved to a package.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(template_to_package("date-fns/locale/${lang}"), Some("date-fns".to_string()));
+/// assert_eq!(template_to_package("${name}"), None);
+/// assert_eq!(template_to_package("lodash${suffix}"), None);
+/// ```
+pub fn template_to_package(template: &str) -> Option<String> {
+    match template.split_once("${") {
+        None => specifier_to_package(template),
+        Some((prefix, _)) => {
+            let (package_prefix, _) = prefix.rsplit_once('/')?;
+            specifier_to_package(package_prefix)
+        }
+    }
+}
+
+/// Resolves a string concatenation specifier (`'lodash' + '/merge'`) to the name of the package it
+/// refers to.
+///
+/// The leading string literals are joined; the first operand that is not a string literal is
+/// treated like a template interpolation, so the result resolves like [`template_to_package`]:
+/// `'lodash' + '/merge'` and `'lodash/' + name` resolve to `lodash`, while `'lodash' + suffix`
+/// could still name another package and `name + '/merge'` is fully dynamic.
+///
+/// # Arguments
+///
+/// * `expression` - The concatenation expression, such as the argument of a `require` call.
+///
+/// # Returns
+///
+/// Returns `Some(String)` with the canonical package name, or `None` if the expression cannot be
+/// resolved to a package.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(concatenation_to_package("'lodash' + '/merge'"), Some("lodash".to_string()));
+/// assert_eq!(concatenation_to_package("'lodash/' + name"), Some("lodash".to_string()));
+/// assert_eq!(concatenation_to_package("'lodash' + suffix"), None);
+/// ```
+pub fn concatenation_to_package(expression: &str) -> Option<String> {
+    let mut template = String::new();
+    for operand in expression.split('+').map(str::trim) {
+        let literal = ['\'', '"'].iter().find_map(|quote| {
+            operand
+                .strip_prefix(*quote)
+                .and_then(|operand| operand.strip_suffix(*quote))
+        });
+        match literal {
+            Some(literal) => template.push_str(literal),
+            None => {
+                template.push_str("${}");
+                break;
+            }
+        }
+    }
+    template_to_package(&template)
+}
+
+/// Finds the declared package an imported package name matches only when case is ignored.
+///
+/// Such imports resolve on case-insensitive filesystems (macOS, Windows) but fail on Linux, so they
+/// usually work locally and break in CI.
+///
+/// # Arguments
+///
+/// * `package` - The package name resolved from an import specifier (see [`specifier_to_package`]).
+/// * `declared` - A reference to the set of packages declared in `package.json`.
+///
+/// # Returns
+///
+/// Returns `Some(&str)` with the declared package name if `package` is not declared as-is but
+/// differs from a declared package only by case, `None` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// let declared: HashSet<String> = ["react".to_string()].into_iter().collect();
+/// assert_eq!(case_mismatched_package("React", &declared), Some("react"));
+/// assert_eq!(case_mismatched_package("react", &declared), None);
+/// ```
+pub fn case_mismatched_package<'a>(
+    package: &str,
+    declared: &'a HashSet<String>,
+) -> Option<&'a str> {
+    if declared.contains(package) {
+        return None;
+    }
+    declared
+        .iter()
+        .find(|name| name.eq_ignore_ascii_case(package))
+        .map(String::as_str)
+}
+
+/// Resolves import specifiers with an external resolver script.
+///
+/// The script runs in the project directory and receives one specifier per line on stdin. It must
+/// print one line per specifier, in the same order: the name of the package the specifier refers
+/// to, `-` if it is not a package (e.g., a bundler alias to local code), or an empty line to leave
+/// it unresolved. Missing trailing lines also leave their specifiers unresolved.
+///
+/// # Arguments
+///
+/// * `script` - The path to the resolver script, relative to the working directory.
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `specifiers` - The specifiers to resolve.
+///
+/// # Returns
+///
+/// Returns `Ok(BTreeMap<String, Option<String>>)` mapping every resolved specifier to its package
+/// name, or to `None` if it is not a package.
+/// Returns `Err(String)` with an error message if the script cannot be run, exits with a failure
+/// status, or prints more lines than it received.
+///
+/// # Examples
+///
+/// ```
+/// let resolved = run_resolver_script("./resolve.sh", ".", &["@app/ui".to_string()])?;
+/// println!("{:?}", resolved.get("@app/ui"));
+/// ```
+pub fn run_resolver_script(
+    script: &str,
+    dir_path: &str,
+    specifiers: &[String],
+) -> Result<BTreeMap<String, Option<String>>, String> {
+    let program = Path::new(script)
+        .canonicalize()
+        .map_err(|e| format!("Failed to find resolver script '{}': {}", script, e))?;
+    let mut child = Command::new(&program)
+        .current_dir(dir_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run resolver script '{}': {}", script, e))?;
+
+    // Write from another thread so a script answering line by line cannot fill its stdout pipe
+    // while cnp is still blocked writing to its stdin
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input: String = specifiers
+        .iter()
+        .map(|spec| format!("{}\n", spec))
+        .collect();
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run resolver script '{}': {}", script, e))?;
+    // A script that exits without reading its whole input is not an error
+    let _ = writer.join();
+
+    if !output.status.success() {
+        return Err(format!(
+            "Resolver script '{}' failed: {}",
+            script,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().map(str::trim).collect();
+    if lines.len() > specifiers.len() {
+        return Err(format!(
+            "Resolver script '{}' printed {} lines for {} specifiers",
+            script,
+            lines.len(),
+            specifiers.len()
+        ));
+    }
+
+    Ok(specifiers
+        .iter()
+        .zip(lines)
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(spec, line)| (spec.clone(), (line != "-").then(|| line.to_string())))
+        .collect())
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A package tarball extracted into a temporary directory, which is removed when dropped.
+#[derive(Debug)]
+pub struct ExtractedTarball {
+    /// The temporary directory the tarball was extracted into.
+    dir: PathBuf,
+    /// The package directory: the single top-level folder of the archive (`package/` for
+    /// `npm pack` tarballs), or `dir` itself.
+    root: PathBuf,
+}
+
+impl ExtractedTarball {
+    /// Returns the extracted package directory, holding its `package.json`.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl Drop for ExtractedTarball {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Extracts a package tarball (e.g., from `npm pack`) into a new temporary directory.
+///
+/// The archive is extracted with the system `tar` command, which detects the compression (`.tgz`
+/// and plain `.tar` are both accepted) and refuses entries escaping the target directory. When the
+/// archive holds a single top-level folder, as `npm pack` tarballs do with `package/`, that folder
+/// is the package directory.
+///
+/// # Arguments
+///
+/// * `path` - A string slice representing the path to the tarball.
+///
+/// # Returns
+///
+/// Returns `Ok(ExtractedTarball)` whose temporary directory is removed when it is dropped.
+/// Returns `Err(String)` if the tarball does not exist, or `tar` is missing or fails.
+///
+/// # Examples
+///
+/// ```
+/// let tarball = extract_tarball("my-package-1.0.0.tgz")?;
+/// let options = AnalyzeOptions::new(tarball.root().to_string_lossy());
+/// let analysis = analyze(&options, &NoProgress)?;
+/// ```
+pub fn extract_tarball(path: &str) -> Result<ExtractedTarball, String> {
+    if !Path::new(path).is_file() {
+        return Err(format!("Error: Tarball `{}` not found.", path));
+    }
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos());
+    let dir = std::env::temp_dir().join(format!("cnp-tarball-{}-{}", std::process::id(), nanos));
+    fs::create_dir_all(&dir).map_err(|e| {
+        format!(
+            "Error: Failed to create a directory to extract `{}`: {}",
+            path, e
+        )
+    })?;
+    // Removes the directory on every early return below
+    let mut tarball = ExtractedTarball {
+        root: dir.clone(),
+        dir,
+    };
+
+    let output = Command::new("tar")
+        .arg("-xf")
+        .arg(path)
+        .arg("-C")
+        .arg(&tarball.dir)
+        .output()
+        .map_err(|e| format!("Error: Failed to run tar to extract `{}`: {}", path, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Error: Failed to extract `{}`: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let entries: Vec<PathBuf> = fs::read_dir(&tarball.dir)
+        .map_err(|e| format!("Error: Failed to read the extracted `{}`: {}", path, e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    if let [entry] = entries.as_slice()
+        && entry.is_dir()
+    {
+        tarball.root = entry.clone();
+    }
+    Ok(tarball)
+}
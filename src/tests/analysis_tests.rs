@@ -0,0 +1,1107 @@
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{Analysis, AnalyzeOptions, FailPolicy, analyze};
+    use crate::dependency::read_dependency_list;
+    use crate::file_scanner::IgnoreReason;
+    use crate::progress::NoProgress;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Copies the JavaScript/TypeScript fixture project into a temporary directory.
+    fn copy_fixture_project() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        for file in [
+            "package.json",
+            "index.js",
+            "aliased.js",
+            "utils.ts",
+            "unused.ts",
+        ] {
+            fs::copy(
+                format!("test_fixtures/{}", file),
+                temp_dir.path().join(file),
+            )
+            .unwrap_or_else(|_| panic!("Failed to copy {} to temporary directory", file));
+        }
+        temp_dir
+    }
+
+    #[test]
+    fn test_analyze_fixture_project() {
+        let temp_dir = copy_fixture_project();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert_eq!(analysis.dependencies.len(), 3);
+        assert_eq!(analysis.used_packages.len(), 3);
+        assert!(analysis.unused_dependencies.is_empty());
+        assert!(analysis.missing_dependencies.is_empty());
+        assert_eq!(analysis.explored_files.len(), 4);
+    }
+
+    #[test]
+    fn test_analyze_with_builder_options() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "react": "^18.0.0", "vue": "^3.0.0" },
+                 "peerDependencies": { "lodash": "^4.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("App.vue"),
+            "import { ref } from 'vue';",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "import React from 'react';",
+        )
+        .unwrap();
+        fs::create_dir_all(temp_dir.path().join("generated")).unwrap();
+        fs::write(
+            temp_dir.path().join("generated/lib.vue"),
+            "import merge from 'lodash/merge';",
+        )
+        .unwrap();
+
+        let options = AnalyzeOptions::new(temp_dir.path().to_str().unwrap())
+            .extensions(["vue"])
+            .ignore_folders(["generated"])
+            .dependency_fields(["dependencies", "peerDependencies"])
+            .max_depth(Some(4));
+        let analysis = analyze(&options, &NoProgress).unwrap();
+
+        assert_eq!(analysis.dependencies.len(), 3);
+        assert!(analysis.used_packages.contains("vue"));
+        assert!(!analysis.used_packages.contains("react"));
+        assert!(!analysis.used_packages.contains("lodash"));
+        assert_eq!(analysis.explored_files.len(), 1);
+        assert_eq!(analysis.ignored_files.len(), 1);
+    }
+
+    #[test]
+    fn test_analyze_skips_vendored_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "react": "^18.0.0", "jquery": "^3.0.0", "moment": "^2.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "import React from 'react';",
+        )
+        .unwrap();
+        fs::create_dir_all(temp_dir.path().join("vendor")).unwrap();
+        fs::write(
+            temp_dir.path().join("vendor/plugin.js"),
+            "import $ from 'jquery';\nimport axios from 'axios';",
+        )
+        .unwrap();
+        fs::create_dir_all(temp_dir.path().join("libs/legacy")).unwrap();
+        fs::write(
+            temp_dir.path().join("libs/legacy/dates.js"),
+            "const moment = require('moment');",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("cnp.toml"),
+            "vendored = [\"vendor\", \"libs/legacy\"]",
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert_eq!(analysis.used_packages.len(), 1);
+        assert!(analysis.used_packages.contains("react"));
+        assert!(analysis.missing_dependencies.is_empty());
+        assert_eq!(analysis.explored_files.len(), 1);
+        assert!(
+            analysis
+                .ignore_reasons
+                .values()
+                .any(|reason| *reason == IgnoreReason::Vendored("libs/legacy".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_analyze_scans_extra_extensions_from_config() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "dayjs": "^1.0.0", "marked": "^12.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("page.ejs"),
+            "<% const dayjs = require('dayjs'); %>\n<p><%= dayjs().format() %></p>",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("post.hbs"),
+            "{{!-- import 'marked' --}}\n<article>{{body}}</article>",
+        )
+        .unwrap();
+        let options = AnalyzeOptions::new(temp_dir.path().to_str().unwrap());
+
+        let analysis = analyze(&options, &NoProgress).unwrap();
+        assert!(analysis.used_packages.is_empty());
+
+        fs::write(
+            temp_dir.path().join("cnp.toml"),
+            "extra_extensions = [\"ejs\", \".hbs\"]",
+        )
+        .unwrap();
+        let analysis = analyze(&options, &NoProgress).unwrap();
+        assert!(analysis.used_packages.contains("dayjs"));
+        assert!(analysis.used_packages.contains("marked"));
+        assert_eq!(analysis.explored_files.len(), 2);
+    }
+
+    #[test]
+    fn test_analyze_warns_about_malformed_dependency_fields() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": ["react"], "devDependencies": "eslint" }"#,
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert!(analysis.dependencies.is_empty());
+        let warnings: Vec<&str> = analysis.warnings.iter().collect();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("`dependencies` in package.json is an array"));
+        assert!(warnings[1].starts_with("`devDependencies` in package.json is a string"));
+    }
+
+    #[test]
+    fn test_analyze_reports_missing_dependencies() {
+        let temp_dir = copy_fixture_project();
+        fs::write(
+            temp_dir.path().join("extra.js"),
+            "import axios from 'axios';\nimport fs from 'fs';\nimport path from 'node:path';\nimport local from './local';",
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert_eq!(analysis.missing_dependencies, vec!["axios".to_string()]);
+    }
+
+    #[test]
+    fn test_analyze_subpackage_imports_root_hoisted_dependency() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("package.json"),
+            r#"{ "workspaces": ["packages/*"], "devDependencies": { "lodash": "^4.17.21" } }"#,
+        )
+        .unwrap();
+        let package = root.join("packages").join("web");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(
+            package.join("package.json"),
+            r#"{ "name": "web", "dependencies": { "react": "^18.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            package.join("index.js"),
+            "import React from 'react';\nimport debounce from 'lodash/debounce';\nimport axios from 'axios';",
+        )
+        .unwrap();
+
+        let analysis =
+            analyze(&AnalyzeOptions::new(package.to_str().unwrap()), &NoProgress).unwrap();
+        assert_eq!(analysis.missing_dependencies, vec!["axios".to_string()]);
+
+        // Outside of the workspaces, the root manifest is not consulted
+        fs::write(
+            root.join("package.json"),
+            r#"{ "devDependencies": { "lodash": "^4.17.21" } }"#,
+        )
+        .unwrap();
+        let analysis =
+            analyze(&AnalyzeOptions::new(package.to_str().unwrap()), &NoProgress).unwrap();
+        assert_eq!(
+            analysis.missing_dependencies,
+            vec!["axios".to_string(), "lodash".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_analyze_counts_declared_packages_named_like_builtins() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "buffer": "^6.0.3", "events": "^3.3.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "import { Buffer } from 'buffer';\nimport fs from 'fs';\nimport path from 'node:path';",
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap())
+                .dependency_list(Some(vec!["buffer".to_string(), "events".to_string()])),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert!(analysis.used_packages.contains("buffer"));
+        assert_eq!(analysis.unused_dependencies, vec!["events".to_string()]);
+        // Undeclared built-ins are never missing
+        assert!(analysis.missing_dependencies.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_reports_case_mismatched_imports() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "react": "^18.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "import React from 'React';\nimport { useState } from 'react';",
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert_eq!(
+            analysis.case_mismatches,
+            [("React".to_string(), "react".to_string())]
+                .into_iter()
+                .collect()
+        );
+        assert!(analysis.missing_dependencies.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_missing_package_json() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_analyze_ignores_tsconfig_path_aliases() {
+        let temp_dir = copy_fixture_project();
+        fs::write(
+            temp_dir.path().join("tsconfig.json"),
+            "{\n  // aliases\n  \"compilerOptions\": { \"paths\": { \"@/*\": [\"./src/*\"], }, },\n}",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("alias.js"),
+            "import Button from '@/components/Button';",
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert!(analysis.missing_dependencies.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_lenient_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest = "{\n  // comment\n  \"dependencies\": { \"react\": \"^18.0.0\", },\n}";
+        fs::write(temp_dir.path().join("package.json"), manifest).unwrap();
+
+        // Strict parsing is the default
+        assert!(
+            analyze(
+                &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+                &NoProgress
+            )
+            .is_err()
+        );
+
+        fs::write(temp_dir.path().join("cnp.toml"), "strict_manifest = false").unwrap();
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+        assert!(analysis.dependencies.contains("react"));
+    }
+
+    #[test]
+    fn test_analyze_reads_json5_manifest_without_package_json() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json5"),
+            "{\n  // JSON5 manifest\n  \"dependencies\": {\n    \"react\": \"^18.0.0\",\n    /* unused */ \"lodash\": \"^4.0.0\",\n  },\n}",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "import React from 'react';",
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert_eq!(analysis.dependencies.len(), 2);
+        assert!(analysis.used_packages.contains("react"));
+    }
+
+    #[test]
+    fn test_analyze_without_tsc_matches_typescript_imports() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::copy(
+            "test_fixtures/package.json",
+            temp_dir.path().join("package.json"),
+        )
+        .unwrap();
+        fs::copy("test_fixtures/utils.ts", temp_dir.path().join("utils.ts")).unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert!(analysis.used_packages.contains("react"));
+        assert_eq!(analysis.used_packages.len(), 1);
+    }
+
+    #[test]
+    fn test_analyze_never_reports_runtime_required_as_unused() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "dotenv": "^16.0.0", "react": "^18.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "console.log('no imports');",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("cnp.toml"),
+            "runtime_required = [\"dotenv\"]",
+        )
+        .unwrap();
+
+        // A dependency list skips the lockfile and manifest, which would keep every dependency
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap())
+                .dependency_list(Some(vec!["dotenv".to_string(), "react".to_string()])),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert!(!analysis.used_packages.contains("dotenv"));
+        assert_eq!(analysis.unused_dependencies, vec!["react".to_string()]);
+    }
+
+    #[test]
+    fn test_analyze_ignore_dev_reports_unused_production_dependencies() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "lodash": "^4.17.21", "react": "^18.0.0", "loose-envify": "^1.4.0" },
+                 "devDependencies": { "jest": "^29.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("package-lock.json"),
+            r#"{ "packages": {
+                "": { "dependencies": { "lodash": "^4.17.21", "react": "^18.0.0", "loose-envify": "^1.4.0" } },
+                "node_modules/lodash": { "version": "4.17.21" },
+                "node_modules/react": { "version": "18.2.0", "dependencies": { "loose-envify": "^1.1.0" } },
+                "node_modules/loose-envify": { "version": "1.4.0" },
+                "node_modules/jest": { "version": "29.7.0", "dev": true, "dependencies": { "react": "*" } }
+            } }"#,
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("index.js"), "import _ from 'lodash';").unwrap();
+        let dir = temp_dir.path().to_str().unwrap();
+
+        // Every declared dependency is required by default
+        let analysis = analyze(&AnalyzeOptions::new(dir), &NoProgress).unwrap();
+        assert!(analysis.unused_dependencies.is_empty());
+
+        // Only production packages keep a dependency: `react` is needed by `jest` alone
+        let analysis = analyze(&AnalyzeOptions::new(dir).ignore_dev(true), &NoProgress).unwrap();
+        assert_eq!(analysis.unused_dependencies, vec!["react".to_string()]);
+    }
+
+    #[test]
+    fn test_analyze_manifest_only_skips_scan() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "lodash": "^4.17.21", "left-pad": "^1.3.0", "@myorg/ui": "workspace:*" },
+                 "devDependencies": { "jest": "^29.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("yarn.lock"),
+            "lodash@^4.17.21:\n  version \"4.17.21\"\n\njest@^29.0.0:\n  version \"29.7.0\"\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("index.js"), "import 'react';").unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()).manifest_only(true),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert!(analysis.explored_files.is_empty());
+        assert_eq!(analysis.dependencies.len(), 3);
+        assert!(analysis.unused_dependencies.is_empty());
+        assert!(analysis.unused_dev_dependencies.is_empty());
+        assert!(analysis.missing_dependencies.is_empty());
+        let warnings: Vec<&str> = analysis.warnings.iter().collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "'left-pad' is declared in package.json but missing from yarn.lock. Run your package manager's install to update it."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze_keeps_workspace_packages() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "@myorg/ui": "workspace:*", "react": "^18.0.0" },
+                 "devDependencies": { "@myorg/eslint-config": "workspace:*", "jest": "^29.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "console.log('no imports');",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("cnp.toml"),
+            "workspace_packages = [\"@myorg/*\"]",
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+        assert!(analysis.unused_dependencies.is_empty());
+        assert_eq!(analysis.unused_dev_dependencies, vec!["jest".to_string()]);
+
+        // Without a manifest, no package is required unless it is workspace-internal
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap())
+                .dependency_list(Some(vec!["@myorg/ui".to_string(), "react".to_string()])),
+            &NoProgress,
+        )
+        .unwrap();
+        assert_eq!(analysis.unused_dependencies, vec!["react".to_string()]);
+    }
+
+    #[test]
+    fn test_analyze_marks_script_preloaded_packages_as_used() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "scripts": { "start": "node -r dotenv/config index.js" },
+                 "dependencies": { "dotenv": "^16.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "console.log(process.env.PORT);",
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert!(analysis.used_packages.contains("dotenv"));
+    }
+
+    #[test]
+    fn test_analyze_keeps_types_node_in_typescript_project() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "@types/node": "^20.0.0", "@types/lodash": "^4.0.0" },
+                 "devDependencies": { "@types/node": "^20.0.0", "left-pad": "^1.3.0" } }"#,
+        )
+        .unwrap();
+        // Node.js globals are typed by `@types/node` without any import of it
+        fs::write(
+            temp_dir.path().join("index.ts"),
+            "export const root: string = process.cwd() + __dirname;",
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()).dependency_list(Some(vec![
+                "@types/node".to_string(),
+                "@types/lodash".to_string(),
+            ])),
+            &NoProgress,
+        )
+        .unwrap();
+        assert_eq!(
+            analysis.unused_dependencies,
+            vec!["@types/lodash".to_string()]
+        );
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+        assert_eq!(
+            analysis.unused_dev_dependencies,
+            vec!["left-pad".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_analyze_reports_types_node_when_overridden() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "devDependencies": { "@types/node": "^20.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("tsconfig.json"), "{}").unwrap();
+        fs::write(
+            temp_dir.path().join("index.ts"),
+            "export const answer = 42;",
+        )
+        .unwrap();
+        let dir = temp_dir.path().to_str().unwrap();
+
+        // The command-line override
+        let analysis = analyze(
+            &AnalyzeOptions::new(dir)
+                .dependency_list(Some(vec!["@types/node".to_string()]))
+                .report_types_node(true),
+            &NoProgress,
+        )
+        .unwrap();
+        assert_eq!(
+            analysis.unused_dependencies,
+            vec!["@types/node".to_string()]
+        );
+
+        // The `cnp.toml` override
+        fs::write(temp_dir.path().join("cnp.toml"), "keep_types_node = false").unwrap();
+        let analysis = analyze(&AnalyzeOptions::new(dir), &NoProgress).unwrap();
+        assert_eq!(
+            analysis.unused_dev_dependencies,
+            vec!["@types/node".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_analyze_reports_unused_dev_dependencies() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "scripts": { "lint": "eslint .", "format": "biome format", "build": "tsc && vite build" },
+                 "dependencies": { "react": "^18.0.0" },
+                 "devDependencies": {
+                     "eslint": "^9.0.0",
+                     "@biomejs/biome": "^1.0.0",
+                     "typescript": "^5.0.0",
+                     "@acme/build": "^1.0.0",
+                     "vitest": "^2.0.0",
+                     "@types/react": "^18.0.0",
+                     "@types/node": "^20.0.0",
+                     "left-pad": "^1.3.0"
+                 } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.test.js"),
+            "import React from 'react';\nimport { test } from 'vitest';",
+        )
+        .unwrap();
+        // Commands named differently from their package are found through the installed `bin`
+        for (package, bin) in [
+            ("@biomejs/biome", r#"{ "biome": "bin/biome" }"#),
+            (
+                "typescript",
+                r#"{ "tsc": "bin/tsc", "tsserver": "bin/tsserver" }"#,
+            ),
+        ] {
+            let package_dir = temp_dir.path().join("node_modules").join(package);
+            fs::create_dir_all(&package_dir).unwrap();
+            fs::write(
+                package_dir.join("package.json"),
+                format!(r#"{{ "name": "{}", "bin": {} }}"#, package, bin),
+            )
+            .unwrap();
+        }
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        // The `build` script word does not name `@acme/build`
+        assert_eq!(
+            analysis.unused_dev_dependencies,
+            vec!["@acme/build".to_string(), "left-pad".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_analyze_fail_on_production_policy() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "lodash": "^4.17.21", "react": "^18.0.0" },
+                 "devDependencies": { "left-pad": "^1.3.0" } }"#,
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("index.js"), "import _ from 'lodash';").unwrap();
+        let options = AnalyzeOptions::new(temp_dir.path().to_str().unwrap()).ignore_dev(true);
+
+        let analysis = analyze(&options, &NoProgress).unwrap();
+        assert_eq!(analysis.unused_dependencies, vec!["react".to_string()]);
+        assert!(FailPolicy::Production.fails(&analysis));
+
+        // An unused devDependency is still reported, but only fails the `all` policy
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "import _ from 'lodash';\nimport React from 'react';",
+        )
+        .unwrap();
+        let analysis = analyze(&options, &NoProgress).unwrap();
+        assert_eq!(
+            analysis.unused_dev_dependencies,
+            vec!["left-pad".to_string()]
+        );
+        assert!(!FailPolicy::Production.fails(&analysis));
+        assert!(FailPolicy::All.fails(&analysis));
+    }
+
+    #[test]
+    fn test_analyze_jest_mocked_dev_dependencies_are_used() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "devDependencies": { "axios": "^1.0.0", "left-pad": "^1.3.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("api.test.js"),
+            "jest.mock('axios');\ntest('fetches', () => {});",
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert_eq!(
+            analysis.unused_dev_dependencies,
+            vec!["left-pad".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_analyze_self_referencing_exports_subpaths_are_not_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "name": "@acme/ui",
+                 "exports": { ".": "./index.js", "./internal": "./src/internal.js" },
+                 "dependencies": { "react": "^18.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "import React from 'react';\nimport { helper } from '@acme/ui/internal';\nexport * from '@acme/ui';",
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert!(analysis.missing_dependencies.is_empty());
+        assert_eq!(analysis.dependencies.len(), 1);
+        assert!(!analysis.used_packages.contains("@acme/ui"));
+    }
+
+    #[test]
+    fn test_fail_policy() {
+        let dev_only = Analysis {
+            unused_dev_dependencies: vec!["left-pad".to_string()],
+            ..Analysis::default()
+        };
+        let production = Analysis {
+            unused_dependencies: vec!["lodash".to_string()],
+            ..Analysis::default()
+        };
+
+        assert!(FailPolicy::All.fails(&dev_only));
+        assert!(!FailPolicy::Production.fails(&dev_only));
+        assert!(FailPolicy::All.fails(&production));
+        assert!(FailPolicy::Production.fails(&production));
+        assert!(!FailPolicy::All.fails(&Analysis::default()));
+        assert_eq!("production".parse(), Ok(FailPolicy::Production));
+        assert!("dev".parse::<FailPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_analyze_tooling_config_dirs_use_dev_dependencies() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "devDependencies": {
+                     "@storybook/react-vite": "^8.0.0",
+                     "@storybook/addon-essentials": "^8.0.0",
+                     "lint-staged": "^15.0.0",
+                     "left-pad": "^1.3.0"
+                 } }"#,
+        )
+        .unwrap();
+        fs::create_dir_all(temp_dir.path().join(".storybook")).unwrap();
+        fs::write(
+            temp_dir.path().join(".storybook/main.ts"),
+            r#"import type { StorybookConfig } from "@storybook/react-vite";
+const config: StorybookConfig = { addons: ["@storybook/addon-essentials"] };
+export default config;"#,
+        )
+        .unwrap();
+        fs::create_dir_all(temp_dir.path().join(".husky")).unwrap();
+        fs::write(
+            temp_dir.path().join(".husky/pre-commit"),
+            "npx lint-staged\n",
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert_eq!(
+            analysis.unused_dev_dependencies,
+            vec!["left-pad".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_analyze_with_dependency_list() {
+        let temp_dir = TempDir::new().unwrap();
+        // An invalid manifest is never read
+        fs::write(temp_dir.path().join("package.json"), "{ not json").unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "import React from 'react';\nimport merge from 'lodash/merge';\nimport axios from 'axios';",
+        )
+        .unwrap();
+        let list = temp_dir.path().join("deps.txt");
+        fs::write(&list, "# runtime\nreact\nlodash\n\nleft-pad # unused\n").unwrap();
+
+        let dependencies = read_dependency_list(list.to_str().unwrap()).unwrap();
+        let options = AnalyzeOptions::new(temp_dir.path().to_str().unwrap())
+            .dependency_list(Some(dependencies));
+        let analysis = analyze(&options, &NoProgress).unwrap();
+
+        assert_eq!(analysis.dependencies.len(), 3);
+        assert_eq!(
+            analysis.used_packages,
+            ["react".to_string(), "lodash".to_string()]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(analysis.unused_dependencies, vec!["left-pad".to_string()]);
+        assert_eq!(analysis.missing_dependencies, vec!["axios".to_string()]);
+    }
+
+    #[test]
+    fn test_analyze_framework_companions() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": {
+                     "next": "^14.0.0",
+                     "react": "^18.0.0",
+                     "react-dom": "^18.0.0",
+                     "sharp": "^0.33.0",
+                     "@vercel/og": "^0.6.0"
+                 },
+                 "devDependencies": { "eslint-config-next": "^14.0.0", "left-pad": "^1.3.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("cnp.toml"),
+            "[framework_companions]\nnext = [\"@vercel/og\"]\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("layout.js"),
+            "import { Inter } from 'next/font/google';\nexport const inter = Inter({ subsets: ['latin'] });",
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert_eq!(analysis.used_packages, analysis.dependencies);
+        assert_eq!(
+            analysis.unused_dev_dependencies,
+            vec!["left-pad".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_analyze_empty_scan_warns_instead_of_reporting_unused() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "react": "^18.0.0", "zod": "^3.0.0" },
+                 "devDependencies": { "eslint": "^9.0.0" } }"#,
+        )
+        .unwrap();
+        // Every source file is ignored
+        fs::create_dir(temp_dir.path().join("dist")).unwrap();
+        fs::write(temp_dir.path().join("dist/index.js"), "import 'react';").unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert!(analysis.is_empty_scan());
+        assert!(analysis.unused_dependencies.is_empty());
+        assert!(analysis.unused_dev_dependencies.is_empty());
+        assert_eq!(analysis.warnings.len(), 1);
+        assert!(
+            analysis
+                .warnings
+                .iter()
+                .any(|warning| warning.contains("No source files were scanned"))
+        );
+    }
+
+    #[test]
+    fn test_analyze_codegen_config_uses_dev_dependencies() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "devDependencies": {
+                     "@graphql-codegen/cli": "^5.0.0",
+                     "@graphql-codegen/typescript": "^4.0.0",
+                     "@graphql-codegen/typescript-operations": "^4.0.0",
+                     "@graphql-codegen/client-preset": "^4.0.0",
+                     "@graphql-codegen/typescript-resolvers": "^4.0.0"
+                 } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("codegen.ts"),
+            r#"import type { CodegenConfig } from "@graphql-codegen/cli";
+const config: CodegenConfig = {
+  generates: {
+    "src/gql/": { preset: "client" },
+    "src/types.ts": { plugins: ["@graphql-codegen/typescript", "typescript-operations"] },
+  },
+};
+export default config;"#,
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert_eq!(
+            analysis.unused_dev_dependencies,
+            vec!["@graphql-codegen/typescript-resolvers".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_analyze_package_json_babel_config_uses_dev_dependencies() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "devDependencies": {
+                     "@babel/core": "^7.0.0",
+                     "@babel/preset-typescript": "^7.0.0",
+                     "babel-preset-unused": "^1.0.0"
+                 },
+                 "babel": { "presets": ["@babel/preset-typescript"] } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "const babel = require('@babel/core');",
+        )
+        .unwrap();
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert_eq!(
+            analysis.unused_dev_dependencies,
+            vec!["babel-preset-unused".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_analyze_cnpignore_paths_section() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "react": "^18.0.0", "zod": "^3.0.0", "lodash": "^4.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join(".cnpignore"),
+            "[packages]\nlodash\n\n[paths]\nsrc/generated\n",
+        )
+        .unwrap();
+        fs::create_dir_all(temp_dir.path().join("src/generated")).unwrap();
+        fs::write(
+            temp_dir.path().join("src/index.js"),
+            "import React from 'react';",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("src/generated/schema.js"),
+            "import { z } from 'zod';",
+        )
+        .unwrap();
+
+        let analysis =
+            analyze(
+                &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()).dependency_list(Some(
+                    vec!["react".to_string(), "zod".to_string(), "lodash".to_string()],
+                )),
+                &NoProgress,
+            )
+            .unwrap();
+
+        assert_eq!(analysis.unused_dependencies, vec!["zod".to_string()]);
+        let generated = analysis
+            .ignore_reasons
+            .iter()
+            .find(|(path, _)| path.contains("generated"))
+            .map(|(_, reason)| reason.clone());
+        assert_eq!(
+            generated,
+            Some(IgnoreReason::Path("src/generated".to_string()))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_analyze_with_resolver_script() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "@acme/design-system": "^1.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "import { Button } from 'ds/button';\nimport config from 'app-config';\nimport x from 'left-pad';",
+        )
+        .unwrap();
+        let script = temp_dir.path().join("resolve.sh");
+        fs::write(
+            &script,
+            r#"#!/bin/sh
+while read spec; do
+  echo "$spec" >> received.txt
+  case "$spec" in
+    ds/*) echo "@acme/design-system" ;;
+    app-config) echo "-" ;;
+    *) echo "" ;;
+  esac
+done
+"#,
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let options = AnalyzeOptions::new(temp_dir.path().to_str().unwrap())
+            .resolver_script(Some(script.to_str().unwrap().to_string()));
+        let analysis = analyze(&options, &NoProgress).unwrap();
+
+        // The script gets the specifiers as written, not their package names
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("received.txt")).unwrap(),
+            "app-config\nds/button\nleft-pad\n"
+        );
+        assert!(analysis.used_packages.contains("@acme/design-system"));
+        assert_eq!(analysis.missing_dependencies, vec!["left-pad".to_string()]);
+        assert!(analysis.warnings.is_empty());
+    }
+}
@@ -0,0 +1,814 @@
+#[cfg(test)]
+mod tests {
+    use crate::cli::{
+        Options, build_command, diff_exit_code, parse_report_file, resolve_project_path, run,
+        use_tsc,
+    };
+    use crate::report::OutputFormat;
+    use crate::utils::ColorChoice;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    /// Creates a project directory with the given `package.json` dependencies and source file.
+    fn create_project(dependencies: &str, source: &str) -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            format!(
+                r#"{{ "name": "project", "dependencies": {{ {} }} }}"#,
+                dependencies
+            ),
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("index.js"), source).unwrap();
+        temp_dir
+    }
+
+    /// Parses the arguments and runs the tool, returning the exit code and the captured output.
+    fn run_with_args(args: &[&str]) -> (i32, String) {
+        let (code, output, _) = run_with_streams(args);
+        (code, output)
+    }
+
+    /// Parses the arguments and runs the tool, returning the exit code and the captured output and
+    /// summary streams.
+    fn run_with_streams(args: &[&str]) -> (i32, String, String) {
+        let matches = build_command()
+            .try_get_matches_from(std::iter::once("cnp").chain(args.iter().copied()))
+            .unwrap();
+        let options = Options::from_matches(&matches);
+        let mut out = Vec::new();
+        let mut summary_out = Vec::new();
+        let code = run(&options, &mut out, &mut summary_out);
+        (
+            code,
+            String::from_utf8(out).unwrap(),
+            String::from_utf8(summary_out).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_options_default_to_current_directory() {
+        let matches = build_command().try_get_matches_from(["cnp"]).unwrap();
+        let options = Options::from_matches(&matches);
+
+        assert_eq!(options.paths, vec![".".to_string()]);
+    }
+
+    #[test]
+    fn test_run_multiple_projects_count() {
+        let app = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let api = create_project(
+            r#""express": "^4.0.0", "lodash": "^4.0.0""#,
+            "const express = require('express');",
+        );
+        let app_path = app.path().to_str().unwrap();
+        let api_path = api.path().to_str().unwrap();
+
+        let (code, output) = run_with_args(&[app_path, api_path, "--count"]);
+
+        assert_eq!(code, 0);
+        assert!(output.contains(&format!("{}: total=1 used=1", app_path)));
+        assert!(output.contains(&format!("{}: total=2 used=1", api_path)));
+    }
+
+    #[test]
+    fn test_run_multiple_projects_console_sections() {
+        let app = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let api = create_project(r#""express": "^4.0.0""#, "import express from 'express';");
+        let app_path = app.path().to_str().unwrap();
+        let api_path = api.path().to_str().unwrap();
+
+        let (code, output) = run_with_args(&[app_path, api_path, "--dry-run"]);
+
+        assert_eq!(code, 0);
+        let app_section = output.find(&format!("Project: {}", app_path)).unwrap();
+        let api_section = output.find(&format!("Project: {}", api_path)).unwrap();
+        assert!(app_section < api_section);
+        assert!(output.contains("react"));
+        assert!(output.contains("express"));
+    }
+
+    #[test]
+    fn test_run_multiple_projects_json() {
+        let app = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let api = create_project(r#""express": "^4.0.0""#, "import express from 'express';");
+        let app_path = app.path().to_str().unwrap();
+        let api_path = api.path().to_str().unwrap();
+
+        let (code, output) = run_with_args(&[app_path, api_path, "--format", "json"]);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(code, 0);
+        assert_eq!(json[0]["project"], app_path);
+        assert_eq!(json[0]["used"], serde_json::json!(["react"]));
+        assert_eq!(json[1]["project"], api_path);
+        assert_eq!(json[1]["used"], serde_json::json!(["express"]));
+    }
+
+    #[test]
+    fn test_run_aggregates_exit_code_on_failure() {
+        let app = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let empty = TempDir::new().unwrap();
+
+        let (code, output) = run_with_args(&[
+            app.path().to_str().unwrap(),
+            empty.path().to_str().unwrap(),
+            "--count",
+        ]);
+
+        // The valid project is still reported, but the run fails overall
+        assert_eq!(code, 1);
+        assert!(output.contains("total=1 used=1"));
+    }
+
+    #[test]
+    fn test_run_json_error_object_when_manifest_is_missing() {
+        let empty = TempDir::new().unwrap();
+
+        let (code, output) = run_with_args(&[empty.path().to_str().unwrap(), "--format", "json"]);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(code, 1);
+        assert_eq!(json["code"], "manifest_not_found");
+        assert_eq!(json["error"], "`package.json` not found.");
+        assert_eq!(json["project"], empty.path().to_str().unwrap());
+    }
+
+    #[test]
+    fn test_run_json_error_objects_keep_project_order() {
+        let app = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let invalid = TempDir::new().unwrap();
+        fs::write(invalid.path().join("package.json"), "{ \"name\": ").unwrap();
+
+        let (code, output) = run_with_args(&[
+            invalid.path().to_str().unwrap(),
+            "./does-not-exist",
+            app.path().to_str().unwrap(),
+            "--format",
+            "json",
+        ]);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(code, 1);
+        assert_eq!(json[0]["code"], "manifest_invalid");
+        assert_eq!(json[1]["code"], "not_a_directory");
+        assert_eq!(json[2]["used"], serde_json::json!(["react"]));
+    }
+
+    #[test]
+    fn test_resolve_project_path_rejects_non_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("package.json");
+        fs::write(&file_path, "{}").unwrap();
+
+        assert!(resolve_project_path(temp_dir.path().to_str().unwrap()).is_ok());
+        assert!(resolve_project_path(file_path.to_str().unwrap()).is_err());
+        assert!(resolve_project_path("/nonexistent/project").is_err());
+    }
+
+    #[test]
+    fn test_run_types_only() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "react": "^18.0.0" },
+                 "devDependencies": { "@types/lodash": "^4.0.0", "@types/react": "^18.0.0" } }"#,
+        )
+        .unwrap();
+        let path = temp_dir.path().to_str().unwrap();
+
+        let (code, output) = run_with_args(&[path, "--types-only"]);
+        assert_eq!(code, 0);
+        assert!(output.contains("Orphaned @types Packages:"));
+        assert!(output.contains("@types/lodash"));
+        assert!(!output.contains("@types/react"));
+
+        let (code, output) = run_with_args(&[path, "--types-only", "--format", "json"]);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(code, 0);
+        assert_eq!(json["orphaned_types"], serde_json::json!(["@types/lodash"]));
+    }
+
+    #[test]
+    fn test_run_no_tsc_detects_typescript_imports() {
+        let temp_dir = create_project(r#""zod": "^3.0.0""#, "");
+        fs::write(
+            temp_dir.path().join("schema.ts"),
+            "import { z } from 'zod';\nexport const schema = z.string();",
+        )
+        .unwrap();
+        let path = temp_dir.path().to_str().unwrap();
+
+        let (code, output) = run_with_args(&[path, "--no-tsc", "--format", "json"]);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(code, 0);
+        assert_eq!(json["used"], serde_json::json!(["zod"]));
+    }
+
+    #[test]
+    fn test_options_color() {
+        let color = |args: &[&str]| {
+            let matches = build_command()
+                .try_get_matches_from(std::iter::once("cnp").chain(args.iter().copied()))
+                .unwrap();
+            Options::from_matches(&matches).color
+        };
+
+        assert_eq!(color(&[]), ColorChoice::Auto);
+        assert_eq!(color(&["--color", "always"]), ColorChoice::Always);
+        assert_eq!(color(&["--color", "never"]), ColorChoice::Never);
+        assert!(
+            build_command()
+                .try_get_matches_from(["cnp", "--color", "sometimes"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_options_no_tsc() {
+        let matches = build_command()
+            .try_get_matches_from(["cnp", "--no-tsc"])
+            .unwrap();
+
+        assert!(Options::from_matches(&matches).no_tsc);
+    }
+
+    #[test]
+    fn test_run_summary_json_stderr() {
+        let temp_dir = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let path = temp_dir.path().to_str().unwrap();
+
+        let (code, output, summary) = run_with_streams(&[path, "--summary-json-stderr"]);
+        let json: serde_json::Value = serde_json::from_str(&summary).unwrap();
+
+        assert_eq!(code, 0);
+        assert!(output.contains("Dependency Usage Report"));
+        assert_eq!(json["summary"]["total"], 1);
+        assert_eq!(json["used"], serde_json::json!(["react"]));
+    }
+
+    #[test]
+    fn test_run_without_summary_json_stderr_leaves_summary_empty() {
+        let temp_dir = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let path = temp_dir.path().to_str().unwrap();
+
+        let (_, output, summary) = run_with_streams(&[path, "--format", "json"]);
+
+        assert!(serde_json::from_str::<serde_json::Value>(&output).is_ok());
+        assert!(summary.is_empty());
+    }
+
+    #[test]
+    fn test_run_explain_ignored() {
+        let temp_dir = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let module_dir = temp_dir.path().join("node_modules").join("react");
+        fs::create_dir_all(&module_dir).unwrap();
+        fs::write(module_dir.join("index.js"), "module.exports = {};").unwrap();
+        let path = temp_dir.path().to_str().unwrap();
+
+        let (code, output) = run_with_args(&[path, "--explain-ignored"]);
+
+        assert_eq!(code, 0);
+        assert!(output.contains("Ignored Files:"));
+        assert!(output.contains("node_modules/react/index.js — "));
+        assert!(output.contains("inside ignored folder `node_modules`"));
+    }
+
+    #[test]
+    fn test_run_fail_on_production_ignores_unused_dev_dependencies() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "react": "^18.0.0" },
+                 "devDependencies": { "left-pad": "^1.3.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "import React from 'react';",
+        )
+        .unwrap();
+        let path = temp_dir.path().to_str().unwrap();
+
+        let (production_code, output) =
+            run_with_args(&[path, "--format", "json", "--fail-on", "production"]);
+        let (all_code, _) = run_with_args(&[path, "--format", "json", "--fail-on", "all"]);
+        let (default_code, _) = run_with_args(&[path, "--format", "json"]);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(production_code, 0);
+        assert_eq!(json["unused_dev"], serde_json::json!(["left-pad"]));
+        assert_eq!(all_code, 1);
+        assert_eq!(default_code, 0);
+    }
+
+    #[test]
+    fn test_run_summary_line_format() {
+        let project = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let path = project.path().to_str().unwrap();
+        let template = "unused={{unused}} missing={{missing}} in {{files}} files";
+
+        let (code, output) = run_with_args(&[path, "--no-tsc", "--summary-line-format", template]);
+        assert_eq!(code, 0);
+        assert!(output.ends_with("unused=0 missing=0 in 1 files\n"));
+
+        // Machine-readable output stays parseable
+        let (_, output, summary) = run_with_streams(&[
+            path,
+            "--no-tsc",
+            "--format",
+            "json",
+            "--summary-line-format",
+            template,
+        ]);
+        assert!(serde_json::from_str::<serde_json::Value>(&output).is_ok());
+        assert_eq!(summary, "unused=0 missing=0 in 1 files\n");
+
+        let error = build_command()
+            .try_get_matches_from(["cnp", "--summary-line-format", "{{unsued}}"])
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Unknown summary variable `unsued`")
+        );
+    }
+
+    #[test]
+    fn test_run_diff_exit_code() {
+        let project = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let path = project.path().to_str().unwrap();
+        let run_with_state = |dependencies: &str, source: &str| {
+            let list = project.path().join("deps.txt");
+            fs::write(&list, dependencies).unwrap();
+            fs::write(project.path().join("index.js"), source).unwrap();
+            run_with_args(&[
+                path,
+                "--no-tsc",
+                "--format",
+                "json",
+                "--dependencies-from",
+                list.to_str().unwrap(),
+                "--diff-exit-code",
+            ])
+            .0
+        };
+
+        assert_eq!(run_with_state("react", "import React from 'react';"), 0);
+        assert_eq!(
+            run_with_state("react\nlodash", "import React from 'react';"),
+            3
+        );
+        assert_eq!(
+            run_with_state(
+                "react",
+                "import React from 'react';\nimport axios from 'axios';"
+            ),
+            4
+        );
+        assert_eq!(
+            run_with_state(
+                "react\nlodash",
+                "import React from 'react';\nimport axios from 'axios';"
+            ),
+            5
+        );
+        let (code, _) = run_with_args(&["/nonexistent/project", "--diff-exit-code"]);
+        assert_eq!(code, 1);
+        // Without the flag, findings never change the exit code
+        assert_eq!(run_with_args(&[path, "--no-tsc", "--format", "json"]).0, 0);
+    }
+
+    #[test]
+    fn test_run_tarball() {
+        let fixture = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let tarball = fixture.path().join("project.tgz");
+        let status = std::process::Command::new("tar")
+            .arg("-czf")
+            .arg(&tarball)
+            .arg("-C")
+            .arg(fixture.path())
+            .args(["package.json", "index.js"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let (code, output) = run_with_args(&[
+            "--tarball",
+            tarball.to_str().unwrap(),
+            "--no-tsc",
+            "--format",
+            "json",
+        ]);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(code, 0);
+        assert_eq!(json["used"], serde_json::json!(["react"]));
+        // The extracted project is removed after the run
+        assert!(!Path::new(json["project"].as_str().unwrap()).exists());
+
+        let (code, _) = run_with_args(&["--tarball", "missing.tgz"]);
+        assert_eq!(code, 1);
+    }
+
+    #[test]
+    fn test_diff_exit_code() {
+        assert_eq!(diff_exit_code(false, false), 0);
+        assert_eq!(diff_exit_code(true, false), 3);
+        assert_eq!(diff_exit_code(false, true), 4);
+        assert_eq!(diff_exit_code(true, true), 5);
+        assert!(
+            build_command()
+                .try_get_matches_from(["cnp", "--diff-exit-code", "--fail-on", "all"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_run_fail_empty_when_no_file_is_scanned() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "react": "^18.0.0" } }"#,
+        )
+        .unwrap();
+        let path = temp_dir.path().to_str().unwrap();
+
+        let (default_code, output) = run_with_args(&[path, "--format", "json"]);
+        let (fail_empty_code, _) = run_with_args(&[path, "--format", "json", "--fail-empty"]);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(default_code, 0);
+        assert_eq!(json["unused"], serde_json::json!([]));
+        assert_eq!(fail_empty_code, 1);
+    }
+
+    #[test]
+    fn test_run_json_output_is_deterministic() {
+        let temp_dir = TempDir::new().unwrap();
+        for file in [
+            "package.json",
+            "index.js",
+            "aliased.js",
+            "utils.ts",
+            "unused.ts",
+        ] {
+            fs::copy(
+                format!("test_fixtures/{}", file),
+                temp_dir.path().join(file),
+            )
+            .unwrap();
+        }
+        fs::create_dir_all(temp_dir.path().join("node_modules/lodash")).unwrap();
+        fs::write(temp_dir.path().join("node_modules/lodash/index.js"), "").unwrap();
+        let path = temp_dir.path().to_str().unwrap();
+
+        let (_, first) = run_with_args(&[path, "--format", "json", "--no-tsc"]);
+        let (_, second) = run_with_args(&[path, "--format", "json", "--no-tsc"]);
+
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_run_compare_with_previous_report() {
+        let project = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let path = project.path().to_str().unwrap();
+        let report_path = project.path().join("previous.json");
+        fs::write(
+            &report_path,
+            r#"{ "project": ".", "used": [], "unused": ["react"] }"#,
+        )
+        .unwrap();
+
+        let (code, output, summary) = run_with_streams(&[
+            path,
+            "--format",
+            "json",
+            "--compare",
+            report_path.to_str().unwrap(),
+        ]);
+
+        assert_eq!(code, 0);
+        assert!(serde_json::from_str::<serde_json::Value>(&output).is_ok());
+        assert!(summary.contains("react"));
+        assert!(summary.contains("(resolved)"));
+    }
+
+    #[test]
+    fn test_options_packages_comma_separated_or_repeated() {
+        let matches = build_command()
+            .try_get_matches_from(["cnp", "--packages", "lodash,react", "--packages", "zod"])
+            .unwrap();
+        let options = Options::from_matches(&matches);
+
+        assert_eq!(options.packages, vec!["lodash", "react", "zod"]);
+    }
+
+    #[test]
+    fn test_run_packages_reports_only_selected_packages() {
+        let project = create_project(
+            r#""lodash": "^4.0.0", "react": "^18.0.0""#,
+            "import React from 'react';\nimport axios from 'axios';",
+        );
+        let path = project.path().to_str().unwrap();
+
+        let (code, output) =
+            run_with_args(&[path, "--packages", "lodash", "--no-tsc", "--format", "json"]);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(code, 0);
+        assert_eq!(json["summary"]["total"], 1);
+        assert_eq!(json["used"], serde_json::json!([]));
+        assert_eq!(json["missing"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_use_tsc_skipped_in_ci_unless_requested() {
+        let options = |args: &[&str]| {
+            let matches = build_command()
+                .try_get_matches_from(std::iter::once("cnp").chain(args.iter().copied()))
+                .unwrap();
+            Options::from_matches(&matches)
+        };
+
+        assert!(use_tsc(&options(&[]), false));
+        assert!(!use_tsc(&options(&[]), true));
+        assert!(use_tsc(&options(&["--tsc"]), true));
+        assert!(!use_tsc(&options(&["--no-tsc"]), false));
+        assert!(
+            build_command()
+                .try_get_matches_from(["cnp", "--tsc", "--no-tsc"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_options_tsc_timeout() {
+        let default = build_command().try_get_matches_from(["cnp"]).unwrap();
+        let custom = build_command()
+            .try_get_matches_from(["cnp", "--tsc-timeout", "5"])
+            .unwrap();
+
+        assert_eq!(Options::from_matches(&default).tsc_timeout, 60);
+        assert_eq!(Options::from_matches(&custom).tsc_timeout, 5);
+    }
+
+    #[test]
+    fn test_run_json_includes_multiple_lockfiles_warning() {
+        let project = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        fs::write(project.path().join("package-lock.json"), "{}").unwrap();
+        fs::write(project.path().join("yarn.lock"), "").unwrap();
+        let path = project.path().to_str().unwrap();
+
+        let (code, output) = run_with_args(&[path, "--no-tsc", "--format", "json"]);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(code, 0);
+        assert_eq!(
+            json["warnings"],
+            serde_json::json!([
+                "Multiple lockfiles detected (package-lock.json, yarn.lock). Please use only one package manager."
+            ])
+        );
+    }
+
+    #[test]
+    fn test_run_no_warnings_drops_json_warnings() {
+        let project = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        fs::write(project.path().join("package-lock.json"), "{}").unwrap();
+        fs::write(project.path().join("yarn.lock"), "").unwrap();
+        let path = project.path().to_str().unwrap();
+
+        let (_, output) = run_with_args(&[path, "--no-tsc", "--no-warnings", "--format", "json"]);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert!(json.get("warnings").is_none());
+    }
+
+    #[test]
+    fn test_run_report_unused_only_hides_used_section() {
+        let project = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let path = project.path().to_str().unwrap();
+
+        let (_, output) = run_with_args(&[path, "--no-tsc"]);
+        assert!(output.contains("Used Dependencies:"));
+
+        let (code, output) = run_with_args(&[path, "--no-tsc", "--report-unused-only"]);
+        assert_eq!(code, 0);
+        assert!(!output.contains("Used Dependencies:"));
+        assert!(output.contains("No unused dependencies found!"));
+    }
+
+    #[test]
+    fn test_options_preselect_all_requires_interactive() {
+        let matches = build_command()
+            .try_get_matches_from(["cnp", "-i", "--preselect-all"])
+            .unwrap();
+
+        assert!(Options::from_matches(&matches).preselect_all);
+        assert!(
+            build_command()
+                .try_get_matches_from(["cnp", "--preselect-all"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_report_section_flags_are_mutually_exclusive() {
+        let result = build_command().try_get_matches_from([
+            "cnp",
+            "--report-used-only",
+            "--report-unused-only",
+        ]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_run_max_warnings_fails_when_exceeded() {
+        let project = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        fs::write(project.path().join("package-lock.json"), "{}").unwrap();
+        fs::write(project.path().join("yarn.lock"), "").unwrap();
+        fs::write(project.path().join("cnp.toml"), "strict_manifest = \"yes\"").unwrap();
+        let path = project.path().to_str().unwrap();
+
+        let (code, output) = run_with_args(&[path, "--no-tsc", "--format", "json"]);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(code, 0);
+        assert_eq!(json["warnings"].as_array().unwrap().len(), 2);
+
+        let (code, _) =
+            run_with_args(&[path, "--no-tsc", "--format", "json", "--max-warnings", "0"]);
+        assert_eq!(code, 1);
+        let (code, _) = run_with_args(&[path, "--no-tsc", "--no-warnings", "--max-warnings", "1"]);
+        assert_eq!(code, 1);
+        let (code, _) =
+            run_with_args(&[path, "--no-tsc", "--format", "json", "--max-warnings", "2"]);
+        assert_eq!(code, 0);
+    }
+
+    #[test]
+    fn test_run_profile_includes_json_timings() {
+        let project = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let path = project.path().to_str().unwrap();
+
+        let (_, output) = run_with_args(&[path, "--no-tsc", "--format", "json"]);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert!(json.get("timings").is_none());
+
+        let (code, output) = run_with_args(&[path, "--no-tsc", "--format", "json", "--profile"]);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(code, 0);
+        let mut phases: Vec<&str> = json["timings"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        phases.sort();
+        assert_eq!(
+            phases,
+            vec!["lockfile", "manifest", "report", "scan", "tsc"]
+        );
+        assert!(json["timings"]["scan"].is_f64());
+    }
+
+    #[test]
+    fn test_run_profile_prints_console_timings() {
+        let project = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let path = project.path().to_str().unwrap();
+
+        let (_, output) = run_with_args(&[path, "--no-tsc", "--profile"]);
+
+        assert!(output.contains("Timings:"));
+        assert!(output.contains("- tsc: "));
+    }
+
+    #[test]
+    fn test_parse_report_file() {
+        assert_eq!(
+            parse_report_file("json=reports/cnp.json"),
+            Ok((OutputFormat::Json, "reports/cnp.json".to_string()))
+        );
+        assert_eq!(
+            parse_report_file("junit=a=b.xml"),
+            Ok((OutputFormat::Junit, "a=b.xml".to_string()))
+        );
+        assert!(parse_report_file("cnp.json").is_err());
+        assert!(parse_report_file("markdown=cnp.md").is_err());
+        assert!(parse_report_file("console=cnp.txt").is_err());
+        assert!(parse_report_file("json=").is_err());
+    }
+
+    #[test]
+    fn test_run_writes_every_report_file() {
+        let project = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        let path = project.path().to_str().unwrap();
+        let out_dir = TempDir::new().unwrap();
+        let json_file = out_dir.path().join("cnp.json");
+        let junit_file = out_dir.path().join("cnp.xml");
+        let json_arg = format!("json={}", json_file.display());
+        let junit_arg = format!("junit={}", junit_file.display());
+
+        let (code, output) = run_with_args(&[
+            path,
+            "--no-tsc",
+            "--report-file",
+            &json_arg,
+            "--report-file",
+            &junit_arg,
+        ]);
+
+        assert_eq!(code, 0);
+        assert!(output.contains("Dependency Usage Report"));
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json_file).unwrap()).unwrap();
+        assert_eq!(json["used"], serde_json::json!(["react"]));
+        let junit = fs::read_to_string(&junit_file).unwrap();
+        assert!(junit.starts_with("<?xml"));
+        assert!(junit.contains(r#"<testsuites name="cnp""#));
+    }
+
+    #[test]
+    fn test_run_allow_empty_prints_single_line() {
+        let project = create_project("", "console.log('no dependencies');");
+        let path = project.path().to_str().unwrap();
+
+        let (_, output) = run_with_args(&[path, "--no-tsc"]);
+        assert!(output.contains("Dependency Usage Report"));
+
+        let (code, output) = run_with_args(&[path, "--no-tsc", "--allow-empty"]);
+        assert_eq!(code, 0);
+        assert!(!output.contains("Dependency Usage Report"));
+        assert_eq!(output.lines().count(), 1);
+        assert!(output.contains("No dependencies declared in"));
+    }
+
+    #[test]
+    fn test_run_single_threaded_output_is_stable() {
+        let project = create_project(
+            r#""react": "^18.0.0", "lodash": "^4.0.0", "zod": "^3.0.0""#,
+            "import React from 'react';",
+        );
+        for (file, source) in [
+            ("a.js", "import merge from 'lodash/merge';"),
+            (
+                "b.js",
+                "import { z } from 'zod';\nimport axios from 'axios';",
+            ),
+            ("c.mjs", "import React from 'react';"),
+        ] {
+            fs::write(project.path().join(file), source).unwrap();
+        }
+        let path = project.path().to_str().unwrap();
+
+        let runs: Vec<String> = (0..3)
+            .map(|_| run_with_args(&[path, "--no-tsc", "--format", "json", "--threads", "1"]).1)
+            .collect();
+        let (_, parallel) =
+            run_with_args(&[path, "--no-tsc", "--format", "json", "--threads", "4"]);
+
+        assert!(runs.iter().all(|run| *run == runs[0]));
+        assert_eq!(parallel, runs[0]);
+        assert!(
+            build_command()
+                .try_get_matches_from(["cnp", "--threads", "0"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_print_config_lists_flags_over_cnp_toml() {
+        let project = create_project(r#""react": "^18.0.0""#, "import React from 'react';");
+        fs::write(project.path().join("cnp.toml"), "keep_types_node = true\n").unwrap();
+        let path = project.path().to_str().unwrap();
+
+        let (code, output) = run_with_args(&[
+            path,
+            "--print-config",
+            "--format",
+            "json",
+            "--report-types-node",
+            "--ignore-dev",
+            "--no-tsc",
+            "--tsc-timeout",
+            "5",
+            "--fail-on",
+            "production",
+        ]);
+
+        assert_eq!(code, 0);
+        let printed: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(printed["keep_types_node"], false);
+        let flags = &printed["flags"];
+        assert_eq!(flags["report_types_node"], true);
+        assert_eq!(flags["ignore_dev"], true);
+        assert_eq!(flags["tsc"], false);
+        assert_eq!(flags["tsc_timeout"], 5);
+        assert_eq!(flags["fail_on"], "production");
+        assert_eq!(flags["paths"], serde_json::json!([path]));
+    }
+}
@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use crate::compare::{
+        ReportDiff, diff_reports, previous_project_report, read_previous_report, write_report_diff,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_fixture_reports() {
+        let previous = read_previous_report("test_fixtures/report_previous.json").unwrap();
+        let current = read_previous_report("test_fixtures/report_current.json").unwrap();
+
+        let diff = diff_reports(&previous, &current);
+
+        assert_eq!(
+            diff,
+            ReportDiff {
+                newly_unused: vec!["axios".to_string()],
+                newly_used: vec!["lodash".to_string()],
+                resolved: vec!["lodash".to_string()],
+            }
+        );
+        assert!(diff.has_regressions());
+    }
+
+    #[test]
+    fn test_diff_reports_without_regressions() {
+        let previous = json!({ "used": ["react"], "unused": ["lodash", "moment"] });
+        let current = json!({ "used": ["react"], "unused": ["moment"] });
+
+        let diff = diff_reports(&previous, &current);
+
+        assert_eq!(diff.resolved, vec!["lodash".to_string()]);
+        assert!(!diff.has_regressions());
+    }
+
+    #[test]
+    fn test_previous_project_report() {
+        let single = json!({ "project": "./app", "unused": [] });
+        let multiple = json!([
+            { "project": "./app", "unused": ["lodash"] },
+            { "project": "./api", "unused": ["moment"] }
+        ]);
+
+        assert_eq!(previous_project_report(&single, "./other"), Some(&single));
+        assert_eq!(
+            previous_project_report(&multiple, "./api"),
+            Some(&multiple[1])
+        );
+        assert_eq!(previous_project_report(&multiple, "./web"), None);
+    }
+
+    #[test]
+    fn test_read_previous_report_missing_file() {
+        let result = read_previous_report("test_fixtures/does-not-exist.json");
+
+        assert!(result.unwrap_err().contains("not found"));
+    }
+
+    #[test]
+    fn test_write_report_diff() {
+        let diff = ReportDiff {
+            newly_unused: vec!["axios".to_string()],
+            newly_used: vec![],
+            resolved: vec!["lodash".to_string()],
+        };
+
+        let mut out = Vec::new();
+        write_report_diff(&mut out, &diff).unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        assert!(output.contains("Changes Since Previous Report:"));
+        assert!(output.contains("axios"));
+        assert!(output.contains("(newly unused)"));
+        assert!(output.contains("(resolved)"));
+    }
+}
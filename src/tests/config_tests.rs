@@ -1,7 +1,12 @@
 #[cfg(test)]
 mod tests {
-    use crate::config::is_typescript_project;
-    use std::fs::File;
+    use crate::config::{
+        Config, EffectiveConfig, Flags, SIDE_EFFECT_PACKAGES, is_typescript_project, load_config,
+        read_tsconfig, tsconfig_path_aliases,
+    };
+    use crate::report::OutputFormat;
+    use std::collections::HashSet;
+    use std::fs::{self, File};
     use tempfile::TempDir;
 
     #[test]
@@ -62,4 +67,276 @@ mod tests {
 
         assert!(!is_typescript_project(&temp_dir.path().to_string_lossy()));
     }
+
+    #[test]
+    fn it_loads_default_config_when_cnp_toml_is_missing() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let config = load_config(&temp_dir.path().to_string_lossy());
+
+        assert_eq!(config, Config::default());
+        assert!(
+            SIDE_EFFECT_PACKAGES
+                .iter()
+                .all(|pkg| config.side_effect_packages.contains(&pkg.to_string()))
+        );
+    }
+
+    #[test]
+    fn it_loads_side_effect_packages_from_cnp_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("cnp.toml"),
+            "side_effect_packages = [\"core-js\", \"my-polyfill\"]\n",
+        )
+        .unwrap();
+
+        let config = load_config(&temp_dir.path().to_string_lossy());
+
+        assert_eq!(
+            config.side_effect_packages,
+            vec!["core-js".to_string(), "my-polyfill".to_string()]
+        );
+    }
+
+    #[test]
+    fn it_falls_back_to_default_config_for_invalid_cnp_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("cnp.toml"),
+            "side_effect_packages = 42",
+        )
+        .unwrap();
+
+        let config = load_config(&temp_dir.path().to_string_lossy());
+
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn it_reads_tsconfig_with_comments_and_trailing_commas() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("tsconfig.json"),
+            r#"{
+                // Compiler settings
+                "compilerOptions": {
+                    "strict": true, /* keep strict */
+                    "paths": {
+                        "@/*": ["./src/*"],
+                        "utils": ["./src/utils"],
+                    },
+                },
+            }"#,
+        )
+        .unwrap();
+
+        let tsconfig = read_tsconfig(&temp_dir.path().to_string_lossy()).unwrap();
+        assert_eq!(tsconfig["compilerOptions"]["strict"], true);
+
+        let mut aliases = tsconfig_path_aliases(&temp_dir.path().to_string_lossy());
+        aliases.sort();
+        assert_eq!(aliases, vec!["@/".to_string(), "utils".to_string()]);
+    }
+
+    #[test]
+    fn it_returns_no_path_aliases_without_tsconfig() {
+        let temp_dir = TempDir::new().unwrap();
+
+        assert!(read_tsconfig(&temp_dir.path().to_string_lossy()).is_none());
+        assert!(tsconfig_path_aliases(&temp_dir.path().to_string_lossy()).is_empty());
+    }
+
+    #[test]
+    fn it_loads_strict_manifest_from_cnp_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("cnp.toml"),
+            "strict_manifest = false\n",
+        )
+        .unwrap();
+
+        let config = load_config(&temp_dir.path().to_string_lossy());
+
+        assert!(!config.strict_manifest);
+        assert!(Config::default().strict_manifest);
+    }
+
+    #[test]
+    fn it_loads_runtime_required_from_cnp_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("cnp.toml"),
+            "runtime_required = [\"dotenv\"]\n",
+        )
+        .unwrap();
+
+        let config = load_config(&temp_dir.path().to_string_lossy());
+
+        assert_eq!(config.runtime_required, vec!["dotenv".to_string()]);
+        assert!(Config::default().runtime_required.is_empty());
+    }
+
+    #[test]
+    fn it_matches_workspace_packages_by_prefix() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("cnp.toml"),
+            "workspace_packages = [\"@myorg/*\", \"internal-\"]\n",
+        )
+        .unwrap();
+
+        let config = load_config(temp_dir.path().to_str().unwrap());
+
+        assert!(config.is_workspace_package("@myorg/ui"));
+        assert!(config.is_workspace_package("internal-utils"));
+        assert!(!config.is_workspace_package("@myorganization/ui"));
+        assert!(!config.is_workspace_package("lodash"));
+        assert!(!Config::default().is_workspace_package("@myorg/ui"));
+    }
+
+    #[test]
+    fn it_reports_duplicate_purpose_groups() {
+        let used: HashSet<String> = ["moment", "dayjs", "lodash", "zod", "yup"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        assert_eq!(
+            Config::default().duplicate_purposes(&used),
+            vec![vec!["dayjs".to_string(), "moment".to_string()]]
+        );
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("cnp.toml"),
+            "duplicate_purpose_groups = [[\"zod\", \"yup\", \"joi\"], [\"moment\", \"dayjs\"]]\n",
+        )
+        .unwrap();
+        let config = load_config(temp_dir.path().to_str().unwrap());
+
+        assert_eq!(
+            config.duplicate_purposes(&used),
+            vec![
+                vec!["dayjs".to_string(), "moment".to_string()],
+                vec!["yup".to_string(), "zod".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn it_merges_framework_companions_from_cnp_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("cnp.toml"),
+            "[framework_companions]\nnext = [\"@vercel/og\"]\nmy-framework = [\"my-runtime\"]\n",
+        )
+        .unwrap();
+
+        let config = load_config(&temp_dir.path().to_string_lossy());
+        let declared = HashSet::from(["next".to_string()]);
+        let companions = config.framework_companions(&declared);
+
+        // Built-in companions are kept alongside the configured ones
+        assert!(companions.contains("@vercel/og"));
+        assert!(companions.contains("sharp"));
+        assert!(!companions.contains("my-runtime"));
+        assert!(
+            Config::default()
+                .framework_companions(&HashSet::new())
+                .is_empty()
+        );
+    }
+
+    fn default_flags() -> Flags {
+        Flags {
+            paths: vec![".".to_string()],
+            format: "console".to_string(),
+            color: "auto".to_string(),
+            reinstall: "never".to_string(),
+            tsc_timeout: 60,
+            cache: true,
+            report_sections: "all".to_string(),
+            ..Flags::default()
+        }
+    }
+
+    #[test]
+    fn it_lets_flags_override_cnp_toml_in_printed_config() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("cnp.toml"),
+            "keep_types_node = true
+",
+        )
+        .unwrap();
+        let flags = Flags {
+            report_types_node: true,
+            ..default_flags()
+        };
+
+        let effective = EffectiveConfig::resolve(&temp_dir.path().to_string_lossy(), flags);
+
+        assert!(!effective.config.keep_types_node);
+        let printed: toml::Value =
+            toml::from_str(&effective.render(OutputFormat::Console)).unwrap();
+        assert_eq!(printed["keep_types_node"], toml::Value::Boolean(false));
+        assert_eq!(
+            printed["flags"]["report_types_node"],
+            toml::Value::Boolean(true)
+        );
+    }
+
+    #[test]
+    fn it_reflects_cnp_toml_overrides_in_printed_config() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("cnp.toml"),
+            "side_effect_packages = [\"my-polyfill\"]\nstrict_manifest = false\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join(".cnpignore"), "react-dom\neslint\n").unwrap();
+
+        let effective =
+            EffectiveConfig::resolve(&temp_dir.path().to_string_lossy(), default_flags());
+
+        // TOML output
+        let printed: toml::Value =
+            toml::from_str(&effective.render(OutputFormat::Console)).unwrap();
+        assert_eq!(
+            printed["side_effect_packages"],
+            toml::Value::Array(vec![toml::Value::String("my-polyfill".to_string())])
+        );
+        assert_eq!(printed["strict_manifest"], toml::Value::Boolean(false));
+        assert_eq!(printed["flags"]["format"].as_str(), Some("console"));
+
+        // JSON output
+        let printed: serde_json::Value =
+            serde_json::from_str(&effective.render(OutputFormat::Json)).unwrap();
+        assert_eq!(
+            printed["side_effect_packages"],
+            serde_json::json!(["my-polyfill"])
+        );
+        assert_eq!(
+            printed["ignored_packages"],
+            serde_json::json!(["eslint", "react-dom"])
+        );
+        assert_eq!(printed["extensions"][0], "js");
+    }
+
+    #[test]
+    fn it_prints_defaults_without_cnp_toml() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let effective =
+            EffectiveConfig::resolve(&temp_dir.path().to_string_lossy(), default_flags());
+
+        assert_eq!(effective.config, Config::default());
+        assert!(effective.ignored_packages.is_empty());
+        assert!(
+            effective
+                .ignore_folders
+                .contains(&"node_modules".to_string())
+        );
+    }
 }
@@ -1,6 +1,17 @@
 #[cfg(test)]
 mod tests {
-    use crate::dependency::{get_required_dependencies, read_cnpignore, read_package_json};
+    use crate::dependency::{
+        audit_lifecycle_scripts, broken_entry_points, bundled_packages, describe_json_error,
+        describe_read_error, duplicate_versions, find_dependency_line, find_lifecycle_scripts,
+        find_orphaned_types, find_preloaded_packages, find_script_commands, find_stale_overrides,
+        get_required_dependencies, installed_bins, is_lockfile_readable, is_types_of_used_package,
+        lockfile_versions, malformed_dependency_fields, manifest_config_packages,
+        missing_script_commands, override_package_name, override_targets, package_sizes,
+        parse_bun_lock, read_cnpignore, read_cnpignore_sections, read_jsonc, read_package_json,
+        script_preloaded_packages, tooling_config_packages, types_base_package,
+        unlocked_dependencies,
+    };
+    use crate::warnings::Warnings;
     use colored::Colorize;
     use std::io::Write;
     use std::{collections::HashSet, error::Error, fs, path::PathBuf};
@@ -57,6 +68,69 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_read_package_json_reports_line_and_column() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_path = temp_dir.path().join("package.json");
+        fs::write(&package_path, "{\n  \"name\": \"app\",\n  \"version\": \n}").unwrap();
+
+        let err = read_package_json(package_path.to_str().unwrap()).unwrap_err();
+
+        assert_eq!(
+            err,
+            "Error: Invalid JSON in package.json. Syntax error at line 4, column 1."
+        );
+    }
+
+    #[test]
+    fn test_read_package_json_reports_unexpected_end_of_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_path = temp_dir.path().join("package.json");
+        fs::write(&package_path, "{ \"name\": \"app\"").unwrap();
+
+        let err = read_package_json(package_path.to_str().unwrap()).unwrap_err();
+
+        assert_eq!(
+            err,
+            "Error: Invalid JSON in package.json. Unexpected end of file at line 1, column 15."
+        );
+    }
+
+    #[test]
+    fn test_read_package_json_directory_is_not_reported_as_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_path = temp_dir.path().join("package.json");
+        fs::create_dir(&package_path).unwrap();
+
+        let err = read_package_json(package_path.to_str().unwrap()).unwrap_err();
+
+        assert!(err.starts_with("Error: Could not read `package.json` ("));
+    }
+
+    #[test]
+    fn test_describe_read_error_kinds() {
+        let error = |kind| std::io::Error::from(kind);
+
+        assert_eq!(
+            describe_read_error("package.json", &error(std::io::ErrorKind::NotFound)),
+            "Error: `package.json` not found."
+        );
+        assert_eq!(
+            describe_read_error("package.json", &error(std::io::ErrorKind::PermissionDenied)),
+            "Error: Permission denied reading `package.json`."
+        );
+    }
+
+    #[test]
+    fn test_describe_json_error_data_error() {
+        let err = serde_json::from_str::<Vec<String>>("[1]").unwrap_err();
+
+        assert_eq!(
+            describe_json_error("cnp.json", &err),
+            "Error: Invalid JSON in cnp.json. Invalid data at line 1, column 2."
+        );
+    }
+
     #[test]
     fn test_get_required_dependencies_single_package_json() {
         // TODO: handle devDependencies logic
@@ -78,7 +152,12 @@ mod tests {
         fs::write(&package_path, content).unwrap();
 
         // Check that only the dependencies are returned
-        let deps = get_required_dependencies(&temp_dir.path().to_str().unwrap());
+        let deps = get_required_dependencies(
+            &temp_dir.path().to_str().unwrap(),
+            false,
+            None,
+            &mut Warnings::default(),
+        );
         println!("Dependencies: {:?}", deps);
         assert!(!deps.is_empty());
         assert_eq!(deps.len(), 2);
@@ -86,13 +165,55 @@ mod tests {
         assert!(deps.contains("test-dep"));
     }
 
+    #[test]
+    fn test_get_required_dependencies_bundled_dependencies() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{
+                "dependencies": { "a": "^1.0.0", "b": "^1.0.0" },
+                "bundledDependencies": ["a", "b"]
+            }"#,
+        )
+        .unwrap();
+
+        let deps = get_required_dependencies(
+            temp_dir.path().to_str().unwrap(),
+            false,
+            None,
+            &mut Warnings::default(),
+        );
+
+        assert!(deps.contains("a"));
+        assert!(deps.contains("b"));
+    }
+
+    #[test]
+    fn test_bundled_packages() {
+        let bundled = serde_json::json!({ "bundledDependencies": ["a", "b"] });
+        let bundle = serde_json::json!({ "bundleDependencies": ["c"] });
+        let all = serde_json::json!({ "bundledDependencies": true });
+
+        assert_eq!(
+            bundled_packages(&bundled),
+            HashSet::from(["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(bundled_packages(&bundle), HashSet::from(["c".to_string()]));
+        assert!(bundled_packages(&all).is_empty());
+    }
+
     #[test]
     fn test_get_required_dependencies_no_package_json() {
         // Create a temporary directory without package.json
         let temp_dir = TempDir::new().unwrap();
 
         // Check that an empty set is returned
-        let deps = get_required_dependencies(temp_dir.path().to_str().unwrap());
+        let deps = get_required_dependencies(
+            temp_dir.path().to_str().unwrap(),
+            false,
+            None,
+            &mut Warnings::default(),
+        );
         assert!(deps.is_empty());
     }
 
@@ -105,7 +226,12 @@ mod tests {
         fs::write(&package_path, "invalid json").expect("Failed to write invalid JSON");
 
         // Check that an empty set is returned
-        let deps = get_required_dependencies(temp_dir.path().to_str().unwrap());
+        let deps = get_required_dependencies(
+            temp_dir.path().to_str().unwrap(),
+            false,
+            None,
+            &mut Warnings::default(),
+        );
         assert!(deps.is_empty());
     }
 
@@ -130,7 +256,12 @@ mod tests {
             .expect("Failed to copy yarn.lock to temporary directory");
 
         // Check that an empty set is returned and a warning is printed
-        let deps = get_required_dependencies(temp_dir.path().to_str().unwrap());
+        let deps = get_required_dependencies(
+            temp_dir.path().to_str().unwrap(),
+            false,
+            None,
+            &mut Warnings::default(),
+        );
         assert!(deps.is_empty());
         eprintln!(
             "\n{}: Multiple lockfiles detected ({}). Please use only one package manager.",
@@ -139,6 +270,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_required_dependencies_ignores_lockfiles_in_subdirectories() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package-lock.json"),
+            r#"{ "packages": { "node_modules/dep1": { "version": "1.0.0" } } }"#,
+        )
+        .unwrap();
+        fs::create_dir_all(temp_dir.path().join("examples/demo")).unwrap();
+        fs::write(
+            temp_dir.path().join("examples/demo/yarn.lock"),
+            "dep2@^1.0.0:\n",
+        )
+        .unwrap();
+        let mut warnings = Warnings::default();
+
+        let deps = get_required_dependencies(
+            temp_dir.path().to_str().unwrap(),
+            false,
+            None,
+            &mut warnings,
+        );
+
+        assert!(warnings.is_empty());
+        assert_eq!(deps, HashSet::from(["dep1".to_string()]));
+    }
+
+    #[test]
+    fn test_get_required_dependencies_named_lockfile() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().to_str().unwrap();
+        fs::write(
+            temp_dir.path().join("package-lock.json"),
+            r#"{ "packages": { "node_modules/dep1": { "version": "1.0.0" } } }"#,
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("yarn.lock"), "dep2@^1.0.0:\n").unwrap();
+
+        let mut warnings = Warnings::default();
+        let deps = get_required_dependencies(dir, false, Some("yarn.lock"), &mut warnings);
+        assert!(warnings.is_empty());
+        assert_eq!(deps, HashSet::from(["dep2".to_string()]));
+
+        let mut warnings = Warnings::default();
+        let deps = get_required_dependencies(dir, false, Some("bun.lock"), &mut warnings);
+        assert!(deps.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert!(
+            warnings
+                .iter()
+                .any(|warning| warning.contains("`bun.lock` not found"))
+        );
+    }
+
     #[test] // TODO: need to handle packages too
     fn test_get_required_dependencies_package_lock_json() {
         // Create a temporary directory with package-lock.json
@@ -152,7 +337,12 @@ mod tests {
         fs::write(&lockfile_path, content).expect("Failed to write package-lock.json");
 
         // Check that only the dependencies are returned
-        let deps = get_required_dependencies(temp_dir.path().to_str().unwrap());
+        let deps = get_required_dependencies(
+            temp_dir.path().to_str().unwrap(),
+            false,
+            None,
+            &mut Warnings::default(),
+        );
 
         assert!(!deps.is_empty());
         assert_eq!(deps.len(), 1);
@@ -194,7 +384,12 @@ mod tests {
         fs::write(&lockfile_path, content).expect("Failed to write yarn.lock");
 
         // Check that only the dependencies are returned
-        let deps = get_required_dependencies(temp_dir.path().to_str().unwrap());
+        let deps = get_required_dependencies(
+            temp_dir.path().to_str().unwrap(),
+            false,
+            None,
+            &mut Warnings::default(),
+        );
 
         assert_eq!(deps.len(), 4);
         assert!(deps.contains("package-1"));
@@ -220,13 +415,147 @@ mod tests {
         fs::write(&lockfile_path, content).expect("Failed to write pnpm-lock.yaml");
 
         // Check that both dependencies and devDependencies are returned
-        let deps = get_required_dependencies(temp_dir.path().to_str().unwrap());
+        let deps = get_required_dependencies(
+            temp_dir.path().to_str().unwrap(),
+            false,
+            None,
+            &mut Warnings::default(),
+        );
 
         assert!(!deps.is_empty());
         assert_eq!(deps.len(), 2);
         assert!(deps.contains("@dep1"));
     }
 
+    #[test]
+    fn test_get_required_dependencies_production_only_package_lock_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = r#"{
+            "packages": {
+                "": { "dependencies": { "dep1": "^1.0.0" }, "devDependencies": { "jest": "^29.0.0" } },
+                "node_modules/dep1": { "version": "1.0.0", "dependencies": { "dep2": "^2.0.0" } },
+                "node_modules/dep2": { "version": "2.0.0" },
+                "node_modules/jest": { "version": "29.0.0", "dev": true, "dependencies": { "expect": "^29.0.0" } },
+                "node_modules/fsevents": { "version": "2.3.3", "devOptional": true }
+            }
+        }"#;
+        fs::write(temp_dir.path().join("package-lock.json"), content).unwrap();
+        let dir = temp_dir.path().to_str().unwrap();
+
+        // Dev-only packages are required unless only production entries are read
+        let all = get_required_dependencies(dir, false, None, &mut Warnings::default());
+        assert!(all.contains("jest") && all.contains("fsevents"));
+
+        // The root project's dependencies are left out, the ones of production packages are kept
+        let production = get_required_dependencies(dir, true, None, &mut Warnings::default());
+        assert_eq!(production, HashSet::from(["dep2".to_string()]));
+    }
+
+    #[test]
+    fn test_get_required_dependencies_production_only_pnpm_importers() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = r#"lockfileVersion: '9.0'
+importers:
+  .:
+    dependencies:
+      react:
+        specifier: ^18.0.0
+        version: 18.2.0
+    devDependencies:
+      vitest:
+        specifier: ^1.0.0
+        version: 1.0.0
+  packages/ui:
+    optionalDependencies:
+      sharp:
+        specifier: ^0.33.0
+        version: 0.33.0
+snapshots:
+  react@18.2.0:
+    dependencies:
+      loose-envify: 1.4.0
+  vitest@1.0.0:
+    dev: true
+    dependencies:
+      tinypool: 0.8.0
+"#;
+        fs::write(temp_dir.path().join("pnpm-lock.yaml"), content).unwrap();
+        let dir = temp_dir.path().to_str().unwrap();
+
+        let all = get_required_dependencies(dir, false, None, &mut Warnings::default());
+        assert!(all.contains("react") && all.contains("vitest"));
+
+        let production = get_required_dependencies(dir, true, None, &mut Warnings::default());
+        assert_eq!(
+            production,
+            HashSet::from(["sharp".to_string(), "loose-envify".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_get_required_dependencies_production_only_yarn_and_bun_locks() {
+        let yarn_dir = TempDir::new().unwrap();
+        fs::write(
+            yarn_dir.path().join("yarn.lock"),
+            r#"react@^18.0.0:
+  version "18.2.0"
+  dependencies:
+    loose-envify "^1.1.0"
+
+"@babel/core@npm:^7.0.0":
+  version: 7.24.0
+  dependencies:
+    "@babel/types": "npm:^7.24.0"
+"#,
+        )
+        .unwrap();
+        let production = get_required_dependencies(
+            yarn_dir.path().to_str().unwrap(),
+            true,
+            None,
+            &mut Warnings::default(),
+        );
+        assert_eq!(
+            production,
+            HashSet::from(["loose-envify".to_string(), "@babel/types".to_string()])
+        );
+
+        let bun_dir = TempDir::new().unwrap();
+        fs::write(
+            bun_dir.path().join("bun.lock"),
+            r#"{
+  "workspaces": { "": { "dependencies": { "react": "^18.0.0", }, }, },
+  "packages": {
+    "react": ["react@18.2.0", "", { "dependencies": { "loose-envify": "^1.1.0" } }, "sha512-"],
+    "loose-envify": ["loose-envify@1.4.0", "", {}, "sha512-"],
+  },
+}"#,
+        )
+        .unwrap();
+        let production = get_required_dependencies(
+            bun_dir.path().to_str().unwrap(),
+            true,
+            None,
+            &mut Warnings::default(),
+        );
+        assert_eq!(production, HashSet::from(["loose-envify".to_string()]));
+    }
+
+    #[test]
+    fn test_get_required_dependencies_production_only_skips_declared_dependencies() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = r#"{
+            "dependencies": { "react": "^18.0.0" },
+            "devDependencies": { "jest": "^29.0.0" }
+        }"#;
+        fs::write(temp_dir.path().join("package.json"), content).unwrap();
+        let dir = temp_dir.path().to_str().unwrap();
+
+        let all = get_required_dependencies(dir, false, None, &mut Warnings::default());
+        assert!(all.contains("react") && all.contains("jest"));
+        assert!(get_required_dependencies(dir, true, None, &mut Warnings::default()).is_empty());
+    }
+
     #[test]
     fn test_get_required_dependencies_bun_lock() {
         // Create a temporary directory with bun.lock
@@ -248,13 +577,141 @@ mod tests {
         fs::write(&lockfile_path, content).expect("Failed to write bun.lock");
 
         // Check that both dependencies and devDependencies are returned
-        let deps = get_required_dependencies(temp_dir.path().to_str().unwrap());
+        let deps = get_required_dependencies(
+            temp_dir.path().to_str().unwrap(),
+            false,
+            None,
+            &mut Warnings::default(),
+        );
 
         assert!(!deps.is_empty());
         assert_eq!(deps.len(), 2);
         assert!(deps.contains("@dep1"));
     }
 
+    #[test]
+    fn test_duplicate_versions_yarn_lock() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::copy(
+            "test_fixtures/yarn-duplicates.lock",
+            temp_dir.path().join("yarn.lock"),
+        )
+        .unwrap();
+
+        let duplicates = duplicate_versions(temp_dir.path().to_str().unwrap(), None);
+
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates["lodash"], vec!["3.10.1", "4.17.21"]);
+    }
+
+    #[test]
+    fn test_duplicate_versions_without_lockfile() {
+        let temp_dir = TempDir::new().unwrap();
+
+        assert!(duplicate_versions(temp_dir.path().to_str().unwrap(), None).is_empty());
+    }
+
+    #[test]
+    fn test_lockfile_versions_other_package_managers() {
+        let package_lock = r#"{
+            "packages": {
+                "": { "name": "app" },
+                "node_modules/debug": { "version": "4.3.4" },
+                "node_modules/send/node_modules/debug": { "version": "2.6.9" }
+            }
+        }"#;
+        let pnpm_lock = "packages:\n  /debug@4.3.4(supports-color@8.1.1):\n    resolution: {}\n  debug@2.6.9:\n    resolution: {}\n  /@scope/pkg/1.0.0:\n    resolution: {}\n";
+        let bun_lock = r#"{ "packages": { "debug": ["debug@4.3.4", {}], "send/debug": ["debug@2.6.9", {}], } }"#;
+
+        for (lockfile, content) in [
+            ("package-lock.json", package_lock),
+            ("pnpm-lock.yaml", pnpm_lock),
+            ("bun.lock", bun_lock),
+        ] {
+            let versions = lockfile_versions(lockfile, content);
+            assert_eq!(
+                versions["debug"].iter().collect::<Vec<_>>(),
+                vec!["2.6.9", "4.3.4"],
+                "{}",
+                lockfile
+            );
+        }
+        assert!(lockfile_versions("pnpm-lock.yaml", pnpm_lock)["@scope/pkg"].contains("1.0.0"));
+    }
+
+    #[test]
+    fn test_parse_bun_lock_trailing_commas_and_workspaces() {
+        let content = r#"{
+  "lockfileVersion": 1,
+  "workspaces": {
+    "": {
+      "name": "monorepo",
+      "devDependencies": {
+        "typescript": "^5.0.0",
+      },
+    },
+    "packages/web": {
+      "name": "web",
+      "dependencies": {
+        "react": "^18.2.0",
+        "@vercel/analytics": "^1.0.0",
+      },
+      "optionalDependencies": {
+        "fsevents": "^2.3.0",
+      },
+    },
+  },
+  "packages": {
+    "react": ["react@18.2.0", "", { "dependencies": { "loose-envify": "^1.1.0" } }, "sha512-abc"],
+  },
+}"#;
+
+        let deps = parse_bun_lock(content);
+
+        let expected: HashSet<String> = ["typescript", "react", "@vercel/analytics", "fsevents"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(deps, expected);
+    }
+
+    #[test]
+    fn test_parse_bun_lock_truncated_content() {
+        // A truncated file is not valid JSON, so its dependency blocks are read line by line
+        let content = r#"{
+  "workspaces": {
+    "": {
+      "dependencies": {
+        "react": "^18.2.0",
+      },
+      "devDependencies": {
+        "typescript": "^5.0.0",
+      },
+    },
+  },
+  "packages": {
+    "react": ["react@18.2.0", "", { "dependencies": { "loose-envify": "^1.1.0" } }, "sha512-abc"],
+    "scheduler": ["scheduler@0.23.0", "#;
+
+        let deps = parse_bun_lock(content);
+
+        assert_eq!(
+            deps,
+            HashSet::from(["react".to_string(), "typescript".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_parse_bun_lock_fixture() {
+        let content = fs::read_to_string("test_fixtures/bun.lock").unwrap();
+
+        let deps = parse_bun_lock(&content);
+
+        assert_eq!(deps.len(), 5);
+        assert!(deps.contains("@vercel/analytics"));
+        assert!(deps.contains("typescript"));
+    }
+
     #[test]
     fn test_read_cnpignore_with_valid_patterns() -> Result<(), Box<dyn Error>> {
         // Create a temporary directory and file
@@ -267,7 +724,7 @@ mod tests {
         writeln!(file, "pattern1").unwrap();
         writeln!(file, "").unwrap(); // Empty line
         writeln!(file, "  pattern2 ").unwrap();
-        writeln!(file, "pattern3# This is an inline comment").unwrap();
+        writeln!(file, "pattern3 # This is an inline comment").unwrap();
 
         // Read the patterns and assert they match expected values
         let ignore_patterns = read_cnpignore(&file_path.to_str().unwrap());
@@ -302,6 +759,64 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_read_cnpignore_crlf_and_inline_hashes() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join(".cnpignore");
+        fs::write(
+            &file_path,
+            "# Windows line endings\r\nreact-dom\r\n  eslint  \r\n\r\nprettier\t# formatter\r\ngithub:user/repo#main\r\n#typescript\r\nold-mac\rlast-line",
+        )
+        .unwrap();
+
+        let ignore_patterns = read_cnpignore(file_path.to_str().unwrap());
+
+        assert_eq!(
+            ignore_patterns,
+            HashSet::from([
+                "react-dom".to_string(),
+                "eslint".to_string(),
+                "prettier".to_string(),
+                "github:user/repo#main".to_string(),
+                "old-mac".to_string(),
+                "last-line".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_read_cnpignore_sections() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join(".cnpignore");
+        fs::write(
+            &file_path,
+            "# Loaded at runtime\nreact-dom\n\n[packages]\neslint # tooling\n\n[paths]\nsrc/generated/**\n**/*.stories.js\n\n[unknown]\nskipped\n[ Packages ]\nprettier\n",
+        )
+        .unwrap();
+
+        let cnpignore = read_cnpignore_sections(file_path.to_str().unwrap());
+
+        assert_eq!(
+            cnpignore.packages,
+            HashSet::from([
+                "react-dom".to_string(),
+                "eslint".to_string(),
+                "prettier".to_string(),
+            ])
+        );
+        assert_eq!(
+            cnpignore.paths,
+            vec![
+                "src/generated/**".to_string(),
+                "**/*.stories.js".to_string()
+            ]
+        );
+        assert_eq!(
+            read_cnpignore(file_path.to_str().unwrap()),
+            cnpignore.packages
+        );
+    }
+
     #[test]
     fn test_read_cnpignore_file_not_found() {
         let temp_dir = TempDir::new().unwrap();
@@ -313,4 +828,550 @@ mod tests {
 
         assert_eq!(ignore_patterns, expected_patterns);
     }
+
+    #[test]
+    fn test_read_jsonc_with_comments() {
+        let temp_dir = TempDir::new().unwrap();
+        let tsconfig_path = temp_dir.path().join("tsconfig.json");
+        fs::write(
+            &tsconfig_path,
+            "{\n  // comment\n  \"compilerOptions\": { \"strict\": true, },\n}",
+        )
+        .unwrap();
+
+        // The strict reader rejects comments, the lenient one accepts them
+        assert!(read_package_json(tsconfig_path.to_str().unwrap()).is_err());
+        let json = read_jsonc(tsconfig_path.to_str().unwrap()).unwrap();
+        assert_eq!(json["compilerOptions"]["strict"], true);
+    }
+
+    #[test]
+    fn test_read_jsonc_invalid_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let tsconfig_path = temp_dir.path().join("tsconfig.json");
+        fs::write(&tsconfig_path, "{ not json }").unwrap();
+
+        match read_jsonc(tsconfig_path.to_str().unwrap()) {
+            Ok(_) => panic!("Expected an error, but got success."),
+            Err(e) => assert!(e.contains("Error: Invalid JSON in tsconfig.json.")),
+        }
+    }
+
+    #[test]
+    fn test_find_preloaded_packages() {
+        let set = |names: &[&str]| -> HashSet<String> {
+            names.iter().map(|name| name.to_string()).collect()
+        };
+
+        assert_eq!(
+            find_preloaded_packages("node -r dotenv/config index.js"),
+            set(&["dotenv"])
+        );
+        assert_eq!(
+            find_preloaded_packages("node --require=ts-node/register --import tsx src/main.ts"),
+            set(&["ts-node", "tsx"])
+        );
+        assert_eq!(
+            find_preloaded_packages("NODE_OPTIONS='--require @babel/register' node app.js"),
+            set(&["@babel/register"])
+        );
+        assert_eq!(
+            find_preloaded_packages("node -r ./setup.js --inspect index.js"),
+            set(&[])
+        );
+    }
+
+    #[test]
+    fn test_script_preloaded_packages() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(".node-flags"), "--import tsx\n").unwrap();
+        let package_json: serde_json::Value = serde_json::from_str(
+            r#"{ "scripts": { "start": "node -r dotenv/config index.js", "test": "jest" } }"#,
+        )
+        .unwrap();
+
+        let packages = script_preloaded_packages(temp_dir.path().to_str().unwrap(), &package_json);
+
+        assert_eq!(
+            packages,
+            HashSet::from(["dotenv".to_string(), "tsx".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_find_dependency_line() {
+        let manifest = r#"{
+  "name": "app",
+  "scripts": { "lodash-check": "node check.js" },
+  "dependencies": {
+    "lodash": "^4.17.21",
+    "@babel/core" : "^7.0.0"
+  }
+}"#;
+
+        assert_eq!(find_dependency_line(manifest, "lodash"), Some(5));
+        assert_eq!(find_dependency_line(manifest, "@babel/core"), Some(6));
+        assert_eq!(find_dependency_line(manifest, "react"), None);
+    }
+
+    #[test]
+    fn test_is_types_of_used_package() {
+        let used = HashSet::from(["@babel/core".to_string(), "lodash".to_string()]);
+
+        assert!(is_types_of_used_package("@types/babel__core", &used));
+        assert!(is_types_of_used_package("@types/lodash", &used));
+        assert!(!is_types_of_used_package("@types/babel__traverse", &used));
+        assert!(!is_types_of_used_package("@babel/core", &used));
+    }
+
+    #[test]
+    fn test_types_base_package() {
+        assert_eq!(
+            types_base_package("@types/lodash"),
+            Some("lodash".to_string())
+        );
+        assert_eq!(
+            types_base_package("@types/babel__core"),
+            Some("@babel/core".to_string())
+        );
+        assert_eq!(
+            types_base_package("@types/apollo__client"),
+            Some("@apollo/client".to_string())
+        );
+        assert_eq!(types_base_package("@types/"), None);
+        assert_eq!(types_base_package("@types/babel__"), None);
+        assert_eq!(types_base_package("@types/__core"), None);
+        assert_eq!(types_base_package("lodash"), None);
+    }
+
+    #[test]
+    fn test_find_orphaned_types() {
+        let package_json = serde_json::json!({
+            "dependencies": {
+                "react": "^18.0.0",
+                "@babel/core": "^7.0.0"
+            },
+            "devDependencies": {
+                "@types/lodash": "^4.0.0",
+                "@types/react": "^18.0.0",
+                "@types/babel__core": "^7.0.0",
+                "@types/babel__traverse": "^7.0.0",
+                "@types/node": "^20.0.0"
+            }
+        });
+
+        assert_eq!(
+            find_orphaned_types(&package_json),
+            vec![
+                "@types/babel__traverse".to_string(),
+                "@types/lodash".to_string()
+            ]
+        );
+    }
+
+    /// Installs a fake package in `node_modules` with the given `scripts` field.
+    fn install_package(dir: &TempDir, name: &str, scripts: &str) {
+        let package_dir = dir.path().join("node_modules").join(name);
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::write(
+            package_dir.join("package.json"),
+            format!(r#"{{ "name": "{}", "scripts": {{ {} }} }}"#, name, scripts),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_find_lifecycle_scripts() {
+        let temp_dir = TempDir::new().unwrap();
+        install_package(
+            &temp_dir,
+            "esbuild",
+            r#""postinstall": "node install.js", "build": "tsc", "preinstall": "node check.js""#,
+        );
+        let dir_path = temp_dir.path().to_str().unwrap();
+
+        assert_eq!(
+            find_lifecycle_scripts(dir_path, "esbuild"),
+            vec!["preinstall".to_string(), "postinstall".to_string()]
+        );
+        assert!(find_lifecycle_scripts(dir_path, "not-installed").is_empty());
+    }
+
+    #[test]
+    fn test_audit_lifecycle_scripts() {
+        let temp_dir = TempDir::new().unwrap();
+        install_package(
+            &temp_dir,
+            "@scope/native",
+            r#""install": "node-gyp rebuild""#,
+        );
+        install_package(&temp_dir, "lodash", r#""test": "jest""#);
+        let dependencies = vec![
+            "@scope/native".to_string(),
+            "lodash".to_string(),
+            "left-pad".to_string(),
+        ];
+
+        let scripts = audit_lifecycle_scripts(temp_dir.path().to_str().unwrap(), &dependencies);
+
+        assert_eq!(scripts.len(), 1);
+        assert_eq!(scripts["@scope/native"], vec!["install".to_string()]);
+    }
+
+    #[test]
+    fn test_find_script_commands() {
+        assert_eq!(
+            find_script_commands("NODE_ENV=test jest --coverage && eslint . | tee lint.log"),
+            vec!["jest", "eslint", "tee"]
+        );
+        assert_eq!(
+            find_script_commands("(cd docs; vitepress build) || ./scripts/fail.sh"),
+            vec!["cd", "vitepress"]
+        );
+        assert!(find_script_commands("$EDITOR .").is_empty());
+    }
+
+    #[test]
+    fn test_installed_bins() {
+        let temp_dir = TempDir::new().unwrap();
+        let node_modules = temp_dir.path().join("node_modules");
+        fs::create_dir_all(node_modules.join("typescript")).unwrap();
+        fs::write(
+            node_modules.join("typescript/package.json"),
+            r#"{ "name": "typescript", "bin": { "tsc": "./bin/tsc", "tsserver": "./bin/tsserver" } }"#,
+        )
+        .unwrap();
+        fs::create_dir_all(node_modules.join("@biomejs/biome")).unwrap();
+        fs::write(
+            node_modules.join("@biomejs/biome/package.json"),
+            r#"{ "name": "@biomejs/biome", "bin": "./bin/biome" }"#,
+        )
+        .unwrap();
+        install_package(&temp_dir, "lodash", "");
+
+        let bins = installed_bins(temp_dir.path().to_str().unwrap());
+
+        let expected: HashSet<String> = ["tsc", "tsserver", "biome"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(bins, expected);
+    }
+
+    #[test]
+    fn test_missing_script_commands() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_dir = temp_dir.path().join("node_modules/prettier");
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::write(
+            package_dir.join("package.json"),
+            r#"{ "name": "prettier", "bin": "./bin/prettier.cjs" }"#,
+        )
+        .unwrap();
+        let package_json = serde_json::json!({
+            "scripts": {
+                "lint": "eslint .",
+                "lint:fix": "eslint . --fix && prettier --write .",
+                "start": "node server.js"
+            }
+        });
+
+        let missing = missing_script_commands(temp_dir.path().to_str().unwrap(), &package_json);
+
+        assert_eq!(missing.len(), 1);
+        assert_eq!(
+            missing["eslint"],
+            vec!["lint".to_string(), "lint:fix".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_missing_script_commands_without_node_modules() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_json = serde_json::json!({ "scripts": { "lint": "eslint ." } });
+
+        assert!(
+            missing_script_commands(temp_dir.path().to_str().unwrap(), &package_json).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_broken_entry_points() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("lib")).unwrap();
+        fs::write(temp_dir.path().join("lib/index.js"), "").unwrap();
+        fs::write(temp_dir.path().join("lib/index.d.ts"), "").unwrap();
+        fs::write(temp_dir.path().join("package.json"), "{}").unwrap();
+        let package_json = serde_json::json!({
+            "main": "lib/index",
+            "module": "dist/index.mjs",
+            "types": "lib/index.d.ts",
+            "exports": {
+                ".": { "import": "./dist/index.mjs", "require": "./lib/index.js" },
+                "./utils/*": "./lib/utils/*.js",
+                "./package.json": "./package.json"
+            }
+        });
+
+        let broken = broken_entry_points(temp_dir.path().to_str().unwrap(), &package_json);
+
+        assert_eq!(
+            broken.into_iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    "exports[\".\"][\"import\"]".to_string(),
+                    "./dist/index.mjs".to_string()
+                ),
+                ("module".to_string(), "dist/index.mjs".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_broken_entry_points_missing_main() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_json = serde_json::json!({ "main": "index.js" });
+
+        let broken = broken_entry_points(temp_dir.path().to_str().unwrap(), &package_json);
+        assert_eq!(broken["main"], "index.js");
+
+        fs::write(temp_dir.path().join("index.js"), "").unwrap();
+        assert!(broken_entry_points(temp_dir.path().to_str().unwrap(), &package_json).is_empty());
+    }
+
+    #[test]
+    fn test_package_sizes() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_dir = temp_dir.path().join("node_modules/left-pad/lib");
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::write(package_dir.join("index.js"), "a".repeat(1000)).unwrap();
+        fs::write(package_dir.join("../package.json"), "b".repeat(24)).unwrap();
+        let unused = vec!["left-pad".to_string(), "not-installed".to_string()];
+
+        let sizes = package_sizes(temp_dir.path().to_str().unwrap(), &unused);
+
+        assert_eq!(sizes.get("left-pad"), Some(&1024));
+        assert!(!sizes.contains_key("not-installed"));
+    }
+
+    #[test]
+    fn test_package_sizes_without_node_modules() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let sizes = package_sizes(temp_dir.path().to_str().unwrap(), &["lodash".to_string()]);
+
+        assert!(sizes.is_empty());
+    }
+
+    #[test]
+    fn test_override_package_name() {
+        assert_eq!(override_package_name("lodash"), Some("lodash".to_string()));
+        assert_eq!(
+            override_package_name("lodash@^4"),
+            Some("lodash".to_string())
+        );
+        assert_eq!(
+            override_package_name("@babel/core@7.0.0"),
+            Some("@babel/core".to_string())
+        );
+        assert_eq!(
+            override_package_name("**/@babel/core"),
+            Some("@babel/core".to_string())
+        );
+        assert_eq!(
+            override_package_name("webpack/terser"),
+            Some("terser".to_string())
+        );
+        assert_eq!(
+            override_package_name("webpack>terser"),
+            Some("terser".to_string())
+        );
+        assert_eq!(override_package_name("."), None);
+    }
+
+    #[test]
+    fn test_override_targets() {
+        let package_json = serde_json::json!({
+            "overrides": { "react": { ".": "18.2.0", "loose-envify": "1.4.0" } },
+            "resolutions": { "**/minimist": "1.2.8" },
+            "pnpm": { "overrides": { "webpack>terser": "5.0.0" } }
+        });
+
+        assert_eq!(
+            override_targets(&package_json),
+            vec!["loose-envify", "minimist", "react", "terser"]
+        );
+    }
+
+    #[test]
+    fn test_overrides_of_removed_package() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path().to_str().unwrap();
+        let package_json = serde_json::json!({
+            "dependencies": { "react": "^18.0.0" },
+            "overrides": { "react": "18.2.0", "left-pad": "1.3.0" }
+        });
+        fs::write(
+            temp_dir.path().join("package.json"),
+            package_json.to_string(),
+        )
+        .unwrap();
+
+        let required = get_required_dependencies(dir_path, false, None, &mut Warnings::default());
+
+        assert!(required.contains("react"));
+        assert!(!required.contains("left-pad"));
+        assert_eq!(
+            find_stale_overrides(dir_path, &package_json, &required),
+            vec!["left-pad".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_overrides_of_installed_transitive_package() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("node_modules/minimist")).unwrap();
+        let package_json = serde_json::json!({ "resolutions": { "**/minimist": "1.2.8" } });
+
+        let stale = find_stale_overrides(
+            temp_dir.path().to_str().unwrap(),
+            &package_json,
+            &HashSet::new(),
+        );
+
+        assert!(stale.is_empty());
+    }
+
+    #[test]
+    fn test_malformed_dependency_fields() {
+        let package_json = serde_json::json!({
+            "dependencies": [],
+            "devDependencies": "eslint",
+            "peerDependencies": { "react": "^18.0.0" }
+        });
+
+        assert_eq!(
+            malformed_dependency_fields(&package_json),
+            vec![
+                "`dependencies` in package.json is an array instead of an object; it is treated as empty.".to_string(),
+                "`devDependencies` in package.json is a string instead of an object; it is treated as empty.".to_string(),
+            ]
+        );
+        assert!(malformed_dependency_fields(&serde_json::json!({ "dependencies": {} })).is_empty());
+        assert!(malformed_dependency_fields(&serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_tooling_config_packages_codegen_yml_short_names() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("codegen.yml"),
+            "schema: schema.graphql\ngenerates:\n  src/types.ts:\n    plugins:\n      - typescript\n      - typescript-operations:\n          avoidOptionals: true\n  src/gql/:\n    preset: client\n",
+        )
+        .unwrap();
+        let candidates: HashSet<String> = [
+            "@graphql-codegen/typescript",
+            "@graphql-codegen/typescript-operations",
+            "@graphql-codegen/client-preset",
+            "@graphql-codegen/near-operation-file-preset",
+            "typescript",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        let found = tooling_config_packages(temp_dir.path().to_str().unwrap(), &candidates);
+
+        let expected: HashSet<String> = [
+            "@graphql-codegen/typescript",
+            "@graphql-codegen/typescript-operations",
+            "@graphql-codegen/client-preset",
+            "typescript",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_manifest_config_packages_babel_presets() {
+        let package_json: serde_json::Value = serde_json::from_str(
+            r#"{ "babel": {
+                     "presets": [["@babel/preset-env", { "targets": "defaults" }], "react-app"],
+                     "plugins": ["macros"]
+                 },
+                 "postcss": { "plugins": { "autoprefixer": {} } },
+                 "scripts": { "build": "babel-plugin-unused" } }"#,
+        )
+        .unwrap();
+        let candidates: HashSet<String> = [
+            "@babel/preset-env",
+            "babel-preset-react-app",
+            "babel-plugin-macros",
+            "babel-plugin-unused",
+            "autoprefixer",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        let found = manifest_config_packages(&package_json, &candidates);
+
+        let expected: HashSet<String> = [
+            "@babel/preset-env",
+            "babel-preset-react-app",
+            "babel-plugin-macros",
+            "autoprefixer",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_unlocked_dependencies() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().to_str().unwrap();
+        let dependencies = HashSet::from(["lodash".to_string(), "left-pad".to_string()]);
+        assert_eq!(unlocked_dependencies(dir, None, &dependencies), None);
+
+        fs::write(
+            temp_dir.path().join("pnpm-lock.yaml"),
+            "lockfileVersion: '9.0'\npackages:\n  lodash@4.17.21:\n    resolution: {integrity: sha512-x}\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            unlocked_dependencies(dir, None, &dependencies),
+            Some(("pnpm-lock.yaml", vec!["left-pad".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_is_lockfile_readable() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().to_str().unwrap();
+        assert!(!is_lockfile_readable(dir, None));
+
+        fs::write(
+            temp_dir.path().join("package-lock.json"),
+            r#"{ "packages": {} }"#,
+        )
+        .unwrap();
+        assert!(is_lockfile_readable(dir, None));
+
+        fs::write(
+            temp_dir.path().join("package-lock.json"),
+            r#"{ "packages": "#,
+        )
+        .unwrap();
+        assert!(!is_lockfile_readable(dir, None));
+
+        // Several lockfiles are never read
+        fs::write(temp_dir.path().join("package-lock.json"), "{}").unwrap();
+        fs::write(temp_dir.path().join("yarn.lock"), "").unwrap();
+        assert!(!is_lockfile_readable(dir, None));
+    }
 }
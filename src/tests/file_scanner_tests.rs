@@ -1,10 +1,19 @@
 #[cfg(test)]
 mod tests {
-    use crate::file_scanner::{get_typescript_unused_imports, normalize_path};
+    use crate::analysis::AnalyzeOptions;
+    use crate::file_scanner::{
+        IgnoreReason, TSC_CACHE_PATH, apply_pragmas, cached_tsc_diagnostics,
+        find_import_specifiers, find_imported_packages, find_style_block_packages,
+        find_stylesheet_packages, get_typescript_unused_imports, ignored_path, mdx_module_code,
+        normalize_path, run_tsc_diagnostics, scan_files, source_files, vendored_folder,
+    };
+    use crate::progress::NoProgress;
+    use crate::warnings::Warnings;
     use serde_json::json;
     use std::io::Write;
+    use std::time::Duration;
     use std::{
-        collections::HashSet,
+        collections::{BTreeMap, HashSet},
         error::Error,
         fs,
         path::{Path, PathBuf},
@@ -116,10 +125,984 @@ mod tests {
         writeln!(ts_file, "function main() {{}}").unwrap();
 
         // Execute the function and check results
-        let unused_imports = get_typescript_unused_imports(&temp_dir.path().to_str().unwrap());
-        let expected_imports = HashSet::from(["analytics".to_string()]);
+        let ts_file = normalize_path(&ts_file_path);
+        let unused_imports = get_typescript_unused_imports(
+            &temp_dir.path().to_str().unwrap(),
+            &[ts_file.clone()],
+            Duration::from_secs(60),
+            false,
+            &mut Warnings::default(),
+        )?;
+        let expected_imports = HashSet::from([(ts_file, "analytics".to_string())]);
         assert_eq!(unused_imports, expected_imports);
 
         Ok(())
     }
+
+    /// Creates a TypeScript project with one source file and a `tsc` stub running the given script.
+    #[cfg(unix)]
+    fn create_project_with_tsc_stub(script: &str) -> (TempDir, PathBuf, Vec<String>) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("tsconfig.json"), "{}").unwrap();
+        fs::write(
+            temp_dir.path().join("index.ts"),
+            "import analytics from 'analytics';",
+        )
+        .unwrap();
+        let tsc = temp_dir.path().join("tsc-stub");
+        fs::write(&tsc, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        fs::set_permissions(&tsc, fs::Permissions::from_mode(0o755)).unwrap();
+        let files = vec![normalize_path(&temp_dir.path().join("index.ts"))];
+        (temp_dir, tsc, files)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_tsc_diagnostics_times_out() {
+        let (temp_dir, tsc, files) = create_project_with_tsc_stub("sleep 5");
+
+        let started = std::time::Instant::now();
+        let result = run_tsc_diagnostics(
+            temp_dir.path().to_str().unwrap(),
+            &files,
+            tsc.to_str().unwrap(),
+            Duration::from_millis(200),
+            &mut Warnings::default(),
+        );
+
+        assert_eq!(result, Err("tsc did not finish within 200ms".to_string()));
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_tsc_diagnostics_within_timeout() {
+        let (temp_dir, tsc, files) = create_project_with_tsc_stub("exit 0");
+
+        let result = run_tsc_diagnostics(
+            temp_dir.path().to_str().unwrap(),
+            &files,
+            tsc.to_str().unwrap(),
+            Duration::from_secs(10),
+            &mut Warnings::default(),
+        );
+
+        assert_eq!(result, Ok(HashSet::new()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_tsc_diagnostics_maps_diagnostics_to_packages() {
+        let (temp_dir, tsc, files) = create_project_with_tsc_stub(
+            "cat <<'EOF'\nindex.ts(1,8): error TS6133: 'analytics' is declared but its value is never read.\nindex.ts(2,10): error TS6133: 'a' is declared but its value is never read.\nindex.ts(3,1): error TS6192: All imports in import declaration are unused.\nindex.ts(6,7): error TS6133: 'local' is declared but its value is never read.\nEOF\nexit 2",
+        );
+        fs::write(
+            temp_dir.path().join("index.ts"),
+            "import analytics from 'analytics';\nimport { a, b } from 'partial';\nimport {\n  c,\n} from 'whole';\nconst local = b;\n",
+        )
+        .unwrap();
+        let mut warnings = Warnings::default();
+
+        let result = run_tsc_diagnostics(
+            temp_dir.path().to_str().unwrap(),
+            &files,
+            tsc.to_str().unwrap(),
+            Duration::from_secs(10),
+            &mut warnings,
+        );
+
+        assert_eq!(
+            result,
+            Ok(HashSet::from([
+                (files[0].clone(), "analytics".to_string()),
+                (files[0].clone(), "whole".to_string()),
+            ]))
+        );
+        // Reporting diagnostics is not a failure
+        assert!(warnings.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_tsc_diagnostics_reports_spawn_failures() {
+        let (temp_dir, _, files) = create_project_with_tsc_stub("exit 0");
+
+        let result = run_tsc_diagnostics(
+            temp_dir.path().to_str().unwrap(),
+            &files,
+            "/nonexistent/tsc",
+            Duration::from_secs(10),
+            &mut Warnings::default(),
+        );
+
+        assert!(result.is_err_and(|err| err.starts_with("Failed to run /nonexistent/tsc")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cached_tsc_diagnostics_reuses_unchanged_inputs() {
+        // The stub counts its runs and reports an unused import
+        let (temp_dir, tsc, files) = create_project_with_tsc_stub(
+            "echo run >> runs.log\necho \"index.ts(1,8): error TS6133: 'analytics' is declared but its value is never read.\" >&2",
+        );
+        let root = temp_dir.path().to_str().unwrap();
+        let tsc = tsc.to_str().unwrap();
+        let runs = || fs::read_to_string(temp_dir.path().join("runs.log")).unwrap_or_default();
+
+        let first = cached_tsc_diagnostics(
+            root,
+            &files,
+            tsc,
+            Duration::from_secs(10),
+            &mut Warnings::default(),
+        );
+        assert_eq!(runs().lines().count(), 1);
+        assert!(first.as_ref().is_ok_and(|unused| !unused.is_empty()));
+        assert!(temp_dir.path().join(TSC_CACHE_PATH).exists());
+
+        // Unchanged TypeScript files reuse the cached diagnostics without running tsc
+        let second = cached_tsc_diagnostics(
+            root,
+            &files,
+            tsc,
+            Duration::from_secs(10),
+            &mut Warnings::default(),
+        );
+        assert_eq!(runs().lines().count(), 1);
+        assert_eq!(second, first);
+
+        // Editing a TypeScript file runs tsc again
+        fs::write(temp_dir.path().join("index.ts"), "import 'analytics';").unwrap();
+        cached_tsc_diagnostics(
+            root,
+            &files,
+            tsc,
+            Duration::from_secs(10),
+            &mut Warnings::default(),
+        )
+        .unwrap();
+        assert_eq!(runs().lines().count(), 2);
+    }
+
+    #[test]
+    fn test_find_imported_packages_side_effect_subpath_import() {
+        // Bare side-effect import of a subpath, without a trailing semicolon
+        let content = "import 'core-js/stable'\nimport \"regenerator-runtime/runtime\";";
+
+        assert_eq!(
+            find_imported_packages(content),
+            HashSet::from(["core-js".to_string(), "regenerator-runtime".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_find_imported_packages_subpath_does_not_match_prefix() {
+        // `core-js-compat` is a different package and must not count for `core-js`
+        let content = "import 'core-js-compat/data';";
+
+        assert_eq!(
+            find_imported_packages(content),
+            HashSet::from(["core-js-compat".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_find_imported_packages_scoped_subpath() {
+        let dependencies = HashSet::from(["@radix-ui/react-dialog".to_string()]);
+
+        for content in [
+            "import * as Dialog from '@radix-ui/react-dialog';",
+            "import { Root } from '@radix-ui/react-dialog/dist/index';",
+            "const dialog = require(\"@radix-ui/react-dialog/dist/index.js\");",
+        ] {
+            assert_eq!(find_imported_packages(content), dependencies, "{}", content);
+        }
+    }
+
+    #[test]
+    fn test_find_imported_packages_require_expression_forms() {
+        let dependencies = HashSet::from(["pkg".to_string()]);
+
+        // Property access, calls and destructuring after `require(...)` do not affect the match
+        for content in [
+            "const x = require('pkg').default;",
+            "const app = require('pkg')();",
+            "const {a} = require('pkg');",
+            "module.exports = { plugins: [require(\"pkg\")({ strict: true })] };",
+        ] {
+            assert_eq!(find_imported_packages(content), dependencies, "{}", content);
+        }
+    }
+
+    #[test]
+    fn test_find_imported_packages_require_context() {
+        // webpack's `require.context` takes a directory, never a package
+        let content = "const icons = require.context('./icons', true, /\\.svg$/);\nconst pages = require.context(`./pages`, false);\nconst req = require\n  .context(\"../locales\", true, /\\.json$/);";
+
+        assert!(find_imported_packages(content).is_empty());
+    }
+
+    #[test]
+    fn test_find_imported_packages_require_concatenation() {
+        let content = "const merge = require('lodash' + '/merge');\nconst locale = require(\"date-fns/locale/\" + lang);\nconst plugin = require('eslint-plugin-' + name);\nconst mod = require(base + '/index');\nconst local = require('./locales/' + lang + '.json');";
+
+        assert_eq!(
+            find_imported_packages(content),
+            HashSet::from(["lodash".to_string(), "date-fns".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_apply_pragmas() {
+        let content = "// cnp-keep lodash, 'dayjs/plugin/utc'\n/* cnp-keep react-dom */\n<!-- cnp-keep \"vue-router\" -->\n// cnp-ignore-next-line\nimport axios from 'axios';\nimport zod from 'zod';";
+
+        let (content, kept) = apply_pragmas(content);
+        assert_eq!(
+            kept,
+            HashSet::from([
+                "lodash".to_string(),
+                "dayjs".to_string(),
+                "react-dom".to_string(),
+                "vue-router".to_string(),
+            ])
+        );
+        assert_eq!(
+            find_imported_packages(&content),
+            HashSet::from(["zod".to_string()])
+        );
+
+        let (content, kept) =
+            apply_pragmas("// cnp-disable\nimport zod from 'zod';\n// cnp-keep lodash");
+        assert!(find_imported_packages(&content).is_empty());
+        assert_eq!(kept, HashSet::from(["lodash".to_string()]));
+    }
+
+    #[test]
+    fn test_apply_pragmas_ignores_unknown_pragma_names() {
+        let content =
+            "// cnp-disable-next-line\n/* cnp-keep-alive lodash */\nimport zod from 'zod';";
+
+        let (content, kept) = apply_pragmas(content);
+        assert!(kept.is_empty());
+        assert_eq!(
+            find_imported_packages(&content),
+            HashSet::from(["zod".to_string()])
+        );
+
+        // A pragma may end right at the end of its comment
+        let (content, _) = apply_pragmas("import zod from 'zod';\n/* cnp-disable*/");
+        assert!(find_imported_packages(&content).is_empty());
+    }
+
+    #[test]
+    fn test_scan_files_keep_pragma_only_when_enabled() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "// cnp-keep lodash\nconsole.log('loaded at runtime');",
+        )
+        .unwrap();
+        let dependencies = HashSet::from(["lodash".to_string()]);
+        let options = AnalyzeOptions::new(temp_dir.path().to_str().unwrap());
+
+        let result = scan_files(&options, &dependencies, &NoProgress);
+        assert!(result.used_packages.is_empty());
+
+        let result = scan_files(&options.pragmas(true), &dependencies, &NoProgress);
+        assert_eq!(result.used_packages, dependencies);
+    }
+
+    #[test]
+    fn test_mdx_module_code() {
+        let content = "---\ntitle: Using lodash\n---\n\nimport { Button } from '@acme/ui';\nimport Chart from \"recharts\";\n\nexport const meta = { tags: ['docs'] };\n\n# Migrating\n\nWe no longer import debounce from 'lodash' anywhere.\nimport the date helpers from 'dayjs' instead.\n\n```js\nimport axios from 'axios';\n```\n\n<Button>Click</Button>\n<Chart />";
+
+        assert_eq!(
+            mdx_module_code(content),
+            "import { Button } from '@acme/ui';\nimport Chart from \"recharts\";\nexport const meta = { tags: ['docs'] };"
+        );
+    }
+
+    #[test]
+    fn test_scan_files_mdx_ignores_prose_mentions() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("guide.mdx"),
+            "import { Card } from '@acme/ui';\n\n# Guide\n\nUnlike `require('lodash')`, the card needs no setup.\n\n~~~js\nimport dayjs from 'dayjs';\n~~~\n\n<Card />",
+        )
+        .unwrap();
+        let dependencies = HashSet::from([
+            "@acme/ui".to_string(),
+            "lodash".to_string(),
+            "dayjs".to_string(),
+        ]);
+        let options = AnalyzeOptions::new(temp_dir.path().to_str().unwrap());
+
+        let result = scan_files(&options, &dependencies, &NoProgress);
+        assert_eq!(
+            result.used_packages,
+            HashSet::from(["@acme/ui".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_find_imported_packages_combined_import_clauses() {
+        let content = "import React, { useState } from 'react';\nimport * as z from 'zod';\nimport type { Config } from 'jest';\nimport dayjs, * as all from \"dayjs\";";
+
+        assert_eq!(
+            find_imported_packages(content),
+            HashSet::from([
+                "react".to_string(),
+                "zod".to_string(),
+                "jest".to_string(),
+                "dayjs".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_find_imported_packages_dynamic_import_magic_comments() {
+        let content = r#"const Chart = lazy(() => import(/* webpackChunkName: "chart" */ 'chart.js'));
+const editor = await import(
+  /* webpackChunkName: "editor" */
+  /* webpackPrefetch: true */
+  "monaco-editor/esm/vs/editor/editor.api"
+);
+const locale = import(/* webpackMode: "lazy" */ `date-fns/locale/${lang}`);"#;
+
+        assert_eq!(
+            find_imported_packages(content),
+            HashSet::from([
+                "chart.js".to_string(),
+                "monaco-editor".to_string(),
+                "date-fns".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_find_imported_packages_test_mocks() {
+        let content = r#"jest.mock('axios');
+const { format } = jest.requireActual("date-fns");
+jest.doMock('@sentry/node', () => ({}));
+vi.mock('lodash/debounce');
+const actual = await vi.importActual('zod');
+jest.mock('./local-module');
+mock('not-a-mock');"#;
+
+        assert_eq!(
+            find_imported_packages(content),
+            HashSet::from([
+                "axios".to_string(),
+                "date-fns".to_string(),
+                "@sentry/node".to_string(),
+                "lodash".to_string(),
+                "zod".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_find_imported_packages_amd_and_systemjs() {
+        let content = r#"define(['jquery', 'require', 'exports', './local'], function ($) {});
+define("widgets/menu", ["underscore", "text!./menu.html"], function (_) {});
+require(["backbone/router"], function (Router) {});
+System.import('moment').then(function (moment) {});"#;
+
+        assert_eq!(
+            find_imported_packages(content),
+            HashSet::from([
+                "jquery".to_string(),
+                "underscore".to_string(),
+                "backbone".to_string(),
+                "moment".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_find_imported_packages_amd_define() {
+        let found =
+            find_imported_packages("define(['jquery'], function ($) { return $('#app'); });");
+
+        assert_eq!(found, HashSet::from(["jquery".to_string()]));
+    }
+
+    #[test]
+    fn test_find_imported_packages_scoped_sibling_does_not_match() {
+        // `@radix-ui/react-dialog-content` is a different package that shares the prefix
+        let content = "import { Content } from '@radix-ui/react-dialog-content';\nimport '@radix-ui/react-dialog-content/styles.css';";
+
+        assert_eq!(
+            find_imported_packages(content),
+            HashSet::from(["@radix-ui/react-dialog-content".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_find_imported_packages() {
+        let content = r#"
+            import React from 'react';
+            import merge from "lodash/merge";
+            import { Dialog } from '@radix-ui/react-dialog/dist/index';
+            import 'core-js/stable';
+            const axios = require('axios');
+            import fs from 'fs';
+            import path from 'node:path';
+            import local from './local';
+            import abs from '/abs/path';
+        "#;
+
+        let found = find_imported_packages(content);
+        let expected = HashSet::from([
+            "react".to_string(),
+            "lodash".to_string(),
+            "@radix-ui/react-dialog".to_string(),
+            "core-js".to_string(),
+            "axios".to_string(),
+            "fs".to_string(),
+        ]);
+
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_find_imported_packages_create_require() {
+        let content = r#"
+            import { createRequire } from 'node:module';
+            const require = createRequire(import.meta.url);
+            const yaml = require('js-yaml');
+        "#;
+
+        let found = find_imported_packages(content);
+
+        assert_eq!(found, HashSet::from(["js-yaml".to_string()]));
+    }
+
+    #[test]
+    fn test_find_imported_packages_create_require_alias() {
+        let content = r#"
+            import module from 'node:module';
+            const req = module.createRequire(import.meta.url);
+            const yaml = req("js-yaml");
+            const pkg = req('./package.json');
+            const other = prereq('not-a-require');
+        "#;
+
+        let found = find_imported_packages(content);
+
+        assert_eq!(found, HashSet::from(["js-yaml".to_string()]));
+    }
+
+    #[test]
+    fn test_find_import_specifiers_keeps_specifiers_whole() {
+        let content = r#"
+            import { Button } from '@app/ui/button';
+            const local = require('./local');
+            const locale = require(`date-fns/locale/${lang}`);
+            jest.mock('ds/theme');
+        "#;
+
+        assert_eq!(
+            find_import_specifiers(content),
+            vec!["@app/ui/button", "./local", "ds/theme"]
+        );
+    }
+
+    #[test]
+    fn test_find_imported_packages_several_create_require_bindings() {
+        let content = r#"
+            const req = createRequire(import.meta.url);
+            let load = module.createRequire(__filename);
+            const yaml = req('js-yaml'), toml = load("@iarna/toml/parse");
+            const other = reqs('not-a-require');
+        "#;
+
+        assert_eq!(
+            find_imported_packages(content),
+            HashSet::from(["js-yaml".to_string(), "@iarna/toml".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_find_imported_packages_call_without_create_require() {
+        let content = "const value = req('not-a-package');";
+
+        assert!(find_imported_packages(content).is_empty());
+    }
+
+    #[test]
+    fn test_find_imported_packages_subpath_and_scoped_imports() {
+        let content = r#"
+            import 'core-js/stable';
+            import merge from "lodash/merge";
+            const helpers = require('@babel/runtime/helpers/interopRequireDefault');
+            import { format } from 'date-fns/format';
+        "#;
+        let found = find_imported_packages(content);
+
+        // Subpaths count for their package, never for a package sharing its prefix
+        let expected: HashSet<String> = ["core-js", "lodash", "@babel/runtime", "date-fns"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_find_imported_packages_ignores_name_outside_imports() {
+        assert!(find_imported_packages("// built with react in mind").is_empty());
+    }
+
+    #[test]
+    fn test_find_imported_packages_template_literals() {
+        let content = r#"
+            const locale = require(`date-fns/locale/${lang}`);
+            const helper = require(someVar);
+            const mod = await import(`${name}`);
+            // lodash and moment are only mentioned, never resolvably imported
+        "#;
+        let found = find_imported_packages(content);
+
+        assert_eq!(found, HashSet::from(["date-fns".to_string()]));
+    }
+
+    #[test]
+    fn test_find_imported_packages_framework_subpaths() {
+        let content = r#"
+            import Image from 'next/image';
+            import { Inter } from 'next/font/google';
+            import { useRouter } from "next/navigation";
+        "#;
+
+        assert_eq!(
+            find_imported_packages(content),
+            HashSet::from(["next".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_find_imported_packages_backtick_specifiers() {
+        let content = r#"
+            import React from `react`;
+            import { merge } from `lodash/merge`;
+            import `core-js/stable`;
+            const axios = require(`axios`);
+            const chart = await import(`chart.js`);
+            import local from `./local`;
+        "#;
+
+        let expected: HashSet<String> = ["react", "lodash", "core-js", "axios", "chart.js"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(find_imported_packages(content), expected);
+    }
+
+    #[test]
+    fn test_find_imported_packages_backtick_interpolation_is_not_static() {
+        // Only calls resolve an interpolated template, and only from its static package prefix
+        let content = r#"
+            import widget from `${vendor}-widget`;
+            import theme from `theme-${name}`;
+            const locale = require(`date-fns/locale/${lang}`);
+        "#;
+
+        assert_eq!(
+            find_imported_packages(content),
+            HashSet::from(["date-fns".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_scan_files_records_ignore_reasons() {
+        let temp_dir = TempDir::new().unwrap();
+        let module_dir = temp_dir.path().join("node_modules").join("lodash");
+        fs::create_dir_all(&module_dir).unwrap();
+        fs::write(module_dir.join("index.js"), "module.exports = {};").unwrap();
+        fs::write(temp_dir.path().join("index.js"), "require('lodash');").unwrap();
+        std::os::unix::fs::symlink(
+            temp_dir.path().join("index.js"),
+            temp_dir.path().join("link.js"),
+        )
+        .unwrap();
+
+        let result = scan_files(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &HashSet::new(),
+            &NoProgress,
+        );
+
+        let module_file = normalize_path(&module_dir.join("index.js"));
+        assert_eq!(
+            result.ignore_reasons.get(&module_file),
+            Some(&IgnoreReason::Folder("node_modules".to_string()))
+        );
+        let link = temp_dir.path().join("link.js").display().to_string();
+        assert_eq!(
+            result.ignore_reasons.get(&link),
+            Some(&IgnoreReason::Symlink)
+        );
+        assert_eq!(result.explored_files.len(), 1);
+    }
+
+    #[test]
+    fn test_scan_files_commonjs_and_esm_module_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("server.cjs"),
+            "const express = require('express');\nconst chalk = await import('chalk');",
+        )
+        .unwrap();
+        fs::write(
+            root.join("client.mjs"),
+            "import { z } from 'zod';\nconst { default: ky } = await import('ky');",
+        )
+        .unwrap();
+        let dependencies: HashSet<String> = ["chalk", "express", "ky", "lodash", "zod"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let result = scan_files(
+            &AnalyzeOptions::new(root.to_str().unwrap()),
+            &dependencies,
+            &NoProgress,
+        );
+
+        let mut used: Vec<&str> = result.used_packages.iter().map(String::as_str).collect();
+        used.sort();
+        assert_eq!(used, vec!["chalk", "express", "ky", "zod"]);
+        assert_eq!(
+            result.dependency_files["express"],
+            vec![normalize_path(&root.join("server.cjs"))]
+        );
+        assert_eq!(
+            result.dependency_files["zod"],
+            vec![normalize_path(&root.join("client.mjs"))]
+        );
+    }
+
+    #[test]
+    fn test_find_stylesheet_packages() {
+        let content = r#"@import "tailwindcss";
+@import "./components.css";
+@import "theme.css";
+@import url("~@fontsource/inter/index.css");
+@config "./tailwind.config.js";
+@plugin "@tailwindcss/typography";
+@plugin 'daisyui' {
+  themes: light;
+}"#;
+
+        assert_eq!(
+            find_stylesheet_packages(content),
+            HashSet::from([
+                "tailwindcss".to_string(),
+                "@fontsource/inter".to_string(),
+                "@tailwindcss/typography".to_string(),
+                "daisyui".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_scan_files_package_names_prefixing_each_other() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("index.js"),
+            "// Renders the react app\nimport { createRoot } from 'react-dom/client';\nconst { render } = require(\"react-dom\");",
+        )
+        .unwrap();
+        let dependencies: HashSet<String> = ["react", "react-dom", "react-router"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let result = scan_files(
+            &AnalyzeOptions::new(root.to_str().unwrap()),
+            &dependencies,
+            &NoProgress,
+        );
+
+        // `react` and `react-router` only occur as prefixes of `react-dom` or in a comment
+        assert_eq!(
+            result.used_packages,
+            HashSet::from(["react-dom".to_string()])
+        );
+        let unused: HashSet<_> = dependencies
+            .difference(&result.used_packages)
+            .cloned()
+            .collect();
+        assert_eq!(
+            unused,
+            HashSet::from(["react".to_string(), "react-router".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_scan_files_tailwind_plugin_in_stylesheet() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("app.css"),
+            "@import \"tailwindcss\";\n@config \"./tailwind.config.js\";\n@plugin \"@tailwindcss/typography\";",
+        )
+        .unwrap();
+        fs::write(
+            root.join("tailwind.config.js"),
+            "import forms from '@tailwindcss/forms';\nexport default { plugins: [forms] };",
+        )
+        .unwrap();
+        let dependencies: HashSet<String> = [
+            "@tailwindcss/forms",
+            "@tailwindcss/typography",
+            "tailwindcss",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        let result = scan_files(
+            &AnalyzeOptions::new(root.to_str().unwrap()),
+            &dependencies,
+            &NoProgress,
+        );
+
+        assert_eq!(result.used_packages, dependencies);
+        assert_eq!(
+            result.dependency_files["@tailwindcss/typography"],
+            vec![normalize_path(&root.join("app.css"))]
+        );
+    }
+
+    #[test]
+    fn test_scan_files_records_each_ignored_path_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        // `dir.js` is a directory matched by the `*.js` pattern, holding files matched by the
+        // `*.js` and `*.ts` patterns
+        let ignored_dir = root.join("node_modules").join("dir.js");
+        fs::create_dir_all(&ignored_dir).unwrap();
+        fs::write(ignored_dir.join("a.js"), "").unwrap();
+        fs::write(ignored_dir.join("b.ts"), "").unwrap();
+        // A non-ignored directory named like a source file is neither explored nor ignored
+        fs::create_dir_all(root.join("weird.ts")).unwrap();
+        fs::write(root.join("weird.ts").join("c.ts"), "").unwrap();
+        std::os::unix::fs::symlink(ignored_dir.join("a.js"), root.join("link.js")).unwrap();
+
+        let result = scan_files(
+            &AnalyzeOptions::new(root.to_str().unwrap()),
+            &HashSet::new(),
+            &NoProgress,
+        );
+
+        let unique: HashSet<&String> = result.ignored_files.iter().collect();
+        assert_eq!(unique.len(), result.ignored_files.len());
+        assert_eq!(result.ignored_files.len(), 4);
+        assert_eq!(result.ignore_reasons.len(), 4);
+        assert_eq!(
+            result.explored_files,
+            vec![normalize_path(&root.join("weird.ts").join("c.ts"))]
+        );
+    }
+
+    #[test]
+    fn test_source_files_enumerates_each_file_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        for file in [
+            "index.js",
+            "src/app.tsx",
+            "src/util.mjs",
+            "README.md",
+            "src/data.json",
+        ] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let mut files: Vec<PathBuf> =
+            source_files(&AnalyzeOptions::new(root.to_str().unwrap())).collect();
+        files.sort();
+
+        assert_eq!(
+            files,
+            vec![
+                root.join("index.js"),
+                root.join("src/app.tsx"),
+                root.join("src/util.mjs"),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_source_files_does_not_follow_symlink_cycles() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/app.js"), "").unwrap();
+        std::os::unix::fs::symlink(root, root.join("src/loop.js")).unwrap();
+
+        let files: Vec<PathBuf> =
+            source_files(&AnalyzeOptions::new(root.to_str().unwrap())).collect();
+
+        assert_eq!(
+            files,
+            vec![root.join("src/app.js"), root.join("src/loop.js")]
+        );
+    }
+
+    #[test]
+    fn test_source_files_respects_max_depth() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/components")).unwrap();
+        for file in ["index.js", "src/app.tsx", "src/components/button.tsx"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let shallow: Vec<PathBuf> =
+            source_files(&AnalyzeOptions::new(root.to_str().unwrap()).max_depth(Some(1))).collect();
+        let two_levels: Vec<PathBuf> =
+            source_files(&AnalyzeOptions::new(root.to_str().unwrap()).max_depth(Some(2))).collect();
+
+        assert_eq!(shallow, vec![root.join("index.js")]);
+        assert_eq!(
+            two_levels,
+            vec![root.join("index.js"), root.join("src/app.tsx")]
+        );
+    }
+
+    #[test]
+    fn test_vendored_folder() {
+        let vendored = ["vendor", "third_party", "libs/legacy/"];
+
+        assert_eq!(
+            vendored_folder(Path::new("packages/ui/vendor/jquery.js"), &vendored),
+            Some(IgnoreReason::Vendored("vendor".to_string()))
+        );
+        assert_eq!(
+            vendored_folder(Path::new("third_party/lib.js"), &vendored),
+            Some(IgnoreReason::Vendored("third_party".to_string()))
+        );
+        assert_eq!(
+            vendored_folder(Path::new("libs/legacy/index.js"), &vendored),
+            Some(IgnoreReason::Vendored("libs/legacy".to_string()))
+        );
+        assert_eq!(
+            vendored_folder(Path::new("src/libs/legacy/index.js"), &vendored),
+            None
+        );
+        assert_eq!(
+            vendored_folder(Path::new("src/vendors.js"), &vendored),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ignored_path() {
+        let patterns = ["src/generated/", "**/*.stories.js", "scripts/*.js"];
+
+        assert_eq!(
+            ignored_path(Path::new("src/generated/api/client.js"), &patterns),
+            Some(IgnoreReason::Path("src/generated/".to_string()))
+        );
+        assert_eq!(
+            ignored_path(Path::new("src/ui/button.stories.js"), &patterns),
+            Some(IgnoreReason::Path("**/*.stories.js".to_string()))
+        );
+        assert_eq!(
+            ignored_path(Path::new("scripts/build.js"), &patterns),
+            Some(IgnoreReason::Path("scripts/*.js".to_string()))
+        );
+        // `*` does not cross directories
+        assert_eq!(
+            ignored_path(Path::new("scripts/release/publish.js"), &patterns),
+            None
+        );
+        assert_eq!(ignored_path(Path::new("src/generated.js"), &patterns), None);
+    }
+
+    #[test]
+    fn test_scan_files_stats_per_extension() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("index.js"), "import _ from 'lodash';").unwrap();
+        fs::write(root.join("util.js"), "const x = 1;").unwrap();
+        fs::write(
+            root.join("app.ts"),
+            "import React from 'react';\nimport { z } from 'zod';",
+        )
+        .unwrap();
+        fs::write(root.join("app.css"), "@import \"tailwindcss\";").unwrap();
+        let dependencies: HashSet<String> = ["lodash", "react", "tailwindcss"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let result = scan_files(
+            &AnalyzeOptions::new(root.to_str().unwrap()),
+            &dependencies,
+            &NoProgress,
+        );
+
+        let stats = &result.stats;
+        assert_eq!(
+            stats.files_per_extension,
+            BTreeMap::from([
+                ("css".to_string(), 1),
+                ("js".to_string(), 2),
+                ("ts".to_string(), 1),
+            ])
+        );
+        assert_eq!(stats.imports, 4);
+        assert_eq!(stats.average_matches_per_file, 0.75);
+        let total_bytes: u64 = ["index.js", "util.js", "app.ts", "app.css"]
+            .iter()
+            .map(|file| fs::metadata(root.join(file)).unwrap().len())
+            .sum();
+        assert_eq!(stats.bytes_read, total_bytes);
+    }
+
+    #[test]
+    fn test_find_style_block_packages_svelte() {
+        let content = "<script>\n  export let name;\n</script>\n<h1>{name}</h1>\n<style lang=\"postcss\">\n  @import url(\"@fontsource/inter\");\n  @import \"theme.css\";\n</style>";
+
+        assert_eq!(
+            find_style_block_packages(content),
+            HashSet::from(["@fontsource/inter".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_scan_files_vue_style_block_import() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("App.vue"),
+            "<template>\n  <div class=\"container\" />\n</template>\n\n<script setup>\nimport { ref } from 'vue';\n</script>\n\n<style scoped>\n@import \"~bootstrap/dist/css/bootstrap.css\";\n</style>\n",
+        )
+        .unwrap();
+        let dependencies: HashSet<String> = ["bootstrap", "vue", "bulma"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let result = scan_files(
+            &AnalyzeOptions::new(root.to_str().unwrap()).extensions(["vue"]),
+            &dependencies,
+            &NoProgress,
+        );
+
+        assert_eq!(
+            result.used_packages,
+            HashSet::from(["bootstrap".to_string(), "vue".to_string()])
+        );
+        assert!(result.imported_packages.contains("bootstrap"));
+    }
 }
@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{AnalyzeOptions, analyze};
+    use crate::git::{dependency_ages, is_git_repository};
+    use crate::progress::NoProgress;
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    /// Runs a git command in the given directory, panicking on failure.
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git")
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_dependency_ages_in_git_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("package.json"),
+            r#"{ "dependencies": { "react": "^18.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(root.join("index.js"), "import React from 'react';").unwrap();
+        git(root, &["init", "-q"]);
+        git(root, &["add", "."]);
+        git(
+            root,
+            &[
+                "-c",
+                "user.name=cnp",
+                "-c",
+                "user.email=cnp@example.com",
+                "commit",
+                "-q",
+                "-m",
+                "init",
+            ],
+        );
+        let dir_path = root.to_str().unwrap();
+
+        let analysis = analyze(&AnalyzeOptions::new(dir_path), &NoProgress).unwrap();
+        let ages = dependency_ages(dir_path, &analysis.dependency_files);
+
+        assert!(is_git_repository(dir_path));
+        assert_eq!(ages.get("react"), Some(&0));
+    }
+
+    #[test]
+    fn test_dependency_ages_outside_git_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "dependencies": { "react": "^18.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.js"),
+            "import React from 'react';",
+        )
+        .unwrap();
+        let dir_path = temp_dir.path().to_str().unwrap();
+
+        let analysis = analyze(&AnalyzeOptions::new(dir_path), &NoProgress).unwrap();
+
+        assert!(!analysis.dependency_files.is_empty());
+        assert!(dependency_ages(dir_path, &analysis.dependency_files).is_empty());
+    }
+}
@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::dependency::read_cnpignore;
+    use crate::init::{init_project, starter_config};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_init_project_creates_files() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let files = init_project(temp_dir.path().to_str().unwrap(), false).unwrap();
+
+        assert_eq!(files, vec!["cnp.toml", ".cnpignore"]);
+        let config = fs::read_to_string(temp_dir.path().join("cnp.toml")).unwrap();
+        assert!(config.contains("node_modules"));
+        assert!(config.contains("strict_manifest = true"));
+        assert!(read_cnpignore(temp_dir.path().join(".cnpignore").to_str().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_init_project_does_not_overwrite() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("cnp.toml");
+        fs::write(&config_path, "strict_manifest = false\n").unwrap();
+        let dir_path = temp_dir.path().to_str().unwrap();
+
+        let result = init_project(dir_path, false);
+
+        assert!(result.unwrap_err().contains("`cnp.toml` already exists"));
+        assert_eq!(
+            fs::read_to_string(&config_path).unwrap(),
+            "strict_manifest = false\n"
+        );
+        assert!(!temp_dir.path().join(".cnpignore").exists());
+
+        init_project(dir_path, true).unwrap();
+        assert!(
+            fs::read_to_string(&config_path)
+                .unwrap()
+                .contains("strict_manifest = true")
+        );
+    }
+
+    #[test]
+    fn test_starter_config_matches_defaults() {
+        let config: Config = toml::from_str(&starter_config()).unwrap();
+        let defaults = Config::default();
+
+        assert_eq!(config.side_effect_packages, defaults.side_effect_packages);
+        assert_eq!(config.strict_manifest, defaults.strict_manifest);
+        assert_eq!(config.runtime_required, defaults.runtime_required);
+        assert_eq!(config.keep_types_node, defaults.keep_types_node);
+        assert_eq!(config.workspace_packages, defaults.workspace_packages);
+    }
+}
@@ -1,8 +1,36 @@
 #[cfg(test)]
+mod analysis_tests;
+#[cfg(all(test, feature = "cli"))]
+mod cli_tests;
+#[cfg(test)]
+mod compare_tests;
+#[cfg(test)]
 mod config_tests;
 #[cfg(test)]
 mod dependency_tests;
 #[cfg(test)]
 mod file_scanner_tests;
 #[cfg(test)]
+mod git_tests;
+#[cfg(test)]
+mod init_tests;
+#[cfg(test)]
 mod main_tests;
+#[cfg(test)]
+mod package_manager_tests;
+#[cfg(test)]
+mod report_tests;
+#[cfg(test)]
+mod resolve_tests;
+#[cfg(test)]
+mod tarball_tests;
+#[cfg(all(test, feature = "cli"))]
+mod uninstall_tests;
+#[cfg(test)]
+mod utils_tests;
+#[cfg(test)]
+mod warnings_tests;
+#[cfg(all(test, feature = "cli"))]
+mod watch_tests;
+#[cfg(test)]
+mod workspace_tests;
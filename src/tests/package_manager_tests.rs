@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod tests {
+    use crate::package_manager::{
+        detect_package_manager, package_manager_ls, parse_package_manager_ls,
+    };
+    use std::collections::HashSet;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_package_manager() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().to_str().unwrap();
+        assert_eq!(detect_package_manager(dir), "npm");
+
+        fs::write(temp_dir.path().join("pnpm-lock.yaml"), "").unwrap();
+        assert_eq!(detect_package_manager(dir), "pnpm");
+    }
+
+    #[test]
+    fn test_parse_package_manager_ls_npm() {
+        // Captured from `npm ls --all --json`
+        let output = r#"{
+  "version": "1.0.0",
+  "name": "app",
+  "dependencies": {
+    "@vercel/analytics": {
+      "version": "1.3.1",
+      "resolved": "https://registry.npmjs.org/@vercel/analytics/-/analytics-1.3.1.tgz",
+      "overridden": false,
+      "dependencies": {
+        "server-only": { "version": "0.0.1", "resolved": "https://registry.npmjs.org/server-only/-/server-only-0.0.1.tgz", "overridden": false }
+      }
+    },
+    "react": {
+      "version": "18.3.1",
+      "resolved": "https://registry.npmjs.org/react/-/react-18.3.1.tgz",
+      "overridden": false,
+      "dependencies": {
+        "loose-envify": {
+          "version": "1.4.0",
+          "resolved": "https://registry.npmjs.org/loose-envify/-/loose-envify-1.4.0.tgz",
+          "overridden": false,
+          "dependencies": {
+            "js-tokens": { "version": "4.0.0", "resolved": "https://registry.npmjs.org/js-tokens/-/js-tokens-4.0.0.tgz", "overridden": false }
+          }
+        }
+      }
+    },
+    "react-dom": {
+      "version": "18.3.1",
+      "overridden": false,
+      "dependencies": {
+        "loose-envify": { "version": "1.4.0" },
+        "react": { "version": "18.3.1" }
+      }
+    }
+  }
+}"#;
+
+        let installed = parse_package_manager_ls(output).unwrap();
+
+        let expected: HashSet<String> = [
+            "@vercel/analytics",
+            "server-only",
+            "react",
+            "loose-envify",
+            "js-tokens",
+            "react-dom",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        assert_eq!(installed, expected);
+    }
+
+    #[test]
+    fn test_parse_package_manager_ls_pnpm() {
+        // Captured from `pnpm ls --json --depth Infinity`
+        let output = r#"[
+  {
+    "name": "app",
+    "version": "1.0.0",
+    "path": "/home/user/app",
+    "private": false,
+    "dependencies": {
+      "zod": { "from": "zod", "version": "3.23.8", "resolved": "https://registry.npmjs.org/zod/-/zod-3.23.8.tgz", "path": "/home/user/app/node_modules/.pnpm/zod@3.23.8/node_modules/zod" }
+    },
+    "devDependencies": {
+      "vitest": {
+        "from": "vitest",
+        "version": "1.6.0",
+        "dependencies": {
+          "tinypool": { "from": "tinypool", "version": "0.8.4" }
+        }
+      }
+    }
+  }
+]"#;
+
+        let installed = parse_package_manager_ls(output).unwrap();
+
+        let expected: HashSet<String> = ["zod", "vitest", "tinypool"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(installed, expected);
+    }
+
+    #[test]
+    fn test_parse_package_manager_ls_invalid() {
+        assert!(parse_package_manager_ls("").is_err());
+        assert!(parse_package_manager_ls("npm ERR! code ELSPROBLEMS").is_err());
+    }
+
+    #[test]
+    fn test_package_manager_ls_requires_node_modules() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let err = package_manager_ls(temp_dir.path().to_str().unwrap()).unwrap_err();
+
+        assert!(err.contains("node_modules"));
+    }
+}
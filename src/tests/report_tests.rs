@@ -0,0 +1,522 @@
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{Analysis, AnalyzeOptions, analyze};
+    use crate::progress::NoProgress;
+    use crate::report::{
+        Metric, OutputFormat, ReportSections, RunCounts, Summary, SummaryTemplate, format_count,
+        junit_cases, manifest_display_path, write_dependency_report, write_github_annotations,
+        write_html_report, write_junit_report, write_toml_report,
+    };
+    use crate::warnings::Warnings;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_format_count_console() {
+        let summary = Summary {
+            total: 3,
+            used: 2,
+            unused: 1,
+            missing: 4,
+        };
+
+        assert_eq!(
+            format_count(&summary, OutputFormat::Console),
+            "total=3 used=2 unused=1 missing=4"
+        );
+    }
+
+    #[test]
+    fn test_format_count_json() {
+        let summary = Summary {
+            total: 3,
+            used: 2,
+            unused: 1,
+            missing: 4,
+        };
+
+        let json: serde_json::Value =
+            serde_json::from_str(&format_count(&summary, OutputFormat::Json)).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({"total": 3, "used": 2, "unused": 1, "missing": 4})
+        );
+    }
+
+    #[test]
+    fn test_format_count_matches_fixture() {
+        let temp_dir = TempDir::new().unwrap();
+        for file in ["package.json", "index.js", "aliased.js"] {
+            fs::copy(
+                format!("test_fixtures/{}", file),
+                temp_dir.path().join(file),
+            )
+            .unwrap();
+        }
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert_eq!(
+            format_count(&Summary::from_analysis(&analysis), OutputFormat::Console),
+            "total=3 used=3 unused=0 missing=0"
+        );
+    }
+
+    #[test]
+    fn test_summary_template_renders_fixture_counts() {
+        let temp_dir = TempDir::new().unwrap();
+        for file in ["package.json", "index.js", "aliased.js"] {
+            fs::copy(
+                format!("test_fixtures/{}", file),
+                temp_dir.path().join(file),
+            )
+            .unwrap();
+        }
+        let analysis = analyze(
+            &AnalyzeOptions::new(temp_dir.path().to_str().unwrap()),
+            &NoProgress,
+        )
+        .unwrap();
+        let mut counts = RunCounts::default();
+        counts.add(&analysis);
+
+        let template: SummaryTemplate =
+            "unused={{unused}} missing={{missing}} in {{files}} files ({{ used }}/{{total}} used)"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            template.render(&counts),
+            "unused=0 missing=0 in 2 files (3/3 used)"
+        );
+    }
+
+    #[test]
+    fn test_summary_template_sums_projects() {
+        let counts = RunCounts {
+            projects: 2,
+            unused: 1,
+            unused_dev: 4,
+            ignored: 7,
+            ..RunCounts::default()
+        };
+        let template: SummaryTemplate =
+            "{{projects}} projects: {{unused}}+{{unused_dev}}, {{ignored}} ignored {}"
+                .parse()
+                .unwrap();
+
+        assert_eq!(template.render(&counts), "2 projects: 1+4, 7 ignored {}");
+    }
+
+    #[test]
+    fn test_summary_template_rejects_unknown_variables() {
+        assert_eq!(
+            "unused={{unsued}}".parse::<SummaryTemplate>(),
+            Err("Error: Unknown summary variable `unsued`. Expected one of: projects, total, used, unused, unused_dev, missing, files, ignored.".to_string())
+        );
+        assert_eq!(
+            "unused={{unused".parse::<SummaryTemplate>(),
+            Err("Error: Unclosed `{{` in summary line format `unused={{unused`.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_output_format_from_str() {
+        assert_eq!("console".parse(), Ok(OutputFormat::Console));
+        assert_eq!("json".parse(), Ok(OutputFormat::Json));
+        assert_eq!("github".parse(), Ok(OutputFormat::Github));
+        assert_eq!("junit".parse(), Ok(OutputFormat::Junit));
+        assert_eq!("html".parse(), Ok(OutputFormat::Html));
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_write_dependency_report_lifecycle_scripts() {
+        let analysis = Analysis {
+            dependencies: ["esbuild".to_string()].into_iter().collect(),
+            unused_dependencies: vec!["esbuild".to_string()],
+            lifecycle_scripts: [("esbuild".to_string(), vec!["postinstall".to_string()])]
+                .into_iter()
+                .collect(),
+            ..Analysis::default()
+        };
+
+        let mut out = Vec::new();
+        write_dependency_report(
+            &mut out,
+            ".",
+            &analysis,
+            ReportSections::All,
+            &Metric::DEFAULT,
+        )
+        .unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        assert!(output.contains("Unused Dependencies With Install Scripts:"));
+        assert!(output.contains("esbuild"));
+        assert!(output.contains("(postinstall)"));
+    }
+
+    #[test]
+    fn test_write_github_annotations() {
+        let manifest = "{\n  \"name\": \"app\",\n  \"dependencies\": {\n    \"lodash\": \"^4.17.21\",\n    \"react\": \"^18.0.0\"\n  }\n}\n";
+        let analysis = Analysis {
+            unused_dependencies: vec!["lodash".to_string()],
+            missing_dependencies: vec!["axios".to_string()],
+            ..Analysis::default()
+        };
+
+        let mut out = Vec::new();
+        write_github_annotations(&mut out, ".", manifest, &analysis).unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        assert_eq!(
+            output,
+            "::error file=package.json,line=4::Unused dependency 'lodash'\n\
+             ::warning file=package.json::Missing dependency 'axios'\n"
+        );
+    }
+
+    #[test]
+    fn test_write_github_annotations_escapes_commands() {
+        let mut warnings = Warnings::default();
+        warnings.push("First line\nsecond line at 100%".to_string());
+        let analysis = Analysis {
+            warnings,
+            ..Analysis::default()
+        };
+
+        let mut out = Vec::new();
+        write_github_annotations(&mut out, "apps/a,b:c", "{}", &analysis).unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        assert_eq!(
+            output,
+            "::warning file=apps/a%2Cb%3Ac/package.json::First line%0Asecond line at 100%25\n"
+        );
+    }
+
+    #[test]
+    fn test_manifest_display_path() {
+        assert_eq!(manifest_display_path("."), "package.json");
+        assert_eq!(manifest_display_path("apps/web"), "apps/web/package.json");
+    }
+
+    #[test]
+    fn test_write_junit_report() {
+        let analysis = Analysis {
+            used_packages: ["react".to_string()].into_iter().collect(),
+            unused_dependencies: vec!["lodash".to_string(), "left-pad".to_string()],
+            missing_dependencies: vec!["<axios>".to_string()],
+            ..Analysis::default()
+        };
+
+        let mut out = Vec::new();
+        write_junit_report(&mut out, &[("apps/web", junit_cases(&analysis))]).unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        assert!(output.starts_with("<?xml"));
+        assert!(output.contains(r#"<testsuite name="apps/web" tests="4" failures="3">"#));
+        assert_eq!(output.matches("<testcase ").count(), 4);
+        assert_eq!(output.matches("<failure ").count(), 3);
+        assert!(output.contains(r#"<testcase classname="used" name="react"/>"#));
+        assert!(output.contains(r#"message="Unused dependency 'left-pad'""#));
+        assert!(output.contains(r#"name="&lt;axios&gt;""#));
+        assert!(output.trim_end().ends_with("</testsuites>"));
+    }
+
+    #[test]
+    fn test_write_junit_report_passes_without_failures() {
+        let analysis = Analysis {
+            used_packages: ["react".to_string()].into_iter().collect(),
+            ..Analysis::default()
+        };
+
+        let mut out = Vec::new();
+        write_junit_report(&mut out, &[(".", junit_cases(&analysis))]).unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        assert!(output.contains(r#"<testsuites name="cnp" tests="1" failures="0">"#));
+        assert_eq!(output.matches("<failure ").count(), 0);
+    }
+
+    #[test]
+    fn test_write_toml_report_parses_back() {
+        let analysis = Analysis {
+            dependencies: ["react", "zod", "lodash", "a\"b\\c"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            used_packages: ["react".to_string()].into_iter().collect(),
+            unused_dependencies: vec![
+                "zod".to_string(),
+                "lodash".to_string(),
+                "a\"b\\c".to_string(),
+            ],
+            dependency_files: [("react".to_string(), vec!["src/app.js".to_string()])]
+                .into_iter()
+                .collect(),
+            ..Analysis::default()
+        };
+
+        let mut out = Vec::new();
+        write_toml_report(&mut out, &[("apps/'web'", &analysis)], false).unwrap();
+        let report: toml::Value = toml::from_str(&String::from_utf8(out).unwrap()).unwrap();
+
+        assert_eq!(report["project"].as_str(), Some("apps/'web'"));
+        assert_eq!(report["summary"]["unused"].as_integer(), Some(3));
+        let names = |list: &str| -> Vec<String> {
+            report[list]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|package| package["name"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(names("unused"), vec!["a\"b\\c", "lodash", "zod"]);
+        assert_eq!(names("used"), vec!["react"]);
+        assert_eq!(
+            report["used"][0]["files"].as_array().unwrap()[0].as_str(),
+            Some("src/app.js")
+        );
+
+        let mut out = Vec::new();
+        write_toml_report(&mut out, &[("a", &analysis), ("b", &analysis)], true).unwrap();
+        let report: toml::Value = toml::from_str(&String::from_utf8(out).unwrap()).unwrap();
+        let projects = report["projects"].as_array().unwrap();
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[1]["project"].as_str(), Some("b"));
+    }
+
+    #[test]
+    fn test_format_count_toml() {
+        let summary = Summary {
+            total: 3,
+            used: 2,
+            unused: 1,
+            missing: 0,
+        };
+
+        assert_eq!(
+            format_count(&summary, OutputFormat::Toml),
+            "total = 3\nused = 2\nunused = 1\nmissing = 0"
+        );
+    }
+
+    #[test]
+    fn test_write_html_report() {
+        let analysis = Analysis {
+            dependencies: ["react", "lodash", "<script>alert(1)</script>"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            used_packages: ["react".to_string()].into_iter().collect(),
+            unused_dependencies: vec![
+                "lodash".to_string(),
+                "<script>alert(1)</script>".to_string(),
+            ],
+            missing_dependencies: vec!["a&b'c\"d".to_string()],
+            ..Analysis::default()
+        };
+
+        let mut out = Vec::new();
+        write_html_report(&mut out, &[("apps/<web>", &analysis)]).unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        assert!(output.starts_with("<!DOCTYPE html>"));
+        assert!(output.trim_end().ends_with("</html>"));
+        assert!(!output.contains("<link") && !output.contains("<script"));
+        assert!(output.contains("<summary>Unused dependencies (2)</summary>"));
+        assert!(output.contains("<li><code>lodash</code></li>"));
+        assert!(output.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(output.contains("a&amp;b&#39;c&quot;d"));
+        assert!(output.contains("<h2>apps/&lt;web&gt;</h2>"));
+        assert!(output.contains(r#"<td class="unused">2</td>"#));
+    }
+
+    #[test]
+    fn test_write_dependency_report_duplicate_versions() {
+        let analysis = Analysis {
+            duplicate_versions: [(
+                "lodash".to_string(),
+                vec!["3.10.1".to_string(), "4.17.21".to_string()],
+            )]
+            .into_iter()
+            .collect(),
+            ..Analysis::default()
+        };
+
+        let mut out = Vec::new();
+        write_dependency_report(
+            &mut out,
+            ".",
+            &analysis,
+            ReportSections::All,
+            &Metric::DEFAULT,
+        )
+        .unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        assert!(output.contains("Duplicate Packages:"));
+        assert!(output.contains("(3.10.1, 4.17.21)"));
+    }
+
+    #[test]
+    fn test_write_dependency_report_duplicate_purposes() {
+        let analysis = Analysis {
+            duplicate_purposes: vec![vec!["dayjs".to_string(), "moment".to_string()]],
+            ..Analysis::default()
+        };
+
+        let mut out = Vec::new();
+        write_dependency_report(
+            &mut out,
+            ".",
+            &analysis,
+            ReportSections::All,
+            &Metric::DEFAULT,
+        )
+        .unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        assert!(output.contains("Duplicate-Purpose Packages:"));
+        assert!(output.contains("dayjs"));
+        assert!(output.contains("moment"));
+    }
+
+    #[test]
+    fn test_write_dependency_report_broken_entry_points() {
+        let analysis = Analysis {
+            broken_entry_points: [("main".to_string(), "dist/index.js".to_string())]
+                .into_iter()
+                .collect(),
+            ..Analysis::default()
+        };
+
+        let mut out = Vec::new();
+        write_dependency_report(
+            &mut out,
+            ".",
+            &analysis,
+            ReportSections::All,
+            &Metric::DEFAULT,
+        )
+        .unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        assert!(output.contains("Broken Entry Points:"));
+        assert!(output.contains("main -> dist/index.js"));
+    }
+
+    #[test]
+    fn test_write_dependency_report_missing_script_commands() {
+        let analysis = Analysis {
+            missing_script_commands: [("eslint".to_string(), vec!["lint".to_string()])]
+                .into_iter()
+                .collect(),
+            ..Analysis::default()
+        };
+
+        let mut out = Vec::new();
+        write_dependency_report(
+            &mut out,
+            ".",
+            &analysis,
+            ReportSections::All,
+            &Metric::DEFAULT,
+        )
+        .unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        assert!(output.contains("Missing Script Commands:"));
+        assert!(output.contains("eslint"));
+        assert!(output.contains("(lint)"));
+    }
+
+    #[test]
+    fn test_write_dependency_report_sections() {
+        let analysis = Analysis {
+            dependencies: ["lodash".to_string(), "react".to_string()]
+                .into_iter()
+                .collect(),
+            used_packages: ["react".to_string()].into_iter().collect(),
+            unused_dependencies: vec!["lodash".to_string()],
+            unused_dev_dependencies: vec!["left-pad".to_string()],
+            ..Analysis::default()
+        };
+
+        let mut out = Vec::new();
+        write_dependency_report(
+            &mut out,
+            ".",
+            &analysis,
+            ReportSections::UsedOnly,
+            &Metric::DEFAULT,
+        )
+        .unwrap();
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.contains("Used Dependencies:"));
+        assert!(!output.contains("Unused Dependencies:"));
+        assert!(!output.contains("Unused Dev Dependencies:"));
+        assert!(!output.contains("No unused dependencies found!"));
+
+        let mut out = Vec::new();
+        write_dependency_report(
+            &mut out,
+            ".",
+            &analysis,
+            ReportSections::UnusedOnly,
+            &Metric::DEFAULT,
+        )
+        .unwrap();
+        let output = String::from_utf8(out).unwrap();
+        assert!(!output.contains("\nUsed Dependencies:"));
+        assert!(output.contains("Unused Dependencies:"));
+        assert!(output.contains("Unused Dev Dependencies:"));
+    }
+
+    #[test]
+    fn test_write_dependency_report_selected_metrics() {
+        let analysis = Analysis {
+            dependencies: ["lodash".to_string()].into_iter().collect(),
+            unused_dependencies: vec!["lodash".to_string()],
+            explored_files: vec!["index.js".to_string()],
+            ..Analysis::default()
+        };
+        let metrics: Vec<Metric> = "unused,explored-files,bytes-scanned"
+            .split(',')
+            .map(|name| name.parse().unwrap())
+            .collect();
+
+        let mut out = Vec::new();
+        write_dependency_report(&mut out, ".", &analysis, ReportSections::All, &metrics).unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        let rows: Vec<&str> = ["Unused Dependencies", "Explored Files", "Bytes Scanned"]
+            .into_iter()
+            .filter(|label| output.contains(&format!("| {}", label)))
+            .collect();
+        assert_eq!(rows.len(), 3);
+        assert!(output.find("| Unused Dependencies") < output.find("| Explored Files"));
+        for label in [
+            "Project",
+            "Extensions",
+            "Total Dependencies",
+            "Used Dependencies",
+        ] {
+            assert!(!output.contains(&format!("| {}", label)), "{}", label);
+        }
+    }
+
+    #[test]
+    fn test_metric_from_str_unknown() {
+        assert_eq!("scan-duration".parse::<Metric>(), Ok(Metric::ScanDuration));
+        let err = "bytes".parse::<Metric>().unwrap_err();
+        assert!(err.contains("Unknown metric `bytes`"));
+        assert!(err.contains("bytes-scanned"));
+    }
+}
@@ -0,0 +1,221 @@
+#[cfg(test)]
+mod tests {
+    use crate::resolve::{
+        case_mismatched_package, concatenation_to_package, is_node_builtin, run_resolver_script,
+        specifier_to_package, template_to_package,
+    };
+    use std::collections::HashSet;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_specifier_to_package_bare() {
+        assert_eq!(specifier_to_package("react"), Some("react".to_string()));
+        assert_eq!(
+            specifier_to_package("lodash.merge"),
+            Some("lodash.merge".to_string())
+        );
+        assert_eq!(specifier_to_package(" react "), Some("react".to_string()));
+    }
+
+    #[test]
+    fn test_specifier_to_package_subpath() {
+        assert_eq!(
+            specifier_to_package("lodash/merge"),
+            Some("lodash".to_string())
+        );
+        assert_eq!(
+            specifier_to_package("core-js/stable/array/from"),
+            Some("core-js".to_string())
+        );
+        assert_eq!(
+            specifier_to_package("react-dom/client"),
+            Some("react-dom".to_string())
+        );
+    }
+
+    #[test]
+    fn test_specifier_to_package_scoped() {
+        assert_eq!(
+            specifier_to_package("@vercel/analytics"),
+            Some("@vercel/analytics".to_string())
+        );
+        assert_eq!(
+            specifier_to_package("@babel/runtime/helpers/extends"),
+            Some("@babel/runtime".to_string())
+        );
+    }
+
+    #[test]
+    fn test_specifier_to_package_malformed_scope() {
+        assert_eq!(specifier_to_package("@scope"), None);
+        assert_eq!(specifier_to_package("@scope/"), None);
+        assert_eq!(specifier_to_package("@/components/Button"), None);
+        assert_eq!(specifier_to_package(""), None);
+    }
+
+    #[test]
+    fn test_specifier_to_package_relative_and_absolute() {
+        assert_eq!(specifier_to_package("./utils"), None);
+        assert_eq!(specifier_to_package("../lib/index.js"), None);
+        assert_eq!(specifier_to_package("."), None);
+        assert_eq!(specifier_to_package("/usr/lib/module.js"), None);
+        assert_eq!(specifier_to_package("#internal/utils"), None);
+    }
+
+    #[test]
+    fn test_specifier_to_package_builtins_and_urls() {
+        // Bare built-in names may be declared npm packages, so they are kept
+        assert_eq!(specifier_to_package("fs"), Some("fs".to_string()));
+        assert_eq!(specifier_to_package("fs/promises"), Some("fs".to_string()));
+        assert_eq!(specifier_to_package("buffer/"), Some("buffer".to_string()));
+        assert_eq!(specifier_to_package("node:path"), None);
+        assert_eq!(specifier_to_package("node:test"), None);
+        assert_eq!(specifier_to_package("https://esm.sh/react"), None);
+        assert_eq!(specifier_to_package("data:text/javascript,export{}"), None);
+    }
+
+    #[test]
+    fn test_is_node_builtin() {
+        assert!(is_node_builtin("fs"));
+        assert!(is_node_builtin("buffer"));
+        assert!(is_node_builtin("events"));
+        assert!(!is_node_builtin("lodash"));
+        assert!(!is_node_builtin("node:fs"));
+    }
+
+    #[test]
+    fn test_specifier_to_package_bundler_suffixes() {
+        assert_eq!(
+            specifier_to_package("pkg/asset.svg?url"),
+            Some("pkg".to_string())
+        );
+        assert_eq!(
+            specifier_to_package("@scope/icons/logo.svg?raw"),
+            Some("@scope/icons".to_string())
+        );
+        assert_eq!(specifier_to_package("pkg?worker"), Some("pkg".to_string()));
+        assert_eq!(
+            specifier_to_package("worker-loader!pkg"),
+            Some("pkg".to_string())
+        );
+        assert_eq!(
+            specifier_to_package("!!raw-loader!pkg/readme.md?inline"),
+            Some("pkg".to_string())
+        );
+        assert_eq!(specifier_to_package("style-loader!./styles.css"), None);
+        assert_eq!(specifier_to_package("./worker.js?worker"), None);
+    }
+
+    #[test]
+    fn test_template_to_package() {
+        assert_eq!(template_to_package("lodash"), Some("lodash".to_string()));
+        assert_eq!(
+            template_to_package("date-fns/locale/${lang}"),
+            Some("date-fns".to_string())
+        );
+        assert_eq!(
+            template_to_package("@formatjs/intl/${name}.js"),
+            Some("@formatjs/intl".to_string())
+        );
+        assert_eq!(template_to_package("${name}"), None);
+        assert_eq!(template_to_package("lodash${suffix}"), None);
+        assert_eq!(template_to_package("@scope/${name}"), None);
+        assert_eq!(template_to_package("./locales/${lang}.json"), None);
+    }
+
+    #[test]
+    fn test_concatenation_to_package() {
+        assert_eq!(
+            concatenation_to_package("'lodash' + '/merge'"),
+            Some("lodash".to_string())
+        );
+        assert_eq!(
+            concatenation_to_package("\"@babel/\" + \"core\" + '/lib/' + file"),
+            Some("@babel/core".to_string())
+        );
+        assert_eq!(
+            concatenation_to_package("'date-fns/locale/' + lang"),
+            Some("date-fns".to_string())
+        );
+        assert_eq!(concatenation_to_package("'lodash' + suffix"), None);
+        assert_eq!(concatenation_to_package("name + '/merge'"), None);
+        assert_eq!(concatenation_to_package("'./locales/' + lang"), None);
+    }
+
+    #[test]
+    fn test_case_mismatched_package() {
+        let declared: HashSet<String> = ["react", "@Scope/UI"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        assert_eq!(case_mismatched_package("React", &declared), Some("react"));
+        assert_eq!(
+            case_mismatched_package("@scope/ui", &declared),
+            Some("@Scope/UI")
+        );
+        assert_eq!(case_mismatched_package("react", &declared), None);
+        assert_eq!(case_mismatched_package("preact", &declared), None);
+    }
+
+    /// Writes an executable resolver script stub into the directory.
+    #[cfg(unix)]
+    fn write_resolver_stub(dir: &std::path::Path, script: &str) -> String {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("resolver.sh");
+        fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_resolver_script_line_protocol() {
+        let temp_dir = TempDir::new().unwrap();
+        let script = write_resolver_stub(
+            temp_dir.path(),
+            r#"while read spec; do
+  case "$spec" in
+    @app/*) echo "@acme/app" ;;
+    ~*) echo "-" ;;
+    *) echo "" ;;
+  esac
+done"#,
+        );
+        let specifiers = vec![
+            "@app/ui".to_string(),
+            "unknown".to_string(),
+            "~components".to_string(),
+        ];
+
+        let resolved =
+            run_resolver_script(&script, temp_dir.path().to_str().unwrap(), &specifiers).unwrap();
+
+        assert_eq!(resolved.len(), 2);
+        assert_eq!(resolved["@app/ui"], Some("@acme/app".to_string()));
+        assert_eq!(resolved["~components"], None);
+        assert!(!resolved.contains_key("unknown"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_resolver_script_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().to_str().unwrap();
+        let specifiers = vec!["@app/ui".to_string()];
+
+        let failing = write_resolver_stub(temp_dir.path(), "echo 'no config' >&2; exit 2");
+        let err = run_resolver_script(&failing, dir, &specifiers).unwrap_err();
+        assert!(err.starts_with("Resolver script '"), "{}", err);
+        assert!(err.contains("no config"));
+
+        let chatty = write_resolver_stub(temp_dir.path(), "echo a; echo b");
+        let err = run_resolver_script(&chatty, dir, &specifiers).unwrap_err();
+        assert!(err.starts_with("Resolver script '"), "{}", err);
+        assert!(err.contains("printed 2 lines for 1 specifiers"));
+
+        assert!(run_resolver_script("./does-not-exist.sh", dir, &specifiers).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{AnalyzeOptions, analyze};
+    use crate::progress::NoProgress;
+    use crate::tarball::extract_tarball;
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    /// Packs a `package/` fixture directory into `package.tgz`, like `npm pack` does.
+    fn pack_fixture(dir: &Path) -> String {
+        let package = dir.join("package");
+        fs::create_dir_all(package.join("src")).unwrap();
+        fs::write(
+            package.join("package.json"),
+            r#"{ "name": "packed", "dependencies": { "react": "^18.0.0", "lodash": "^4.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            package.join("src/index.js"),
+            "import React from 'react';\nimport axios from 'axios';",
+        )
+        .unwrap();
+        let tarball = dir.join("package.tgz");
+        let status = Command::new("tar")
+            .arg("-czf")
+            .arg(&tarball)
+            .arg("-C")
+            .arg(dir)
+            .arg("package")
+            .status()
+            .unwrap();
+        assert!(status.success());
+        tarball.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_extract_tarball_analyzes_packed_package() {
+        let temp_dir = TempDir::new().unwrap();
+        let tarball = pack_fixture(temp_dir.path());
+
+        let extracted = extract_tarball(&tarball).unwrap();
+        let root = extracted.root().to_path_buf();
+        assert!(root.ends_with("package"));
+        assert!(root.join("package.json").is_file());
+
+        let analysis = analyze(
+            &AnalyzeOptions::new(root.to_str().unwrap())
+                .dependency_list(Some(vec!["react".to_string(), "lodash".to_string()])),
+            &NoProgress,
+        )
+        .unwrap();
+        assert_eq!(analysis.unused_dependencies, vec!["lodash".to_string()]);
+        assert_eq!(analysis.missing_dependencies, vec!["axios".to_string()]);
+
+        // The temporary directory is removed with the extracted tarball
+        drop(extracted);
+        assert!(!root.exists());
+    }
+
+    #[test]
+    fn test_extract_tarball_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing.tgz");
+        assert!(
+            extract_tarball(missing.to_str().unwrap())
+                .unwrap_err()
+                .contains("not found")
+        );
+
+        let invalid = temp_dir.path().join("invalid.tgz");
+        fs::write(&invalid, "not a tarball").unwrap();
+        assert!(
+            extract_tarball(invalid.to_str().unwrap())
+                .unwrap_err()
+                .starts_with("Error: Failed to extract")
+        );
+    }
+}
@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod tests {
+    use crate::uninstall::{
+        ReinstallPolicy, RemovalSummary, handle_unused_dependencies, install_command,
+        reinstall_after_removal, selection_defaults, stale_modules, write_removal_plan,
+    };
+    use std::collections::BTreeMap;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_handle_unused_dependencies_dry_run_summary() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_dir = temp_dir.path().join("node_modules/lodash");
+        fs::create_dir_all(&package_dir).unwrap();
+        let unused = vec!["lodash".to_string(), "left-pad".to_string()];
+
+        let summary = handle_unused_dependencies(
+            temp_dir.path().to_str().unwrap(),
+            &unused,
+            &BTreeMap::new(),
+            true,
+            false,
+            false,
+            ReinstallPolicy::Always.into(),
+        );
+
+        assert_eq!(
+            summary,
+            RemovalSummary {
+                dry_run: true,
+                planned: unused,
+                ..RemovalSummary::default()
+            }
+        );
+        assert!(package_dir.exists());
+    }
+
+    #[test]
+    fn test_selection_defaults() {
+        assert_eq!(selection_defaults(3, true), vec![true, true, true]);
+        assert_eq!(selection_defaults(3, false), vec![false, false, false]);
+        assert!(selection_defaults(0, true).is_empty());
+    }
+
+    #[test]
+    fn test_write_removal_plan() {
+        let summary = RemovalSummary {
+            dry_run: true,
+            planned: vec!["lodash".to_string(), "left-pad".to_string()],
+            ..RemovalSummary::default()
+        };
+        let sizes = BTreeMap::from([("lodash".to_string(), 1536)]);
+        let mut out = Vec::new();
+
+        write_removal_plan(&mut out, &summary, &sizes).unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        assert!(output.contains("Would delete:"));
+        assert!(output.contains("lodash (1.5 KB)"));
+        assert!(output.contains("left-pad"));
+    }
+
+    #[test]
+    fn test_reinstall_after_removal_never_leaves_node_modules_untouched() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_dir = temp_dir.path().join("node_modules/lodash");
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::write(package_dir.join("index.js"), "module.exports = {};").unwrap();
+        let removed = vec!["lodash".to_string()];
+
+        let result = reinstall_after_removal(
+            temp_dir.path().to_str().unwrap(),
+            &removed,
+            ReinstallPolicy::Never.into(),
+        );
+
+        assert_eq!(result, None);
+        assert!(package_dir.join("index.js").exists());
+    }
+
+    #[test]
+    fn test_reinstall_after_removal_auto_skips_up_to_date_node_modules() {
+        let temp_dir = TempDir::new().unwrap();
+        let node_modules = temp_dir.path().join("node_modules");
+        fs::create_dir_all(node_modules.join("react")).unwrap();
+        let removed = vec!["lodash".to_string()];
+
+        let result = reinstall_after_removal(
+            temp_dir.path().to_str().unwrap(),
+            &removed,
+            ReinstallPolicy::Auto.into(),
+        );
+
+        assert_eq!(result, None);
+        assert!(node_modules.join("react").exists());
+    }
+
+    #[test]
+    fn test_install_command_per_package_manager() {
+        let cases: [(&str, bool, &str, &[&str]); 7] = [
+            ("package-lock.json", true, "npm", &["ci"]),
+            ("package-lock.json", false, "npm", &["install"]),
+            ("yarn.lock", true, "yarn", &["install", "--immutable"]),
+            ("yarn.lock", false, "yarn", &["install"]),
+            (
+                "pnpm-lock.yaml",
+                true,
+                "pnpm",
+                &["install", "--frozen-lockfile"],
+            ),
+            ("pnpm-lock.yaml", false, "pnpm", &["install"]),
+            ("bun.lock", true, "bun", &["install", "--frozen-lockfile"]),
+        ];
+        for (lockfile, frozen, package_manager, args) in cases {
+            let temp_dir = TempDir::new().unwrap();
+            fs::write(temp_dir.path().join(lockfile), "").unwrap();
+
+            assert_eq!(
+                install_command(temp_dir.path().to_str().unwrap(), frozen),
+                (package_manager.to_string(), args.to_vec()),
+                "{} (frozen: {})",
+                lockfile,
+                frozen
+            );
+        }
+    }
+
+    #[test]
+    fn test_install_command_npm_without_lockfile() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // `npm ci` fails without a package-lock.json
+        assert_eq!(
+            install_command(temp_dir.path().to_str().unwrap(), true),
+            ("npm".to_string(), vec!["install"])
+        );
+    }
+
+    #[test]
+    fn test_stale_modules() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("node_modules/@scope/pkg")).unwrap();
+        let removed = vec!["@scope/pkg".to_string(), "lodash".to_string()];
+
+        let stale = stale_modules(temp_dir.path().to_str().unwrap(), &removed);
+
+        assert_eq!(stale, vec!["@scope/pkg".to_string()]);
+    }
+
+    #[test]
+    fn test_reinstall_policy_from_str() {
+        assert_eq!("always".parse(), Ok(ReinstallPolicy::Always));
+        assert_eq!("never".parse(), Ok(ReinstallPolicy::Never));
+        assert_eq!("auto".parse(), Ok(ReinstallPolicy::Auto));
+        assert!("sometimes".parse::<ReinstallPolicy>().is_err());
+    }
+}
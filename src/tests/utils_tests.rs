@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use crate::utils::{ColorChoice, format_size, is_ci_value, run_with_timeout, strip_jsonc};
+    use std::process::Command;
+    use std::time::Duration;
+
+    #[test]
+    fn test_strip_jsonc_removes_comments() {
+        let content = r#"{
+            // line comment
+            "a": 1, /* block
+            comment */ "b": 2
+        }"#;
+
+        let value: serde_json::Value = serde_json::from_str(&strip_jsonc(content)).unwrap();
+
+        assert_eq!(value, serde_json::json!({"a": 1, "b": 2}));
+    }
+
+    #[test]
+    fn test_strip_jsonc_removes_trailing_commas() {
+        let content = r#"{ "a": [1, 2, ], "b": { "c": true, }, }"#;
+
+        let value: serde_json::Value = serde_json::from_str(&strip_jsonc(content)).unwrap();
+
+        assert_eq!(value, serde_json::json!({"a": [1, 2], "b": {"c": true}}));
+    }
+
+    #[test]
+    fn test_strip_jsonc_preserves_strings() {
+        let content = r#"{ "url": "https://example.com/*x*/", "quote": "a \"// b\" ,}" }"#;
+
+        let value: serde_json::Value = serde_json::from_str(&strip_jsonc(content)).unwrap();
+
+        assert_eq!(value["url"], "https://example.com/*x*/");
+        assert_eq!(value["quote"], "a \"// b\" ,}");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MB");
+        assert_eq!(format_size(5 * 1024 * 1024 * 1024), "5.0 GB");
+    }
+
+    #[test]
+    fn test_run_with_timeout_kills_slow_command() {
+        let mut command = Command::new("sleep");
+        command.arg("5");
+
+        let output = run_with_timeout(&mut command, Duration::from_millis(100)).unwrap();
+
+        assert!(output.is_none());
+    }
+
+    #[test]
+    fn test_run_with_timeout_captures_output() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo out; echo err >&2"]);
+
+        let output = run_with_timeout(&mut command, Duration::from_secs(10))
+            .unwrap()
+            .unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "out\n");
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "err\n");
+    }
+
+    #[test]
+    fn test_is_ci_value() {
+        assert!(is_ci_value(Some("true")));
+        assert!(is_ci_value(Some("1")));
+        assert!(!is_ci_value(Some("false")));
+        assert!(!is_ci_value(Some("0")));
+        assert!(!is_ci_value(Some("")));
+        assert!(!is_ci_value(None));
+    }
+
+    #[test]
+    fn test_color_choice_enabled() {
+        // Piped output
+        assert!(ColorChoice::Always.enabled(false, None));
+        assert!(ColorChoice::Always.enabled(false, Some("1")));
+        assert!(!ColorChoice::Auto.enabled(false, None));
+        // Terminal output
+        assert!(!ColorChoice::Never.enabled(true, None));
+        assert!(ColorChoice::Auto.enabled(true, None));
+        assert!(ColorChoice::Auto.enabled(true, Some("")));
+        assert!(!ColorChoice::Auto.enabled(true, Some("1")));
+    }
+
+    #[test]
+    fn test_color_choice_from_str() {
+        assert_eq!("always".parse(), Ok(ColorChoice::Always));
+        assert_eq!("never".parse(), Ok(ColorChoice::Never));
+        assert_eq!("auto".parse(), Ok(ColorChoice::Auto));
+        assert!("yes".parse::<ColorChoice>().is_err());
+    }
+}
@@ -0,0 +1,29 @@
+#[cfg(test)]
+mod tests {
+    use crate::warnings::Warnings;
+
+    #[test]
+    fn test_warnings_serialize_as_array_in_order() {
+        let mut warnings = Warnings::default();
+        warnings.push("first");
+        let mut scan_warnings = Warnings::default();
+        scan_warnings.push("second".to_string());
+        warnings.extend(scan_warnings);
+
+        assert_eq!(warnings.iter().collect::<Vec<_>>(), vec!["first", "second"]);
+        assert_eq!(
+            serde_json::to_value(&warnings).unwrap(),
+            serde_json::json!(["first", "second"])
+        );
+    }
+
+    #[test]
+    fn test_warnings_clear() {
+        let mut warnings = Warnings::default();
+        warnings.push("Invalid cnp.toml");
+
+        warnings.clear();
+
+        assert!(warnings.is_empty());
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use crate::watch::{debounce_changes, is_relevant_change};
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc::channel;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_is_relevant_change() {
+        let root = Path::new("/project");
+
+        assert!(is_relevant_change(root, Path::new("/project/src/index.ts")));
+        assert!(is_relevant_change(root, Path::new("/project/package.json")));
+        assert!(is_relevant_change(root, Path::new("/project/cnp.toml")));
+        assert!(!is_relevant_change(
+            root,
+            Path::new("/project/node_modules/react/index.js")
+        ));
+        assert!(!is_relevant_change(root, Path::new("/project/README.md")));
+    }
+
+    #[test]
+    fn test_debounce_changes_rescans_once_per_burst() {
+        let root = Path::new("/project");
+        let (sender, receiver) = channel();
+
+        let source = thread::spawn(move || {
+            // A burst of relevant changes, then an irrelevant one, then a second burst
+            for file in ["src/a.js", "src/b.js", "package.json"] {
+                sender.send(root.join(file)).unwrap();
+            }
+            thread::sleep(Duration::from_millis(200));
+            sender.send(PathBuf::from("/project/notes.txt")).unwrap();
+            sender.send(PathBuf::from("/project/src/c.ts")).unwrap();
+        });
+
+        let mut rescans = 0;
+        debounce_changes(root, &receiver, Duration::from_millis(50), || rescans += 1);
+        source.join().unwrap();
+
+        assert_eq!(rescans, 2);
+    }
+
+    #[test]
+    fn test_debounce_changes_ignores_irrelevant_events() {
+        let (sender, receiver) = channel();
+        sender
+            .send(PathBuf::from("/project/node_modules/lodash/index.js"))
+            .unwrap();
+        sender.send(PathBuf::from("/project/.git/HEAD")).unwrap();
+        drop(sender);
+
+        let mut rescans = 0;
+        debounce_changes(
+            Path::new("/project"),
+            &receiver,
+            Duration::from_millis(10),
+            || rescans += 1,
+        );
+
+        assert_eq!(rescans, 0);
+    }
+}
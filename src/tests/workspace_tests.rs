@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "cli")]
+    use crate::cli::with_workspaces;
+    use crate::workspace::{hoisted_packages, workspace_globs, workspace_packages, workspace_root};
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    /// Creates a workspace package directory with a minimal `package.json`.
+    fn create_package(root: &Path, dir: &str) {
+        fs::create_dir_all(root.join(dir)).unwrap();
+        fs::write(root.join(dir).join("package.json"), "{}").unwrap();
+    }
+
+    #[test]
+    fn test_workspace_packages_pnpm() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("pnpm-workspace.yaml"),
+            "packages:\n  - 'packages/*'\n  - '!packages/legacy'\n",
+        )
+        .unwrap();
+        create_package(root, "packages/web");
+        create_package(root, "packages/api");
+        create_package(root, "packages/legacy");
+        fs::create_dir_all(root.join("packages/docs")).unwrap();
+        let root_path = root.to_str().unwrap();
+
+        let packages = workspace_packages(root_path);
+
+        assert_eq!(
+            packages,
+            vec![
+                format!("{}/packages/api", root_path),
+                format!("{}/packages/web", root_path),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_workspace_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("package.json"),
+            r#"{ "workspaces": ["packages/*"] }"#,
+        )
+        .unwrap();
+        create_package(root, "packages/web");
+        create_package(root, "examples/demo");
+        let root_path = fs::canonicalize(root).unwrap();
+
+        assert_eq!(
+            workspace_root(root.join("packages/web").to_str().unwrap()),
+            Some(root_path.display().to_string())
+        );
+        assert_eq!(
+            workspace_root(root.join("examples/demo").to_str().unwrap()),
+            None
+        );
+        assert_eq!(workspace_root(root.to_str().unwrap()), None);
+    }
+
+    #[test]
+    fn test_hoisted_packages() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("package.json"),
+            r#"{ "workspaces": ["packages/*"], "dependencies": { "react": "^18.0.0" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("package-lock.json"),
+            r#"{ "packages": { "": {}, "node_modules/lodash": { "version": "4.17.21" } } }"#,
+        )
+        .unwrap();
+
+        let packages = hoisted_packages(root.to_str().unwrap());
+        assert!(packages.contains("react"));
+        assert!(packages.contains("lodash"));
+
+        // pnpm does not hoist locked packages
+        fs::remove_file(root.join("package-lock.json")).unwrap();
+        fs::write(
+            root.join("pnpm-lock.yaml"),
+            "lockfileVersion: '9.0'\npackages:\n  lodash@4.17.21:\n    resolution: {integrity: sha512-x}\n",
+        )
+        .unwrap();
+        let packages = hoisted_packages(root.to_str().unwrap());
+        assert!(packages.contains("react"));
+        assert!(!packages.contains("lodash"));
+    }
+
+    #[test]
+    fn test_workspace_globs_combines_pnpm_and_package_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("pnpm-workspace.yaml"),
+            "packages:\n  - packages/*\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("package.json"),
+            r#"{ "workspaces": { "packages": ["apps/*"] } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            workspace_globs(root.to_str().unwrap()),
+            vec!["packages/*", "apps/*"]
+        );
+    }
+
+    #[test]
+    fn test_workspace_packages_package_json_array() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("package.json"),
+            r#"{ "workspaces": ["./apps/*/"] }"#,
+        )
+        .unwrap();
+        create_package(root, "apps/web");
+        let root_path = root.to_str().unwrap();
+
+        assert_eq!(
+            workspace_packages(root_path),
+            vec![format!("{}/apps/web", root_path)]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_with_workspaces() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("pnpm-workspace.yaml"),
+            "packages:\n  - packages/*\n",
+        )
+        .unwrap();
+        create_package(root, "packages/web");
+        let root_path = root.to_str().unwrap().to_string();
+
+        assert_eq!(
+            with_workspaces(vec![root_path.clone()], false),
+            vec![root_path.clone()]
+        );
+        assert_eq!(
+            with_workspaces(vec![root_path.clone()], true),
+            vec![root_path.clone(), format!("{}/packages/web", root_path)]
+        );
+    }
+
+    #[test]
+    fn test_workspace_packages_without_workspaces() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("package.json"), "{}").unwrap();
+
+        assert!(workspace_packages(temp_dir.path().to_str().unwrap()).is_empty());
+    }
+}
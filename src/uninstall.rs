@@ -1,55 +1,286 @@
 use crate::package_manager::detect_package_manager;
-use crate::utils::{create_bar, create_spinner};
+use crate::utils::{create_bar, create_spinner, format_size};
 use colored::*;
 use dialoguer::{MultiSelect, theme::ColorfulTheme};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
-use std::io::{self};
+use std::io::{self, Write};
 use std::path::Path;
 use std::process::Command;
+use std::str::FromStr;
 
-/// Reinstalls the project's `node_modules` directory.
+/// The outcome of handling a project's unused dependencies.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RemovalSummary {
+    /// Whether the run was a dry run, in which case nothing was deleted.
+    pub dry_run: bool,
+    /// Dependencies selected for deletion (all unused dependencies in dry-run mode).
+    pub planned: Vec<String>,
+    /// Dependencies that were successfully uninstalled.
+    pub deleted: Vec<String>,
+    /// Dependencies that could not be uninstalled, with the reason, keyed by name.
+    pub failed: BTreeMap<String, String>,
+    /// Whether `node_modules` was reinstalled successfully after the deletions.
+    pub reinstalled: bool,
+    /// The error of the reinstall after the deletions, including the install command's stderr.
+    pub reinstall_error: Option<String>,
+}
+
+/// When `node_modules` is reinstalled after unused dependencies are removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReinstallPolicy {
+    /// Remove `node_modules` and reinstall it from scratch.
+    Always,
+    /// Never reinstall; `node_modules` is left as the package manager left it.
+    Never,
+    /// Run a plain install only if a removed package is still present in `node_modules`.
+    Auto,
+}
+
+/// How `node_modules` is brought up to date after unused dependencies are removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReinstallOptions {
+    /// When the install runs.
+    pub policy: ReinstallPolicy,
+    /// Whether the install must leave the lockfile untouched (`npm ci`, `pnpm install
+    /// --frozen-lockfile`), from `--frozen-install`.
+    pub frozen: bool,
+}
+
+impl From<ReinstallPolicy> for ReinstallOptions {
+    fn from(policy: ReinstallPolicy) -> Self {
+        ReinstallOptions {
+            policy,
+            frozen: false,
+        }
+    }
+}
+
+impl fmt::Display for ReinstallPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReinstallPolicy::Always => write!(f, "always"),
+            ReinstallPolicy::Never => write!(f, "never"),
+            ReinstallPolicy::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl FromStr for ReinstallPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "always" => Ok(ReinstallPolicy::Always),
+            "never" => Ok(ReinstallPolicy::Never),
+            "auto" => Ok(ReinstallPolicy::Auto),
+            _ => Err(format!("Error: Unknown reinstall policy `{}`.", s)),
+        }
+    }
+}
+
+/// Reinstalls a project's `node_modules` directory.
 ///
 /// This function removes the existing `node_modules` directory (if present) and runs the
 /// appropriate package manager's install command (e.g., `npm install`, `yarn install`) to
-/// reinstall dependencies. A progress spinner provides feedback during the process.
+/// reinstall dependencies, or its frozen variant (see [`install_command`]). A progress spinner
+/// provides feedback during the process.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `frozen` - If `true`, runs the install variant that never updates the lockfile.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the reinstallation succeeded.
+/// Returns `Err(String)` with an error message if `node_modules` could not be removed or the
+/// install command failed, including its stderr.
 ///
 /// # Output
 ///
 /// Prints success or failure messages to the console via a progress spinner:
 /// - Success: "Reinstallation successful!" (in green).
-/// - Failure: An error message (in red) if removal or installation fails.
+/// - Failure: The error message (in red).
 ///
 /// # Examples
 ///
 /// ```
-/// reinstall_modules();
-/// // If `node_modules` exists, it is deleted and reinstalled with the detected package manager.
-/// // Outputs a spinner with status messages.
+/// if let Err(err) = reinstall_modules(".", false) {
+///     eprintln!("node_modules is out of date: {}", err);
+/// }
 /// ```
-pub fn reinstall_modules() {
+pub fn reinstall_modules(dir_path: &str, frozen: bool) -> Result<(), String> {
     let pb = create_spinner("Reinstalling node_modules...");
 
-    let node_modules_path = Path::new("node_modules");
-    if node_modules_path.exists() {
-        if let Err(e) = fs::remove_dir_all(node_modules_path) {
-            pb.abandon_with_message(
-                format!("Failed to remove node_modules: {}", e)
-                    .red()
-                    .to_string(),
-            );
-            return;
+    let node_modules_path = Path::new(dir_path).join("node_modules");
+    let result = match fs::remove_dir_all(&node_modules_path) {
+        Err(e) if node_modules_path.exists() => {
+            Err(format!("Error: Failed to remove node_modules: {}", e))
         }
+        _ => run_install(dir_path, frozen),
+    };
+
+    match &result {
+        Ok(()) => pb.finish_with_message("Reinstallation successful!".green().to_string()),
+        Err(err) => pb.abandon_with_message(err.red().to_string()),
+    }
+    result
+}
+
+/// Chooses the install command of a project's package manager.
+///
+/// The frozen variant installs exactly what the lockfile lists and fails instead of updating it:
+/// `npm ci`, `yarn install --immutable`, `pnpm install --frozen-lockfile` or `bun install
+/// --frozen-lockfile`. `npm ci` needs a `package-lock.json` (or `npm-shrinkwrap.json`), so npm
+/// projects without one fall back to a plain `npm install`.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `frozen` - If `true`, chooses the variant that never updates the lockfile.
+///
+/// # Returns
+///
+/// Returns the package manager program and its arguments.
+///
+/// # Examples
+///
+/// ```
+/// // In a project with a pnpm-lock.yaml
+/// assert_eq!(
+///     install_command(".", true),
+///     ("pnpm".to_string(), vec!["install", "--frozen-lockfile"])
+/// );
+/// ```
+pub fn install_command(dir_path: &str, frozen: bool) -> (String, Vec<&'static str>) {
+    let package_manager = detect_package_manager(dir_path);
+    let has_npm_lockfile = ["package-lock.json", "npm-shrinkwrap.json"]
+        .iter()
+        .any(|lockfile| Path::new(dir_path).join(lockfile).exists());
+    let args = match package_manager.as_str() {
+        _ if !frozen => vec!["install"],
+        "npm" if has_npm_lockfile => vec!["ci"],
+        "npm" => vec!["install"],
+        "yarn" => vec!["install", "--immutable"],
+        _ => vec!["install", "--frozen-lockfile"],
+    };
+    (package_manager, args)
+}
+
+/// Runs the package manager's install command in a project, without removing `node_modules`.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `frozen` - If `true`, runs the install variant that never updates the lockfile (see
+///   [`install_command`]).
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the install command exited successfully.
+/// Returns `Err(String)` with an error message including the command's stderr otherwise.
+///
+/// # Examples
+///
+/// ```
+/// match run_install(".", false) {
+///     Ok(()) => println!("node_modules is up to date."),
+///     Err(err) => eprintln!("{}", err),
+/// }
+/// ```
+pub fn run_install(dir_path: &str, frozen: bool) -> Result<(), String> {
+    let (package_manager, args) = install_command(dir_path, frozen);
+    let command = format!("{} {}", package_manager, args.join(" "));
+    let output = Command::new(&package_manager)
+        .args(&args)
+        .current_dir(dir_path)
+        .output()
+        .map_err(|e| format!("Error: Failed to run `{}`: {}", command, e))?;
+
+    if output.status.success() {
+        return Ok(());
     }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(format!(
+        "Error: `{}` failed ({}).\n{}",
+        command,
+        output.status,
+        stderr.trim_end()
+    ))
+}
 
-    let package_manager = detect_package_manager();
-    let result = Command::new(&package_manager).arg("install").output();
+/// Lists the removed dependencies the package manager left behind in `node_modules`.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `removed` - A slice of `String` containing the removed dependency names.
+///
+/// # Returns
+///
+/// Returns a `Vec<String>` with the removed dependencies whose `node_modules` directory still
+/// exists.
+///
+/// # Examples
+///
+/// ```
+/// let stale = stale_modules(".", &["lodash".to_string()]);
+/// if !stale.is_empty() {
+///     println!("node_modules is stale: {:?}", stale);
+/// }
+/// ```
+pub fn stale_modules(dir_path: &str, removed: &[String]) -> Vec<String> {
+    let node_modules_path = Path::new(dir_path).join("node_modules");
+    removed
+        .iter()
+        .filter(|dep| node_modules_path.join(dep).exists())
+        .cloned()
+        .collect()
+}
 
-    match result {
-        Ok(output) if output.status.success() => {
-            pb.finish_with_message("Reinstallation successful!".green().to_string());
-        }
-        _ => {
-            pb.abandon_with_message("Failed to reinstall dependencies".red().to_string());
+/// Brings `node_modules` up to date after dependencies were removed, following a
+/// `ReinstallPolicy`.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+/// * `removed` - A slice of `String` containing the removed dependency names.
+/// * `reinstall` - The `ReinstallOptions` deciding whether an install runs, and which one.
+///
+/// # Returns
+///
+/// Returns `None` if no install ran (nothing was removed, the policy is `Never`, or the policy is
+/// `Auto` and `node_modules` is not stale), or `Some` with the result of the install.
+///
+/// # Examples
+///
+/// ```
+/// let removed = vec!["lodash".to_string()];
+/// assert_eq!(reinstall_after_removal(".", &removed, ReinstallPolicy::Never.into()), None);
+/// ```
+pub fn reinstall_after_removal(
+    dir_path: &str,
+    removed: &[String],
+    reinstall: ReinstallOptions,
+) -> Option<Result<(), String>> {
+    if removed.is_empty() {
+        return None;
+    }
+
+    match reinstall.policy {
+        ReinstallPolicy::Never => None,
+        ReinstallPolicy::Always => Some(reinstall_modules(dir_path, reinstall.frozen)),
+        ReinstallPolicy::Auto if stale_modules(dir_path, removed).is_empty() => None,
+        ReinstallPolicy::Auto => {
+            let pb = create_spinner("Updating node_modules...");
+            let result = run_install(dir_path, reinstall.frozen);
+            match &result {
+                Ok(()) => pb.finish_with_message("node_modules updated!".green().to_string()),
+                Err(err) => pb.abandon_with_message(err.red().to_string()),
+            }
+            Some(result)
         }
     }
 }
@@ -57,21 +288,34 @@ pub fn reinstall_modules() {
 /// Handles the deletion of unused dependencies based on user preferences.
 ///
 /// This function processes unused dependencies, allowing deletion in three modes:
-/// - Dry-run: Lists dependencies that would be deleted without making changes.
+/// - Dry-run: Plans every unused dependency for deletion without making changes.
 /// - Interactive: Prompts the user to select dependencies to delete.
-/// Successfully deleted dependencies trigger a reinstall of `node_modules`.
+/// - Otherwise: Asks for confirmation before deleting all unused dependencies.
+///
+/// Successfully deleted dependencies trigger a reinstall of `node_modules`, as decided by the
+/// `reinstall` policy (see [`reinstall_after_removal`]). The dry-run plan is
+/// not printed; pass the returned summary to `write_removal_plan` to display it.
 ///
 /// # Arguments
 ///
+/// * `dir_path` - A string slice representing the path to the project directory.
 /// * `unused_dependencies` - A slice of `String` containing unused dependency names.
+/// * `package_sizes` - A reference to the `node_modules` size in bytes of each installed unused
+///   dependency, shown next to its name.
 /// * `dry_run` - If `true`, simulates deletion without making changes.
 /// * `interactive` - If `true`, prompts the user to select dependencies to delete.
+/// * `preselect_all` - If `true`, every dependency starts selected in the interactive prompt.
+/// * `reinstall` - The `ReinstallOptions` applied once dependencies are deleted.
+///
+/// # Returns
+///
+/// Returns a `RemovalSummary` with the planned, deleted and failed dependencies and whether
+/// `node_modules` was reinstalled (or why the reinstall failed).
 ///
 /// # Output
 ///
-/// Prints to the console:
-/// - In dry-run mode: A list of dependencies that would be deleted.
-/// - In interactive mode: A selection prompt for dependencies.
+/// Outside dry-run mode, prints to the console:
+/// - In interactive mode: A selection prompt for dependencies, with their sizes.
 /// - Progress bar updates for each deletion attempt (success in green, failure in red).
 /// - A final message indicating completion and, if deletions occurred, a reinstallation message.
 ///
@@ -79,38 +323,36 @@ pub fn reinstall_modules() {
 ///
 /// ```
 /// let unused = vec!["lodash".to_string(), "react".to_string()];
-/// handle_unused_dependencies(&unused, true, false);
-/// // Prints a dry-run list of dependencies without deleting.
-/// // Output: "Dry-run mode: No changes will be made."
-/// //         "Would delete:"
-/// //         "- lodash"
-/// //         "- react"
-///
-/// handle_unused_dependencies(&unused, false, true);
-/// // Prompts interactively to select dependencies for deletion.
+/// let summary =
+///     handle_unused_dependencies(".", &unused, &BTreeMap::new(), true, false, false, ReinstallPolicy::Always.into());
+/// assert_eq!(summary.planned, unused);
+/// assert!(summary.deleted.is_empty());
+///
+/// let summary =
+///     handle_unused_dependencies(".", &unused, &BTreeMap::new(), false, true, true, ReinstallPolicy::Auto.into());
+/// // Prompts interactively to select dependencies for deletion, all selected at first.
+/// println!("Deleted: {:?}", summary.deleted);
 /// ```
 pub fn handle_unused_dependencies(
+    dir_path: &str,
     unused_dependencies: &[String],
+    package_sizes: &BTreeMap<String, u64>,
     dry_run: bool,
     interactive: bool,
-) {
+    preselect_all: bool,
+    reinstall: ReinstallOptions,
+) -> RemovalSummary {
     if dry_run {
-        println!(
-            "\n{}",
-            "Dry-run mode: No changes will be made.".yellow().bold()
-        );
-        println!("{}", "Would delete:".yellow());
-
-        for dep in unused_dependencies {
-            println!("- {}", dep.yellow());
-        }
-
-        return;
+        return RemovalSummary {
+            dry_run,
+            planned: unused_dependencies.to_vec(),
+            ..RemovalSummary::default()
+        };
     }
 
-    let package_manager = detect_package_manager();
+    let package_manager = detect_package_manager(dir_path);
     let to_delete = if interactive {
-        select_dependencies_interactively(unused_dependencies)
+        select_dependencies_interactively(unused_dependencies, package_sizes, preselect_all)
     } else {
         confirm_all_deletion(unused_dependencies)
     };
@@ -120,19 +362,26 @@ pub fn handle_unused_dependencies(
             "\n{}",
             "No dependencies selected for deletion.".yellow().bold()
         );
-        return;
+        return RemovalSummary::default();
     }
 
-    let pb = create_bar(to_delete.len() as u64, "Deleting dependencies...");
-    let mut deleted = Vec::new();
-    for dep in &to_delete {
+    let mut summary = RemovalSummary {
+        planned: to_delete,
+        ..RemovalSummary::default()
+    };
+    let pb = create_bar(summary.planned.len() as u64, "Deleting dependencies...");
+    for dep in &summary.planned {
         pb.inc(1);
 
-        if uninstall_dependency(dep, &package_manager) {
-            pb.set_message(format!("Deleted: {}", dep).green().to_string());
-            deleted.push(dep.clone());
-        } else {
-            pb.set_message(format!("Failed to delete: {}", dep).red().to_string());
+        match uninstall_dependency(dep, &package_manager, dir_path) {
+            Ok(()) => {
+                pb.set_message(format!("Deleted: {}", dep).green().to_string());
+                summary.deleted.push(dep.clone());
+            }
+            Err(reason) => {
+                pb.set_message(format!("Failed to delete: {}", dep).red().to_string());
+                summary.failed.insert(dep.clone(), reason);
+            }
         }
 
         pb.tick();
@@ -140,19 +389,69 @@ pub fn handle_unused_dependencies(
 
     pb.finish_with_message("Deletion complete!".green().to_string());
 
-    if !deleted.is_empty() {
-        reinstall_modules();
+    match reinstall_after_removal(dir_path, &summary.deleted, reinstall) {
+        Some(Ok(())) => summary.reinstalled = true,
+        Some(Err(err)) => summary.reinstall_error = Some(err),
+        None => {}
+    }
+
+    summary
+}
+
+/// Writes the dependencies a dry run would delete.
+///
+/// # Arguments
+///
+/// * `out` - The writer the plan is written to.
+/// * `summary` - A reference to the dry-run `RemovalSummary`.
+/// * `package_sizes` - A reference to the `node_modules` size in bytes of each installed unused
+///   dependency, shown next to its name.
+///
+/// # Returns
+///
+/// Returns an `io::Result<()>` indicating whether writing succeeded.
+///
+/// # Examples
+///
+/// ```
+/// let summary =
+///     handle_unused_dependencies(".", &unused, &sizes, true, false, false, ReinstallPolicy::Always.into());
+/// write_removal_plan(&mut io::stdout(), &summary, &sizes)?;
+/// // Output: "Dry-run mode: No changes will be made."
+/// //         "Would delete:"
+/// //         "- lodash (1.4 MB)"
+/// ```
+pub fn write_removal_plan(
+    out: &mut dyn Write,
+    summary: &RemovalSummary,
+    package_sizes: &BTreeMap<String, u64>,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "\n{}",
+        "Dry-run mode: No changes will be made.".yellow().bold()
+    )?;
+    writeln!(out, "{}", "Would delete:".yellow())?;
+
+    for dep in &summary.planned {
+        writeln!(out, "- {}", dependency_label(dep, package_sizes).yellow())?;
     }
+
+    Ok(())
 }
 
 /// Prompts the user to interactively select dependencies for deletion.
 ///
 /// Displays a multi-select interface where the user can choose which dependencies to delete from
-/// the provided list. All dependencies are unselected by default.
+/// the provided list. The prompt explains the keys, including `a` to select or unselect every
+/// dependency at once.
 ///
 /// # Arguments
 ///
 /// * `unused_dependencies` - A slice of `String` containing unused dependency names.
+/// * `package_sizes` - A reference to the `node_modules` size in bytes of each installed unused
+///   dependency.
+/// * `preselect_all` - If `true`, every dependency starts selected; otherwise none does.
 ///
 /// # Returns
 ///
@@ -163,17 +462,27 @@ pub fn handle_unused_dependencies(
 ///
 /// ```
 /// let unused = vec!["lodash".to_string(), "react".to_string()];
-/// let selected = select_dependencies_interactively(&unused);
+/// let selected = select_dependencies_interactively(&unused, &BTreeMap::new(), false);
 /// // Displays a prompt; if user selects "lodash", returns ["lodash"].
 /// ```
-fn select_dependencies_interactively(unused_dependencies: &[String]) -> Vec<String> {
+fn select_dependencies_interactively(
+    unused_dependencies: &[String],
+    package_sizes: &BTreeMap<String, u64>,
+    preselect_all: bool,
+) -> Vec<String> {
     println!("\n{}", "Select dependencies to delete:".cyan().bold());
 
-    let defaults = vec![false; unused_dependencies.len()];
+    let defaults = selection_defaults(unused_dependencies.len(), preselect_all);
+    let labels: Vec<String> = unused_dependencies
+        .iter()
+        .map(|dep| dependency_label(dep, package_sizes))
+        .collect();
     let selection = MultiSelect::with_theme(&ColorfulTheme::default())
-        .items(unused_dependencies)
+        .items(&labels)
         .defaults(&defaults)
-        .with_prompt("Use arrow keys and space to select, Enter to confirm")
+        .with_prompt(
+            "Use arrow keys to move, Space to toggle, `a` to select or unselect all, Enter to confirm, Esc to cancel",
+        )
         .interact_opt()
         .unwrap_or(None);
 
@@ -187,6 +496,52 @@ fn select_dependencies_interactively(unused_dependencies: &[String]) -> Vec<Stri
     }
 }
 
+/// Builds the initial selection of the interactive deletion prompt.
+///
+/// # Arguments
+///
+/// * `count` - The number of dependencies in the prompt.
+/// * `preselect_all` - If `true`, every dependency starts selected.
+///
+/// # Returns
+///
+/// Returns a `Vec<bool>` with one entry per dependency, `true` when it starts selected.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(selection_defaults(2, true), vec![true, true]);
+/// assert_eq!(selection_defaults(2, false), vec![false, false]);
+/// ```
+pub fn selection_defaults(count: usize, preselect_all: bool) -> Vec<bool> {
+    vec![preselect_all; count]
+}
+
+/// Formats a dependency name with its `node_modules` size, if known.
+///
+/// # Arguments
+///
+/// * `dependency` - The name of the dependency.
+/// * `package_sizes` - A reference to the `node_modules` size in bytes of installed dependencies.
+///
+/// # Returns
+///
+/// Returns a `String` such as `lodash (1.4 MB)`, or the bare name if the size is unknown.
+///
+/// # Examples
+///
+/// ```
+/// let sizes = BTreeMap::from([("lodash".to_string(), 1536)]);
+/// assert_eq!(dependency_label("lodash", &sizes), "lodash (1.5 KB)");
+/// assert_eq!(dependency_label("react", &sizes), "react");
+/// ```
+pub fn dependency_label(dependency: &str, package_sizes: &BTreeMap<String, u64>) -> String {
+    match package_sizes.get(dependency) {
+        Some(size) => format!("{} ({})", dependency, format_size(*size)),
+        None => dependency.to_string(),
+    }
+}
+
 /// Prompts the user to confirm deletion of all unused dependencies.
 ///
 /// Displays a yes/no prompt asking the user to confirm deleting all provided dependencies.
@@ -235,34 +590,42 @@ fn confirm_all_deletion(unused_dependencies: &[String]) -> Vec<String> {
 ///
 /// * `dependency` - The name of the dependency to uninstall.
 /// * `package_manager` - The name of the package manager to use (e.g., "npm", "yarn").
+/// * `dir_path` - A string slice representing the path to the project directory.
 ///
 /// # Returns
 ///
-/// Returns `true` if the uninstall command succeeds, `false` otherwise.
+/// Returns `Ok(())` if the uninstall command succeeds.
+/// Returns `Err(String)` with the reason if the package manager is unsupported, cannot be run, or
+/// exits with an error.
 ///
 /// # Examples
 ///
 /// ```
-/// let success = uninstall_dependency("lodash", "npm");
-/// if success {
-///     println!("Successfully uninstalled lodash");
-/// } else {
-///     println!("Failed to uninstall lodash");
+/// match uninstall_dependency("lodash", "npm", ".") {
+///     Ok(()) => println!("Successfully uninstalled lodash"),
+///     Err(reason) => println!("Failed to uninstall lodash: {}", reason),
 /// }
 /// ```
-fn uninstall_dependency(dependency: &str, package_manager: &str) -> bool {
+fn uninstall_dependency(
+    dependency: &str,
+    package_manager: &str,
+    dir_path: &str,
+) -> Result<(), String> {
     let command = match package_manager {
         "npm" => "uninstall",
         "pnpm" | "yarn" | "bun" => "remove",
-        _ => {
-            eprintln!("Unsupported package manager: {}", package_manager);
-            return false;
-        }
+        _ => return Err(format!("Unsupported package manager: {}", package_manager)),
     };
 
     let output = Command::new(package_manager)
         .args([command, dependency])
-        .output();
+        .current_dir(dir_path)
+        .output()
+        .map_err(|e| format!("Failed to run `{}`: {}", package_manager, e))?;
 
-    matches!(output, Ok(result) if result.status.success())
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
 }
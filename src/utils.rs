@@ -1,5 +1,13 @@
+#[cfg(feature = "cli")]
 use indicatif::{ProgressBar, ProgressStyle};
+use std::fmt;
+use std::fs;
+use std::io::{self, IsTerminal, Read};
 use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
 
 /// Creates a spinner-style progress bar with a custom message.
 ///
@@ -21,6 +29,7 @@ use std::path::Path;
 /// // Displays a green spinner with "Processing..." until finished
 /// spinner.finish_with_message("Done!");
 /// ```
+#[cfg(feature = "cli")]
 pub fn create_spinner(message: &str) -> ProgressBar {
     let pb = ProgressBar::new_spinner();
     pb.set_style(
@@ -56,6 +65,7 @@ pub fn create_spinner(message: &str) -> ProgressBar {
 /// }
 /// bar.finish_with_message("Scan complete!");
 /// ```
+#[cfg(feature = "cli")]
 pub fn create_bar(len: u64, message: &str) -> ProgressBar {
     let pb = ProgressBar::new(len);
     pb.set_style(
@@ -102,3 +112,323 @@ pub fn get_file_name_and_extension(path: &str) -> Option<(String, String)> {
     }
     None
 }
+
+/// Strips JSONC extensions from a JSON document so it can be parsed as strict JSON.
+///
+/// This function removes `//` line comments, `/* */` block comments and trailing commas before a
+/// closing `}` or `]`. String literals are left untouched, so URLs or comment-like sequences inside
+/// strings are preserved.
+///
+/// # Arguments
+///
+/// * `content` - A string slice containing the JSONC document.
+///
+/// # Returns
+///
+/// Returns a `String` containing the document without comments and trailing commas.
+///
+/// # Examples
+///
+/// ```
+/// let content = r#"{ "a": 1, /* comment */ "b": "http://x", }"#;
+/// assert_eq!(strip_jsonc(content), r#"{ "a": 1,  "b": "http://x" }"#);
+/// ```
+pub fn strip_jsonc(content: &str) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        output.push(escaped);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                output.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                // Line comment: skip until the end of the line
+                while chars.next_if(|next| *next != '\n').is_some() {}
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                // Block comment: skip until the closing `*/`
+                chars.next();
+                let mut previous = '\0';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            '}' | ']' => {
+                // Drop a trailing comma before the closing bracket
+                let trimmed_len = output.trim_end().len();
+                if output[..trimmed_len].ends_with(',') {
+                    output.remove(trimmed_len - 1);
+                }
+                output.push(c);
+            }
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+/// Computes the total size of the files in a directory, recursively.
+///
+/// Symbolic links are not followed, so linked packages (e.g., pnpm's store or workspace links) are
+/// not counted twice. Entries that cannot be read are skipped.
+///
+/// # Arguments
+///
+/// * `path` - A reference to the `Path` of the directory.
+///
+/// # Returns
+///
+/// Returns the total size in bytes, or `0` if the directory cannot be read.
+///
+/// # Examples
+///
+/// ```
+/// let size = directory_size(Path::new("node_modules/lodash"));
+/// println!("lodash takes {}", format_size(size));
+/// ```
+pub fn directory_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => directory_size(&entry.path()),
+            Ok(metadata) if metadata.is_file() => metadata.len(),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Formats a size in bytes as a human-readable string.
+///
+/// # Arguments
+///
+/// * `bytes` - The size in bytes.
+///
+/// # Returns
+///
+/// Returns a `String` using the largest unit (B, KB, MB, GB) that keeps the value at or above 1,
+/// with one decimal for units above bytes.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(format_size(512), "512 B");
+/// assert_eq!(format_size(1536), "1.5 KB");
+/// assert_eq!(format_size(3 * 1024 * 1024), "3.0 MB");
+/// ```
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Runs a command to completion, killing it if it does not exit within the given timeout.
+///
+/// Standard output and standard error are captured while the command runs, so a verbose command
+/// cannot block on a full pipe.
+///
+/// # Arguments
+///
+/// * `command` - A mutable reference to the `Command` to run.
+/// * `timeout` - The maximum time the command may run.
+///
+/// # Returns
+///
+/// Returns `Ok(Some(Output))` if the command exited in time, `Ok(None)` if it was killed after the
+/// timeout, or `Err(io::Error)` if it could not be started.
+///
+/// # Examples
+///
+/// ```
+/// let mut command = Command::new("sleep");
+/// command.arg("10");
+/// assert!(run_with_timeout(&mut command, Duration::from_millis(100))?.is_none());
+/// ```
+pub fn run_with_timeout(command: &mut Command, timeout: Duration) -> io::Result<Option<Output>> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    fn drain(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            let _ = pipe.read_to_end(&mut buffer);
+            buffer
+        })
+    }
+    let stdout = child.stdout.take().map(drain);
+    let stderr = child.stderr.take().map(drain);
+    let collect = |pipe: Option<thread::JoinHandle<Vec<u8>>>| {
+        pipe.and_then(|handle| handle.join().ok())
+            .unwrap_or_default()
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(Output {
+                status,
+                stdout: collect(stdout),
+                stderr: collect(stderr),
+            }));
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Checks whether a `CI` environment variable value means the process runs in CI.
+///
+/// # Arguments
+///
+/// * `value` - The value of the `CI` environment variable, or `None` if it is not set.
+///
+/// # Returns
+///
+/// Returns `true` if the variable is set to anything but an empty string, `0` or `false`.
+///
+/// # Examples
+///
+/// ```
+/// assert!(is_ci_value(Some("true")));
+/// assert!(!is_ci_value(Some("false")));
+/// assert!(!is_ci_value(None));
+/// ```
+pub fn is_ci_value(value: Option<&str>) -> bool {
+    value.is_some_and(|value| !matches!(value.trim().to_lowercase().as_str(), "" | "0" | "false"))
+}
+
+/// Checks whether the process runs in CI, from the `CI` environment variable set by most CI
+/// providers.
+///
+/// # Returns
+///
+/// Returns `true` if `CI` is set to a truthy value (see [`is_ci_value`]), `false` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// if is_ci() {
+///     println!("Running in CI");
+/// }
+/// ```
+pub fn is_ci() -> bool {
+    is_ci_value(std::env::var("CI").ok().as_deref())
+}
+
+/// When the output is colored, from `--color`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Always color, even when the output is piped.
+    Always,
+    /// Never color.
+    Never,
+    /// Color only when stdout is a terminal and `NO_COLOR` is not set.
+    Auto,
+}
+
+impl fmt::Display for ColorChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorChoice::Always => write!(f, "always"),
+            ColorChoice::Never => write!(f, "never"),
+            ColorChoice::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            "auto" => Ok(ColorChoice::Auto),
+            _ => Err(format!("Error: Unknown color choice `{}`.", s)),
+        }
+    }
+}
+
+impl ColorChoice {
+    /// Decides whether the output is colored.
+    ///
+    /// # Arguments
+    ///
+    /// * `is_terminal` - Whether stdout is a terminal.
+    /// * `no_color` - The value of the `NO_COLOR` environment variable, or `None` if it is not set.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` for `Always`, `false` for `Never`, and for `Auto`, `true` if stdout is a
+    /// terminal and `NO_COLOR` is unset or empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert!(ColorChoice::Always.enabled(false, None));
+    /// assert!(!ColorChoice::Auto.enabled(true, Some("1")));
+    /// ```
+    pub fn enabled(self, is_terminal: bool, no_color: Option<&str>) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => is_terminal && no_color.is_none_or(str::is_empty),
+        }
+    }
+
+    /// Colors, or stops coloring, every string printed afterwards, from stdout and `NO_COLOR`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// ColorChoice::Never.apply();
+    /// assert_eq!("unused".red().to_string(), "unused");
+    /// ```
+    pub fn apply(self) {
+        let no_color = std::env::var("NO_COLOR").ok();
+        colored::control::set_override(
+            self.enabled(io::stdout().is_terminal(), no_color.as_deref()),
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use serde::Serialize;
+
+/// Warnings collected while analyzing a project (e.g., multiple lockfiles, a `tsc` failure or an
+/// invalid `cnp.toml`), rendered with the report in the chosen output format.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct Warnings(Vec<String>);
+
+impl Warnings {
+    /// Records a warning.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The warning message, without any `Warning:` prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut warnings = Warnings::default();
+    /// warnings.push("Multiple lockfiles detected (package-lock.json, yarn.lock).");
+    /// assert!(!warnings.is_empty());
+    /// ```
+    pub fn push(&mut self, message: impl Into<String>) {
+        self.0.push(message.into());
+    }
+
+    /// Appends every warning of another collection.
+    pub fn extend(&mut self, other: Warnings) {
+        self.0.extend(other.0);
+    }
+
+    /// Removes every collected warning, e.g. when warnings are silenced with `--no-warnings`.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Returns the number of collected warnings.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if no warning was collected.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the warning messages, in the order they were collected.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+}
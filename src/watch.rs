@@ -0,0 +1,160 @@
+use crate::cli::{Options, resolve_project_path, run};
+use crate::config::{
+    CONFIG_FILE_NAME, EXTENSIONS, IGNORE_FOLDERS, LENIENT_MANIFEST_PATHS, PACKAGE_JSON_PATH,
+};
+use crate::file_scanner::should_ignore;
+use colored::*;
+use notify::{Event, RecursiveMode, Watcher, recommended_watcher};
+use std::ffi::OsStr;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, channel};
+use std::time::Duration;
+
+/// How long the project must stay quiet after a change before the analysis is re-run.
+pub const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Checks whether a changed path should trigger a new analysis.
+///
+/// A change is relevant if it is outside the ignored folders (e.g., `node_modules`) and touches a
+/// scanned source file or one of the files the analysis reads (`package.json`, `cnp.toml`,
+/// `.cnpignore`).
+///
+/// # Arguments
+///
+/// * `root` - A reference to the `Path` of the watched project directory.
+/// * `path` - A reference to the `Path` of the changed file.
+///
+/// # Returns
+///
+/// Returns `true` if the change should trigger a new analysis, `false` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// let root = Path::new("/project");
+/// assert!(is_relevant_change(root, Path::new("/project/src/index.ts")));
+/// assert!(!is_relevant_change(root, Path::new("/project/node_modules/react/index.js")));
+/// ```
+pub fn is_relevant_change(root: &Path, path: &Path) -> bool {
+    if should_ignore(path.strip_prefix(root).unwrap_or(path), &IGNORE_FOLDERS).is_some() {
+        return false;
+    }
+
+    let file_name = path.file_name().and_then(OsStr::to_str).unwrap_or_default();
+    let extension = path.extension().and_then(OsStr::to_str).unwrap_or_default();
+    [PACKAGE_JSON_PATH, CONFIG_FILE_NAME, ".cnpignore"].contains(&file_name)
+        || LENIENT_MANIFEST_PATHS.contains(&file_name)
+        || EXTENSIONS.contains(&extension)
+}
+
+/// Calls `on_change` once per burst of relevant changes received from an event source.
+///
+/// After a relevant change, further events are drained until no event arrives for `debounce`, so
+/// a burst of writes (e.g., a formatter touching many files) triggers a single re-run. The loop
+/// ends when the event source is disconnected.
+///
+/// # Arguments
+///
+/// * `root` - A reference to the `Path` of the watched project directory.
+/// * `events` - The `Receiver` the changed paths are read from.
+/// * `debounce` - The quiet period that ends a burst of changes.
+/// * `on_change` - The callback run after each burst of relevant changes.
+///
+/// # Examples
+///
+/// ```
+/// let (sender, receiver) = channel();
+/// sender.send(PathBuf::from("/project/index.js")).unwrap();
+/// drop(sender);
+/// debounce_changes(Path::new("/project"), &receiver, WATCH_DEBOUNCE, || println!("changed"));
+/// ```
+pub fn debounce_changes(
+    root: &Path,
+    events: &Receiver<PathBuf>,
+    debounce: Duration,
+    mut on_change: impl FnMut(),
+) {
+    while let Ok(path) = events.recv() {
+        if !is_relevant_change(root, &path) {
+            continue;
+        }
+
+        loop {
+            match events.recv_timeout(debounce) {
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    on_change();
+                    return;
+                }
+            }
+        }
+
+        on_change();
+    }
+}
+
+/// Runs the analysis, then re-runs it on every relevant change of the first project directory.
+///
+/// The screen is cleared before each report. Unused dependencies are never removed in watch mode.
+///
+/// # Arguments
+///
+/// * `options` - A reference to the run `Options`.
+///
+/// # Returns
+///
+/// Returns the process exit code: `1` if the project directory cannot be watched. Otherwise the
+/// loop runs until the process is interrupted.
+///
+/// # Examples
+///
+/// ```
+/// let code = watch(&options);
+/// std::process::exit(code);
+/// ```
+pub fn watch(options: &Options) -> i32 {
+    let root = match resolve_project_path(&options.paths[0]) {
+        Ok(dir_path) => PathBuf::from(dir_path),
+        Err(err) => {
+            eprintln!("{}", err.red());
+            return 1;
+        }
+    };
+    let (sender, receiver) = channel();
+    let watcher = recommended_watcher(move |event: notify::Result<Event>| {
+        if let Ok(event) = event {
+            for path in event.paths {
+                let _ = sender.send(path);
+            }
+        }
+    });
+    let mut watcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("{}", format!("Error: Failed to start watcher: {}", e).red());
+            return 1;
+        }
+    };
+    if let Err(e) = watcher.watch(&root, RecursiveMode::Recursive) {
+        eprintln!(
+            "{}",
+            format!("Error: Failed to watch `{}`: {}", root.display(), e).red()
+        );
+        return 1;
+    }
+
+    let rerun = || {
+        // Clear the screen and move the cursor to the top-left corner
+        print!("\x1B[2J\x1B[1;1H");
+        run(options, &mut io::stdout(), &mut io::stderr());
+        println!("\n{}", "Watching for changes... (Ctrl+C to exit)".cyan());
+        let _ = io::stdout().flush();
+    };
+
+    rerun();
+    debounce_changes(&root, &receiver, WATCH_DEBOUNCE, rerun);
+
+    0
+}
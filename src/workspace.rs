@@ -0,0 +1,200 @@
+use crate::config::PACKAGE_JSON_PATH;
+use crate::dependency::{
+    declared_packages, lockfile_versions, project_lockfiles, read_package_json,
+};
+use glob::{Pattern, glob};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// The pnpm workspace definition file.
+pub const PNPM_WORKSPACE_PATH: &str = "pnpm-workspace.yaml";
+
+/// Collects the workspace package globs declared by a monorepo root.
+///
+/// Globs come from the `packages` list of `pnpm-workspace.yaml` (pnpm) and from the `workspaces`
+/// field of the root `package.json` (npm, yarn and bun), which is either a list of globs or an
+/// object with a `packages` list. Globs starting with `!` exclude directories.
+///
+/// # Arguments
+///
+/// * `root` - A string slice representing the path to the monorepo root.
+///
+/// # Returns
+///
+/// Returns a `Vec<String>` with the globs in declaration order, pnpm first. Returns an empty vector
+/// if the directory declares no workspaces.
+///
+/// # Examples
+///
+/// ```
+/// // pnpm-workspace.yaml: packages: ["packages/*", "!packages/legacy"]
+/// assert_eq!(workspace_globs("."), vec!["packages/*", "!packages/legacy"]);
+/// ```
+pub fn workspace_globs(root: &str) -> Vec<String> {
+    let mut globs = Vec::new();
+
+    let pnpm_workspace = fs::read_to_string(Path::new(root).join(PNPM_WORKSPACE_PATH))
+        .ok()
+        .and_then(|content| serde_yaml::from_str::<serde_yaml::Value>(&content).ok());
+    let pnpm_packages = pnpm_workspace
+        .as_ref()
+        .and_then(|yaml| yaml.get("packages")?.as_sequence());
+    globs.extend(
+        pnpm_packages
+            .into_iter()
+            .flatten()
+            .filter_map(|glob| glob.as_str())
+            .map(str::to_string),
+    );
+
+    let manifest_path = Path::new(root).join(PACKAGE_JSON_PATH);
+    if let Ok(package_json) = read_package_json(manifest_path.to_str().unwrap_or_default()) {
+        let workspaces = package_json.get("workspaces");
+        let packages = workspaces
+            .and_then(Value::as_array)
+            .or_else(|| workspaces?.get("packages")?.as_array());
+        globs.extend(
+            packages
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string),
+        );
+    }
+
+    globs
+}
+
+/// Resolves the workspace globs of a monorepo root to package directories.
+///
+/// Only directories containing a `package.json` are kept, and directories matched by a `!` glob
+/// are excluded. `node_modules` directories never count as workspace packages.
+///
+/// # Arguments
+///
+/// * `root` - A string slice representing the path to the monorepo root.
+///
+/// # Returns
+///
+/// Returns a sorted `Vec<String>` of the workspace package directories, joined to `root`. Returns
+/// an empty vector if the directory declares no workspaces.
+///
+/// # Examples
+///
+/// ```
+/// // pnpm-workspace.yaml: packages: ["packages/*"]
+/// assert_eq!(workspace_packages("."), vec!["./packages/api", "./packages/web"]);
+/// ```
+pub fn workspace_packages(root: &str) -> Vec<String> {
+    let globs = workspace_globs(root);
+    let (excludes, includes): (Vec<&String>, Vec<&String>) =
+        globs.iter().partition(|glob| glob.starts_with('!'));
+    let excludes: Vec<Pattern> = excludes
+        .iter()
+        .filter_map(|glob| Pattern::new(normalize_glob(&glob[1..])).ok())
+        .collect();
+
+    let mut packages = BTreeSet::new();
+    for include in includes {
+        let pattern = format!(
+            "{}/{}",
+            Pattern::escape(root.trim_end_matches('/')),
+            normalize_glob(include)
+        );
+        let Ok(paths) = glob(&pattern) else {
+            continue;
+        };
+
+        for path in paths.flatten() {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            let is_excluded = excludes
+                .iter()
+                .any(|exclude| exclude.matches_path(relative));
+            let in_node_modules = relative
+                .components()
+                .any(|component| component.as_os_str() == "node_modules");
+            if path.join(PACKAGE_JSON_PATH).is_file() && !is_excluded && !in_node_modules {
+                packages.insert(path.display().to_string());
+            }
+        }
+    }
+
+    packages.into_iter().collect()
+}
+
+/// Finds the monorepo root a project directory is a workspace package of.
+///
+/// Ancestors are searched from the closest one, and the first whose workspace globs (see
+/// [`workspace_packages`]) match the directory is the root. A directory is never its own root.
+///
+/// # Arguments
+///
+/// * `dir_path` - A string slice representing the path to the project directory.
+///
+/// # Returns
+///
+/// Returns `Some(String)` with the path to the monorepo root, or `None` if the directory is not a
+/// workspace package.
+///
+/// # Examples
+///
+/// ```
+/// // package.json: { "workspaces": ["packages/*"] }
+/// assert_eq!(workspace_root("./packages/web"), Some("/repo".to_string()));
+/// ```
+pub fn workspace_root(dir_path: &str) -> Option<String> {
+    let dir = fs::canonicalize(dir_path).ok()?;
+    dir.ancestors().skip(1).find_map(|ancestor| {
+        let root = ancestor.to_str()?;
+        workspace_packages(root)
+            .iter()
+            .any(|package| fs::canonicalize(package).is_ok_and(|package| package == dir))
+            .then(|| root.to_string())
+    })
+}
+
+/// Collects the packages a monorepo root makes available to its workspace packages.
+///
+/// With hoisted installs, a package declared at the root, or installed at the root by its lockfile,
+/// can be imported by every workspace package without being declared there. The packages declared in
+/// the root `package.json` always count, and so do the packages of the root lockfile, unless it is
+/// a `pnpm-lock.yaml`: pnpm links each package's own dependencies only. Nothing is read from the
+/// lockfiles when the root holds several.
+///
+/// # Arguments
+///
+/// * `root` - A string slice representing the path to the monorepo root.
+///
+/// # Returns
+///
+/// Returns a `HashSet<String>` with the available package names. Returns an empty set if the root
+/// has neither a readable `package.json` nor a lockfile.
+///
+/// # Examples
+///
+/// ```
+/// // package.json: { "devDependencies": { "lodash": "^4.17.21" } }
+/// assert!(hoisted_packages("/repo").contains("lodash"));
+/// ```
+pub fn hoisted_packages(root: &str) -> HashSet<String> {
+    let manifest_path = Path::new(root).join(PACKAGE_JSON_PATH);
+    let mut packages = read_package_json(manifest_path.to_str().unwrap_or_default())
+        .map(|package_json| declared_packages(&package_json))
+        .unwrap_or_default();
+
+    if let [lockfile] = project_lockfiles(root, None)[..]
+        && lockfile != "pnpm-lock.yaml"
+        && let Ok(content) = fs::read_to_string(Path::new(root).join(lockfile))
+    {
+        packages.extend(lockfile_versions(lockfile, &content).into_keys());
+    }
+
+    packages
+}
+
+/// Strips the `./` prefix and trailing `/` that workspace globs may carry.
+fn normalize_glob(glob: &str) -> &str {
+    glob.trim_start_matches("./").trim_end_matches('/')
+}
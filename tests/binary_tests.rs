@@ -0,0 +1,131 @@
+//! Runs the built `cnp` binary in a separate process, for behavior depending on the process
+//! environment (`PATH`, terminals) that the unit tests in `src/tests` cannot change safely.
+#![cfg(all(unix, feature = "cli"))]
+
+use serde_json::Value;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// Creates a TypeScript project importing `analytics`, and a `tsc` stub running the given script
+/// (`tsc --version` always succeeds) in a `bin` directory of its own.
+fn create_typescript_project(script: &str) -> (TempDir, TempDir) {
+    let project = TempDir::new().unwrap();
+    fs::write(
+        project.path().join("package.json"),
+        r#"{ "dependencies": { "analytics": "^0.8.0" } }"#,
+    )
+    .unwrap();
+    fs::write(project.path().join("tsconfig.json"), "{}").unwrap();
+    fs::write(
+        project.path().join("index.ts"),
+        "import analytics from 'analytics';\nanalytics();\n",
+    )
+    .unwrap();
+
+    let bin = TempDir::new().unwrap();
+    let tsc = bin.path().join("tsc");
+    fs::write(
+        &tsc,
+        format!(
+            "#!/bin/sh\nif [ \"$1\" = \"--version\" ]; then echo 5.0.0; exit 0; fi\n{}\n",
+            script
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&tsc, fs::Permissions::from_mode(0o755)).unwrap();
+    (project, bin)
+}
+
+/// Runs `cnp` with the given arguments and the `bin` directory first on the `PATH`.
+fn run_cnp(bin: &Path, args: &[&str]) -> Output {
+    let path = format!(
+        "{}:{}",
+        bin.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    Command::new(env!("CARGO_BIN_EXE_cnp"))
+        .args(args)
+        .env("PATH", path)
+        .env_remove("CI")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_tsc_timeout_falls_back_to_import_matching() {
+    let (project, bin) = create_typescript_project("sleep 5");
+
+    let output = run_cnp(
+        bin.path(),
+        &[
+            project.path().to_str().unwrap(),
+            "--tsc-timeout",
+            "1",
+            "--format",
+            "json",
+        ],
+    );
+
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let warnings = report["warnings"].to_string();
+    assert!(
+        warnings.contains("tsc did not finish within 1s. Matching TypeScript imports like JavaScript ones instead."),
+        "{}",
+        warnings
+    );
+    assert_eq!(report["used"], serde_json::json!(["analytics"]));
+}
+
+#[test]
+fn test_tsc_diagnostics_are_cached_between_runs() {
+    let runs = TempDir::new().unwrap();
+    let log = runs.path().join("runs.log");
+    let (project, bin) = create_typescript_project(&format!("echo run >> {}", log.display()));
+    let args = [project.path().to_str().unwrap(), "--format", "json"];
+    let run_count = || fs::read_to_string(&log).unwrap_or_default().lines().count();
+
+    assert!(run_cnp(bin.path(), &args).status.success());
+    assert_eq!(run_count(), 1);
+
+    // Unchanged TypeScript files reuse the cached diagnostics
+    assert!(run_cnp(bin.path(), &args).status.success());
+    assert_eq!(run_count(), 1);
+
+    // --no-cache always runs tsc
+    run_cnp(bin.path(), &[args[0], "--format", "json", "--no-cache"]);
+    assert_eq!(run_count(), 2);
+}
+
+#[test]
+fn test_color_flag_controls_ansi_codes_when_piped() {
+    let project = TempDir::new().unwrap();
+    fs::write(
+        project.path().join("package.json"),
+        r#"{ "dependencies": { "react": "^18.0.0" } }"#,
+    )
+    .unwrap();
+    fs::write(
+        project.path().join("index.js"),
+        "import React from 'react';",
+    )
+    .unwrap();
+    let run = |color: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_cnp"))
+            .args([project.path().to_str().unwrap(), "--no-tsc", "--dry-run"])
+            .args(["--color", color])
+            .env_remove("NO_COLOR")
+            .env_remove("CLICOLOR_FORCE")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert!(run("always").contains("\x1b["));
+    assert!(!run("never").contains("\x1b["));
+    // Piped output is not a terminal
+    assert!(!run("auto").contains("\x1b["));
+}